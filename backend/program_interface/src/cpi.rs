@@ -0,0 +1,200 @@
+//! Membership checks for other programs, e.g. an event ticketing program
+//! letting in only registered users of this program.
+//!
+//! [check_member] reads the user PDA passed to the calling program directly,
+//! [verify_member] asks the anti-loneliness program itself with a
+//! [VerifyMember](crate::instruction::ProgramInstruction::VerifyMember) CPI.
+//! The user PDA of a wallet is found with [find_user_pda](crate::pda::find_user_pda).
+
+use {
+    crate::{
+        error::{Error, ErrorCause},
+        instruction::{new_instruction, ProgramInstruction, VerifyMemberInstructionData},
+        pda,
+        state::UserProfile,
+    },
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, instruction::AccountMeta,
+        program::invoke, program_error::ProgramError, pubkey::Pubkey,
+    },
+};
+
+/// Checks that `user_pda` is the user PDA of `user` owned by the program `program_id`,
+/// the user is not flagged and has all the `required_verifications` flags.
+/// Fails with [ErrorCause::AccountNotFound] if the account is missing or closed.
+pub fn check_member(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_pda: &AccountInfo,
+    required_verifications: u64,
+) -> Result<UserProfile, Error> {
+    if user_pda.lamports() == 0 || user_pda.data_is_empty() {
+        return Err(Error::new(ErrorCause::AccountNotFound).with_account(user_pda.key));
+    }
+    if user_pda.owner != program_id {
+        return Err(Error::new(ErrorCause::InvalidAccountOwner)
+            .with_account(user_pda.key)
+            .with_message(format!("Owned by {}", user_pda.owner)));
+    }
+
+    let data = user_pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(user_pda.key))?;
+    let profile = UserProfile::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(user_pda.key))?;
+
+    let expected_pda = pda::create_user_pda(program_id, user, profile.bump_seed);
+    if profile.owner != *user || expected_pda != Ok(*user_pda.key) {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(user_pda.key)
+            .with_message(format!("Not a PDA of {}", user)));
+    }
+    if profile.flagged {
+        return Err(Error::new(ErrorCause::Flagged).with_account(user_pda.key));
+    }
+    if !profile.is_verified(required_verifications) {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(user_pda.key)
+            .with_message(format!(
+                "Missing verifications: {:#b}",
+                required_verifications & !profile.verifications
+            )));
+    }
+    Ok(profile)
+}
+
+/// Invokes the [VerifyMember](crate::instruction::ProgramInstruction::VerifyMember)
+/// instruction of the program `program_id`, passed to the caller as `program`.
+/// The CPI fails, and with it the calling instruction, if `user` is not a member
+/// in good standing with all the `required_verifications` flags.
+pub fn verify_member<'a>(
+    program_id: &Pubkey,
+    program: &AccountInfo<'a>,
+    user: &AccountInfo<'a>,
+    user_pda: &AccountInfo<'a>,
+    required_verifications: u64,
+) -> ProgramResult {
+    if program.key != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    // The PDA is not derived here, a wrong one is rejected by the program.
+    let instruction = new_instruction(
+        program_id,
+        &ProgramInstruction::VerifyMember(VerifyMemberInstructionData {
+            required_verifications,
+        }),
+        vec![
+            AccountMeta::new_readonly(*user.key, false),
+            AccountMeta::new_readonly(*user_pda.key, false),
+        ],
+    );
+    invoke(
+        &instruction,
+        &[user.clone(), user_pda.clone(), program.clone()],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{VERIFIED_IN_PERSON, VERIFIED_ONBOARDING};
+
+    fn user_profile(program_id: &Pubkey, user: &Pubkey) -> (Pubkey, UserProfile) {
+        let (key, bump_seed) = pda::find_user_pda(program_id, user);
+        let profile = UserProfile::new(*user, bump_seed, "paulinka".to_string(), 42);
+        (key, profile)
+    }
+
+    fn check(
+        program_id: &Pubkey,
+        user: &Pubkey,
+        key: &Pubkey,
+        owner: &Pubkey,
+        profile: Option<&UserProfile>,
+        required_verifications: u64,
+    ) -> Result<UserProfile, ErrorCause> {
+        let mut data = vec![0u8; pda::USER_PDA_SIZE as usize];
+        let mut lamports = 1_000_000;
+        match profile {
+            Some(profile) => profile.pack(&mut data).unwrap(),
+            None => {
+                data.clear();
+                lamports = 0;
+            }
+        }
+        let account =
+            AccountInfo::new(key, false, false, &mut lamports, &mut data, owner, false, 0);
+        check_member(program_id, user, &account, required_verifications).map_err(|e| *e.cause())
+    }
+
+    #[test]
+    fn check_members() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let (key, mut profile) = user_profile(&program_id, &user);
+
+        let checked = check(&program_id, &user, &key, &program_id, Some(&profile), 0);
+        assert_eq!(Ok(profile.clone()), checked);
+        assert_eq!(
+            Err(ErrorCause::AccountNotFound),
+            check(&program_id, &user, &key, &program_id, None, 0)
+        );
+        assert_eq!(
+            Err(ErrorCause::InvalidAccountOwner),
+            check(
+                &program_id,
+                &user,
+                &key,
+                &Pubkey::new_unique(),
+                Some(&profile),
+                0
+            )
+        );
+
+        profile.verifications = VERIFIED_ONBOARDING;
+        let checked = check(
+            &program_id,
+            &user,
+            &key,
+            &program_id,
+            Some(&profile),
+            VERIFIED_ONBOARDING,
+        );
+        assert!(checked.is_ok());
+        let checked = check(
+            &program_id,
+            &user,
+            &key,
+            &program_id,
+            Some(&profile),
+            VERIFIED_ONBOARDING | VERIFIED_IN_PERSON,
+        );
+        assert_eq!(Err(ErrorCause::Unauthorized), checked);
+
+        profile.flagged = true;
+        assert_eq!(
+            Err(ErrorCause::Flagged),
+            check(&program_id, &user, &key, &program_id, Some(&profile), 0)
+        );
+    }
+
+    #[test]
+    fn check_member_of_other_wallet() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let other_user = Pubkey::new_unique();
+        let (key, profile) = user_profile(&program_id, &other_user);
+
+        // A valid PDA, but of another user.
+        assert_eq!(
+            Err(ErrorCause::InvalidPda),
+            check(&program_id, &user, &key, &program_id, Some(&profile), 0)
+        );
+        // The PDA of the user with the profile of another one.
+        let (key, _) = pda::find_user_pda(&program_id, &user);
+        assert_eq!(
+            Err(ErrorCause::InvalidPda),
+            check(&program_id, &user, &key, &program_id, Some(&profile), 0)
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use solana_program::pubkey::Pubkey;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
+use std::fmt::Formatter;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Error {
+    cause: ErrorCause,
+    account_key: Option<Pubkey>,
+    message: Option<String>,
+}
+
+/// First code of [ErrorCause]. The system program returns its own custom errors
+/// from the low codes, they are reported at our instruction when it fails in a CPI.
+pub const ERROR_CODE_BASE: u32 = 6000;
+
+/// Cause of the error, returned by the program as `ProgramError::Custom(cause)`.
+/// The codes are part of the program interface, never change or reuse them.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[repr(u32)]
+pub enum ErrorCause {
+    #[num_enum(default)]
+    GeneralError = 6000,
+    InvalidPubKey = 6001,
+    AccountNotFound = 6002,
+    AccountAlreadyInitialized = 6003,
+    MissingRequiredSignature = 6004,
+    AccountNotWritable = 6005,
+    InvalidAccountOwner = 6006,
+    InvalidAccountData = 6007,
+    InvalidPda = 6008,
+    InvalidBumpSeed = 6009,
+    InvalidSystemProgram = 6010,
+    InvalidInstructionData = 6011,
+    InvalidProfileData = 6012,
+    AccountDataTooSmall = 6013,
+    InsufficientRent = 6014,
+    InvalidAccountSize = 6015,
+    InvalidState = 6016,
+    Unauthorized = 6017,
+    UsernameTaken = 6018,
+    ProgramPaused = 6019,
+    SessionExpired = 6020,
+    Blocked = 6021,
+    Flagged = 6022,
+}
+
+impl Error {
+    pub fn new(cause: ErrorCause) -> Self {
+        Error {
+            cause,
+            account_key: None,
+            message: None,
+        }
+    }
+
+    pub fn with_account(mut self, account_key: &Pubkey) -> Self {
+        self.account_key = Some(*account_key);
+        self
+    }
+
+    pub fn with_message(mut self, message: String) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    pub fn cause(&self) -> &ErrorCause {
+        &self.cause
+    }
+
+    pub fn account_key(&self) -> Option<Pubkey> {
+        self.account_key
+    }
+
+    /// Finds the account of the last error logged by the program.
+    /// `logs` are the log messages of a failed transaction.
+    pub fn account_key_from_logs(logs: &[String]) -> Option<Pubkey> {
+        logs.iter().rev().find_map(|log| {
+            log.strip_prefix("Program log: Account: ")
+                .and_then(|rest| rest.split(" | ").next())
+                .and_then(|key| key.parse().ok())
+        })
+    }
+
+    fn get_error_msg(&self) -> String {
+        let get_account_key = || -> String {
+            self.account_key
+                .map(|k| k.to_string())
+                .unwrap_or("<NO DATA>".to_string())
+        };
+
+        match &self.cause {
+            ErrorCause::GeneralError => {
+                format!(
+                    "GeneralError: {}",
+                    self.message.clone().unwrap_or("Unknown".to_string())
+                )
+            }
+            ErrorCause::InvalidPubKey => {
+                format!(
+                    "Account: {} | InvalidPubKey: {}",
+                    get_account_key(),
+                    self.message.clone().unwrap_or("Invalid".to_string())
+                )
+            }
+            ErrorCause::AccountNotFound => {
+                format!("Account: {} | AccountNotFound", get_account_key(),)
+            }
+            ErrorCause::AccountAlreadyInitialized => {
+                format!("Account: {} | AccountAlreadyInitialized", get_account_key(),)
+            }
+            ErrorCause::MissingRequiredSignature
+            | ErrorCause::AccountNotWritable
+            | ErrorCause::InvalidAccountOwner
+            | ErrorCause::InvalidAccountData
+            | ErrorCause::InvalidPda
+            | ErrorCause::InvalidBumpSeed
+            | ErrorCause::InvalidSystemProgram
+            | ErrorCause::InvalidInstructionData
+            | ErrorCause::InvalidProfileData
+            | ErrorCause::AccountDataTooSmall
+            | ErrorCause::InsufficientRent
+            | ErrorCause::InvalidAccountSize
+            | ErrorCause::InvalidState
+            | ErrorCause::Unauthorized
+            | ErrorCause::UsernameTaken
+            | ErrorCause::ProgramPaused
+            | ErrorCause::SessionExpired
+            | ErrorCause::Blocked
+            | ErrorCause::Flagged => match &self.message {
+                Some(message) => {
+                    format!(
+                        "Account: {} | {:?}: {}",
+                        get_account_key(),
+                        self.cause,
+                        message
+                    )
+                }
+                None => format!("Account: {} | {:?}", get_account_key(), self.cause),
+            },
+        }
+    }
+}
+
+impl From<Error> for ProgramError {
+    fn from(value: Error) -> Self {
+        // Only the cause is carried by the program error,
+        // log the account and the message while we still have them.
+        msg!("{}", value.get_error_msg());
+        ProgramError::Custom(value.cause.into())
+    }
+}
+
+impl num_traits::FromPrimitive for Error {
+    fn from_i64(n: i64) -> Option<Self> {
+        Some(Error {
+            cause: ErrorCause::from(u32::from_i64(n).unwrap_or(0)),
+            account_key: None,
+            message: None,
+        })
+    }
+
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(Error {
+            cause: ErrorCause::from(u32::from_u64(n).unwrap_or(0)),
+            account_key: None,
+            message: None,
+        })
+    }
+}
+
+impl<T> DecodeError<T> for Error {
+    fn type_of() -> &'static str {
+        "MyHugeError"
+    }
+}
+
+impl PrintProgramError for Error {
+    fn print<E>(&self)
+    where
+        E: 'static
+            + std::error::Error
+            + DecodeError<E>
+            + PrintProgramError
+            + num_traits::FromPrimitive,
+    {
+        msg!("{}", self.get_error_msg())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+
+    fn description(&self) -> &str {
+        "description() is deprecated; use Display"
+    }
+
+    fn cause(&self) -> Option<&dyn std::error::Error> {
+        None
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.get_error_msg())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_cause_codes() {
+        // The codes are decoded by the clients, make sure they don't change.
+        let causes = [
+            ErrorCause::GeneralError,
+            ErrorCause::InvalidPubKey,
+            ErrorCause::AccountNotFound,
+            ErrorCause::AccountAlreadyInitialized,
+            ErrorCause::MissingRequiredSignature,
+            ErrorCause::AccountNotWritable,
+            ErrorCause::InvalidAccountOwner,
+            ErrorCause::InvalidAccountData,
+            ErrorCause::InvalidPda,
+            ErrorCause::InvalidBumpSeed,
+            ErrorCause::InvalidSystemProgram,
+            ErrorCause::InvalidInstructionData,
+            ErrorCause::InvalidProfileData,
+            ErrorCause::AccountDataTooSmall,
+            ErrorCause::InsufficientRent,
+            ErrorCause::InvalidAccountSize,
+            ErrorCause::InvalidState,
+            ErrorCause::Unauthorized,
+            ErrorCause::UsernameTaken,
+            ErrorCause::ProgramPaused,
+            ErrorCause::SessionExpired,
+            ErrorCause::Blocked,
+            ErrorCause::Flagged,
+        ];
+        for (code, cause) in (ERROR_CODE_BASE..).zip(causes) {
+            let program_error: ProgramError = Error::new(cause).into();
+            assert_eq!(ProgramError::Custom(code), program_error);
+            assert_eq!(cause, ErrorCause::from(code));
+        }
+        assert_eq!(ErrorCause::GeneralError, ErrorCause::from(1));
+        assert_eq!(ErrorCause::GeneralError, ErrorCause::from(u32::MAX));
+    }
+
+    #[test]
+    fn account_key_from_logs() {
+        let account_key = Pubkey::new_unique();
+        let error = Error::new(ErrorCause::AccountAlreadyInitialized).with_account(&account_key);
+        let logs = vec![
+            "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+            format!("Program log: {}", error.get_error_msg()),
+            "Program 11111111111111111111111111111111 failed: custom program error: 0x3"
+                .to_string(),
+        ];
+        assert_eq!(Some(account_key), Error::account_key_from_logs(&logs));
+
+        let error = Error::new(ErrorCause::GeneralError);
+        let logs = vec![format!("Program log: {}", error.get_error_msg())];
+        assert_eq!(None, Error::account_key_from_logs(&logs));
+    }
+}
@@ -0,0 +1,524 @@
+//! Events emitted by the program on every successful instruction,
+//! except the read-only `VerifyMember`.
+//!
+//! An event is logged with `sol_log_data` as two fields:
+//! the 8 bytes discriminator of the event and its Borsh encoded body.
+//! Indexers find them in the transaction logs as `Program data: <base64> <base64>`.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{log::sol_log_data, msg, program_error::ProgramError, pubkey::Pubkey},
+};
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct UserInitialized {
+    pub owner: Pubkey,
+    pub user_pda: Pubkey,
+    pub username: String,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct AccountClosed {
+    pub owner: Pubkey,
+    pub user_pda: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ProfileUpdated {
+    pub owner: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct UserPdaResized {
+    pub owner: Pubkey,
+    pub new_size: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct OwnershipTransferred {
+    pub owner: Pubkey,
+    pub new_owner: Pubkey,
+    /// User PDA of the new owner, the profile was moved to.
+    pub user_pda: Pubkey,
+}
+
+/// Emitted when a connection is requested, accepted, rejected or removed.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ConnectionChanged {
+    pub requester: Pubkey,
+    pub recipient: Pubkey,
+    /// User who signed the instruction.
+    pub changed_by: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct UsernameClaimed {
+    pub owner: Pubkey,
+    pub username: String,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub max_user_pda_size: u64,
+    pub registration_fee: u64,
+    pub deposit: u64,
+    pub attestation_authority: Pubkey,
+    pub referral_reward: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct DepositSlashed {
+    pub owner: Pubkey,
+    /// Lamports moved from the user PDA to the config PDA.
+    pub lamports: u64,
+}
+
+/// Emitted when the admin flags or unflags a user.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct FlagChanged {
+    pub owner: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct SessionCreated {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub expires_at_slot: u64,
+    pub allowed_instructions: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct SessionRevoked {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    /// Owner or delegate who signed the instruction.
+    pub revoked_by: Pubkey,
+}
+
+/// Emitted when an endorsement is given or revoked.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct EndorsementChanged {
+    pub endorser: Pubkey,
+    pub endorsed: Pubkey,
+    /// Endorsement count of the endorsed user after the change.
+    pub endorsement_count: u64,
+}
+
+/// Emitted when a user is blocked or unblocked.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct BlockChanged {
+    pub owner: Pubkey,
+    pub user: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct GroupCreated {
+    pub group: Pubkey,
+    pub admin: Pubkey,
+    pub name: String,
+}
+
+/// Emitted when a user joins, leaves or is kicked from a group.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct MembershipChanged {
+    pub group: Pubkey,
+    pub member: Pubkey,
+    /// Member count of the group after the change.
+    pub member_count: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct EventCreated {
+    pub event: Pubkey,
+    pub organizer: Pubkey,
+    pub starts_at: i64,
+    pub capacity: u32,
+}
+
+/// Emitted when a user RSVPs to an event or cancels the RSVP.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RsvpChanged {
+    pub event: Pubkey,
+    pub attendee: Pubkey,
+    /// Attendee count of the event after the change.
+    pub attendee_count: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct EventClosed {
+    pub event: Pubkey,
+    pub attendee_count: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct EscrowCreated {
+    pub escrow: Pubkey,
+    pub proposer: Pubkey,
+    pub partner: Pubkey,
+    pub stake: u64,
+    pub deadline_slot: u64,
+}
+
+/// Emitted when a participant joins the escrow or checks in.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct EscrowChanged {
+    pub escrow: Pubkey,
+    pub participant: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct EscrowSettled {
+    pub escrow: Pubkey,
+    /// Lamports returned to the participants who checked in.
+    pub refunded: u64,
+    /// Lamports of the no-shows moved to the charity or the other participant.
+    pub forfeited: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct GuardiansSet {
+    pub owner: Pubkey,
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RecoveryProposed {
+    pub recovery: Pubkey,
+    pub user_pda: Pubkey,
+    pub guardian: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+/// Emitted when a guardian approves the recovery or the recovery is cancelled.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct RecoveryChanged {
+    pub recovery: Pubkey,
+    pub user_pda: Pubkey,
+    /// User who signed the instruction.
+    pub changed_by: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Attested {
+    pub owner: Pubkey,
+    pub authority: Pubkey,
+    /// All the verification flags of the user after the change.
+    pub verifications: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Referred {
+    pub referrer: Pubkey,
+    pub user: Pubkey,
+    /// Lamports paid to the referrer from the config PDA.
+    pub reward: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct PollCreated {
+    pub poll: Pubkey,
+    pub creator: Pubkey,
+    /// Group whose members can vote, if any.
+    pub group: Option<Pubkey>,
+    pub question: String,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Voted {
+    pub poll: Pubkey,
+    pub voter: Pubkey,
+    pub option: u8,
+    /// Votes for the option after this one.
+    pub votes: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct PollClosed {
+    pub poll: Pubkey,
+    /// Final votes for each of the options.
+    pub tallies: Vec<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct VoteClosed {
+    pub poll: Pubkey,
+    pub voter: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Withdrawn {
+    pub admin: Pubkey,
+    pub recipient: Pubkey,
+    /// Lamports moved from the config PDA.
+    pub lamports: u64,
+}
+
+/// Events emitted by the program.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProgramEvent {
+    UserInitialized(UserInitialized),
+    AccountClosed(AccountClosed),
+    ProfileUpdated(ProfileUpdated),
+    UserPdaResized(UserPdaResized),
+    ConnectionRequested(ConnectionChanged),
+    ConnectionAccepted(ConnectionChanged),
+    ConnectionRejected(ConnectionChanged),
+    ConnectionRemoved(ConnectionChanged),
+    UsernameClaimed(UsernameClaimed),
+    /// Emitted by InitializeConfig, UpdateConfig and TransferAuthority.
+    ConfigUpdated(ConfigUpdated),
+    /// Also emitted when the session is renewed.
+    SessionCreated(SessionCreated),
+    SessionRevoked(SessionRevoked),
+    Endorsed(EndorsementChanged),
+    EndorsementRevoked(EndorsementChanged),
+    UserBlocked(BlockChanged),
+    UserUnblocked(BlockChanged),
+    GroupCreated(GroupCreated),
+    MemberJoined(MembershipChanged),
+    MemberLeft(MembershipChanged),
+    MemberKicked(MembershipChanged),
+    EventCreated(EventCreated),
+    Rsvped(RsvpChanged),
+    RsvpCancelled(RsvpChanged),
+    EventClosed(EventClosed),
+    DepositSlashed(DepositSlashed),
+    EscrowCreated(EscrowCreated),
+    EscrowJoined(EscrowChanged),
+    CheckedIn(EscrowChanged),
+    EscrowSettled(EscrowSettled),
+    /// Also emitted when a recovery is executed.
+    OwnershipTransferred(OwnershipTransferred),
+    GuardiansSet(GuardiansSet),
+    RecoveryProposed(RecoveryProposed),
+    RecoveryApproved(RecoveryChanged),
+    RecoveryCancelled(RecoveryChanged),
+    /// Emitted both when the verification flags are set and cleared.
+    Attested(Attested),
+    /// Emitted after UserInitialized when the new user has a referrer.
+    Referred(Referred),
+    PollCreated(PollCreated),
+    Voted(Voted),
+    PollClosed(PollClosed),
+    Withdrawn(Withdrawn),
+    UserFlagged(FlagChanged),
+    UserUnflagged(FlagChanged),
+    VoteClosed(VoteClosed),
+}
+
+impl ProgramEvent {
+    /// Tags the event in the logs, never change the existing ones.
+    pub fn discriminator(&self) -> [u8; 8] {
+        match self {
+            Self::UserInitialized(_) => *b"usrinitd",
+            Self::AccountClosed(_) => *b"usrclosd",
+            Self::ProfileUpdated(_) => *b"profupdt",
+            Self::UserPdaResized(_) => *b"pdaresiz",
+            Self::ConnectionRequested(_) => *b"connreqd",
+            Self::ConnectionAccepted(_) => *b"connaccd",
+            Self::ConnectionRejected(_) => *b"connrejd",
+            Self::ConnectionRemoved(_) => *b"connremd",
+            Self::UsernameClaimed(_) => *b"usrnclmd",
+            Self::ConfigUpdated(_) => *b"cfgupdtd",
+            Self::SessionCreated(_) => *b"sesncrtd",
+            Self::SessionRevoked(_) => *b"sesnrvkd",
+            Self::Endorsed(_) => *b"endorsed",
+            Self::EndorsementRevoked(_) => *b"endrsrvk",
+            Self::UserBlocked(_) => *b"usrblckd",
+            Self::UserUnblocked(_) => *b"usrunblk",
+            Self::GroupCreated(_) => *b"grpcrtd_",
+            Self::MemberJoined(_) => *b"grpjoind",
+            Self::MemberLeft(_) => *b"grpleftm",
+            Self::MemberKicked(_) => *b"grpkickd",
+            Self::EventCreated(_) => *b"evtcrtd_",
+            Self::Rsvped(_) => *b"evtrsvpd",
+            Self::RsvpCancelled(_) => *b"evtrsvcn",
+            Self::EventClosed(_) => *b"evtclosd",
+            Self::DepositSlashed(_) => *b"dpstslsh",
+            Self::EscrowCreated(_) => *b"escrcrtd",
+            Self::EscrowJoined(_) => *b"escrjoin",
+            Self::CheckedIn(_) => *b"escrchkd",
+            Self::EscrowSettled(_) => *b"escrstld",
+            Self::OwnershipTransferred(_) => *b"ownrtrsf",
+            Self::GuardiansSet(_) => *b"grdnsset",
+            Self::RecoveryProposed(_) => *b"rcvrprpd",
+            Self::RecoveryApproved(_) => *b"rcvrappr",
+            Self::RecoveryCancelled(_) => *b"rcvrcncl",
+            Self::Attested(_) => *b"attested",
+            Self::Referred(_) => *b"referred",
+            Self::PollCreated(_) => *b"pollcrtd",
+            Self::Voted(_) => *b"pollvotd",
+            Self::PollClosed(_) => *b"pollclsd",
+            Self::Withdrawn(_) => *b"withdrwn",
+            Self::UserFlagged(_) => *b"usrflagd",
+            Self::UserUnflagged(_) => *b"usrunflg",
+            Self::VoteClosed(_) => *b"pollvtcl",
+        }
+    }
+
+    /// Encodes the body of the event.
+    pub fn pack(&self) -> Result<Vec<u8>, borsh::io::Error> {
+        match self {
+            Self::UserInitialized(event) => borsh::to_vec(event),
+            Self::AccountClosed(event) => borsh::to_vec(event),
+            Self::ProfileUpdated(event) => borsh::to_vec(event),
+            Self::UserPdaResized(event) => borsh::to_vec(event),
+            Self::ConnectionRequested(event)
+            | Self::ConnectionAccepted(event)
+            | Self::ConnectionRejected(event)
+            | Self::ConnectionRemoved(event) => borsh::to_vec(event),
+            Self::UsernameClaimed(event) => borsh::to_vec(event),
+            Self::ConfigUpdated(event) => borsh::to_vec(event),
+            Self::SessionCreated(event) => borsh::to_vec(event),
+            Self::SessionRevoked(event) => borsh::to_vec(event),
+            Self::Endorsed(event) | Self::EndorsementRevoked(event) => borsh::to_vec(event),
+            Self::UserBlocked(event) | Self::UserUnblocked(event) => borsh::to_vec(event),
+            Self::GroupCreated(event) => borsh::to_vec(event),
+            Self::MemberJoined(event) | Self::MemberLeft(event) | Self::MemberKicked(event) => {
+                borsh::to_vec(event)
+            }
+            Self::EventCreated(event) => borsh::to_vec(event),
+            Self::Rsvped(event) | Self::RsvpCancelled(event) => borsh::to_vec(event),
+            Self::EventClosed(event) => borsh::to_vec(event),
+            Self::DepositSlashed(event) => borsh::to_vec(event),
+            Self::EscrowCreated(event) => borsh::to_vec(event),
+            Self::EscrowJoined(event) | Self::CheckedIn(event) => borsh::to_vec(event),
+            Self::EscrowSettled(event) => borsh::to_vec(event),
+            Self::OwnershipTransferred(event) => borsh::to_vec(event),
+            Self::GuardiansSet(event) => borsh::to_vec(event),
+            Self::RecoveryProposed(event) => borsh::to_vec(event),
+            Self::RecoveryApproved(event) | Self::RecoveryCancelled(event) => borsh::to_vec(event),
+            Self::Attested(event) => borsh::to_vec(event),
+            Self::Referred(event) => borsh::to_vec(event),
+            Self::PollCreated(event) => borsh::to_vec(event),
+            Self::Voted(event) => borsh::to_vec(event),
+            Self::PollClosed(event) => borsh::to_vec(event),
+            Self::Withdrawn(event) => borsh::to_vec(event),
+            Self::UserFlagged(event) | Self::UserUnflagged(event) => borsh::to_vec(event),
+            Self::VoteClosed(event) => borsh::to_vec(event),
+        }
+    }
+
+    /// Decodes an event from its discriminator and body.
+    pub fn unpack(discriminator: &[u8], mut data: &[u8]) -> Result<Self, ProgramError> {
+        let data = &mut data;
+        let event = match discriminator {
+            b"usrinitd" => UserInitialized::deserialize(data).map(Self::UserInitialized),
+            b"usrclosd" => AccountClosed::deserialize(data).map(Self::AccountClosed),
+            b"profupdt" => ProfileUpdated::deserialize(data).map(Self::ProfileUpdated),
+            b"pdaresiz" => UserPdaResized::deserialize(data).map(Self::UserPdaResized),
+            b"connreqd" => ConnectionChanged::deserialize(data).map(Self::ConnectionRequested),
+            b"connaccd" => ConnectionChanged::deserialize(data).map(Self::ConnectionAccepted),
+            b"connrejd" => ConnectionChanged::deserialize(data).map(Self::ConnectionRejected),
+            b"connremd" => ConnectionChanged::deserialize(data).map(Self::ConnectionRemoved),
+            b"usrnclmd" => UsernameClaimed::deserialize(data).map(Self::UsernameClaimed),
+            b"cfgupdtd" => ConfigUpdated::deserialize(data).map(Self::ConfigUpdated),
+            b"sesncrtd" => SessionCreated::deserialize(data).map(Self::SessionCreated),
+            b"sesnrvkd" => SessionRevoked::deserialize(data).map(Self::SessionRevoked),
+            b"endorsed" => EndorsementChanged::deserialize(data).map(Self::Endorsed),
+            b"endrsrvk" => EndorsementChanged::deserialize(data).map(Self::EndorsementRevoked),
+            b"usrblckd" => BlockChanged::deserialize(data).map(Self::UserBlocked),
+            b"usrunblk" => BlockChanged::deserialize(data).map(Self::UserUnblocked),
+            b"grpcrtd_" => GroupCreated::deserialize(data).map(Self::GroupCreated),
+            b"grpjoind" => MembershipChanged::deserialize(data).map(Self::MemberJoined),
+            b"grpleftm" => MembershipChanged::deserialize(data).map(Self::MemberLeft),
+            b"grpkickd" => MembershipChanged::deserialize(data).map(Self::MemberKicked),
+            b"evtcrtd_" => EventCreated::deserialize(data).map(Self::EventCreated),
+            b"evtrsvpd" => RsvpChanged::deserialize(data).map(Self::Rsvped),
+            b"evtrsvcn" => RsvpChanged::deserialize(data).map(Self::RsvpCancelled),
+            b"evtclosd" => EventClosed::deserialize(data).map(Self::EventClosed),
+            b"dpstslsh" => DepositSlashed::deserialize(data).map(Self::DepositSlashed),
+            b"escrcrtd" => EscrowCreated::deserialize(data).map(Self::EscrowCreated),
+            b"escrjoin" => EscrowChanged::deserialize(data).map(Self::EscrowJoined),
+            b"escrchkd" => EscrowChanged::deserialize(data).map(Self::CheckedIn),
+            b"escrstld" => EscrowSettled::deserialize(data).map(Self::EscrowSettled),
+            b"ownrtrsf" => OwnershipTransferred::deserialize(data).map(Self::OwnershipTransferred),
+            b"grdnsset" => GuardiansSet::deserialize(data).map(Self::GuardiansSet),
+            b"rcvrprpd" => RecoveryProposed::deserialize(data).map(Self::RecoveryProposed),
+            b"rcvrappr" => RecoveryChanged::deserialize(data).map(Self::RecoveryApproved),
+            b"rcvrcncl" => RecoveryChanged::deserialize(data).map(Self::RecoveryCancelled),
+            b"attested" => Attested::deserialize(data).map(Self::Attested),
+            b"referred" => Referred::deserialize(data).map(Self::Referred),
+            b"pollcrtd" => PollCreated::deserialize(data).map(Self::PollCreated),
+            b"pollvotd" => Voted::deserialize(data).map(Self::Voted),
+            b"pollclsd" => PollClosed::deserialize(data).map(Self::PollClosed),
+            b"withdrwn" => Withdrawn::deserialize(data).map(Self::Withdrawn),
+            b"usrflagd" => FlagChanged::deserialize(data).map(Self::UserFlagged),
+            b"usrunflg" => FlagChanged::deserialize(data).map(Self::UserUnflagged),
+            b"pollvtcl" => VoteClosed::deserialize(data).map(Self::VoteClosed),
+            _ => {
+                msg!("Unknown event discriminator");
+                return Err(ProgramError::InvalidArgument);
+            }
+        };
+        event.map_err(|e| {
+            msg!("Failed to deserialize event: {}", e);
+            ProgramError::InvalidArgument
+        })
+    }
+
+    /// Logs the event with `sol_log_data`.
+    pub fn emit(&self) {
+        let body = self
+            .pack()
+            .expect("Serializing into a vector should never fail");
+        sol_log_data(&[&self.discriminator(), &body]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_unpack_events() {
+        let owner = Pubkey::new_unique();
+        let connection = ConnectionChanged {
+            requester: owner,
+            recipient: Pubkey::new_unique(),
+            changed_by: owner,
+        };
+        for event in [
+            ProgramEvent::UserInitialized(UserInitialized {
+                owner,
+                user_pda: Pubkey::new_unique(),
+                username: "paulinka".to_string(),
+            }),
+            ProgramEvent::UserPdaResized(UserPdaResized {
+                owner,
+                new_size: 2048,
+            }),
+            ProgramEvent::ConnectionRequested(connection.clone()),
+            ProgramEvent::ConnectionRemoved(connection),
+            ProgramEvent::SessionCreated(SessionCreated {
+                owner,
+                delegate: Pubkey::new_unique(),
+                expires_at_slot: 1000,
+                allowed_instructions: 0b1000,
+            }),
+        ] {
+            let body = event.pack().unwrap();
+            assert_eq!(
+                event,
+                ProgramEvent::unpack(&event.discriminator(), &body).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn unpack_unknown_event() {
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            ProgramEvent::unpack(b"unknown!", &[])
+        );
+        // Body too short for the event
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            ProgramEvent::unpack(b"profupdt", &[1, 2, 3])
+        );
+    }
+}
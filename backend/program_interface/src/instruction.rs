@@ -0,0 +1,2458 @@
+use {
+    crate::pda::{
+        find_block_list_pda, find_config_pda, find_connection_pda, find_endorsement_pda,
+        find_escrow_pda, find_event_pda, find_group_pda, find_membership_pda, find_poll_pda,
+        find_recovery_pda, find_referral_pda, find_rsvp_pda, find_session_pda, find_user_pda,
+        find_username_pda, find_vote_pda,
+    },
+    borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
+    solana_program::{
+        bpf_loader_upgradeable,
+        instruction::{AccountMeta, Instruction},
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+    std::mem::size_of,
+};
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct InitializeInstructionData {
+    pub lamports: u64, // max lamports the payer agrees to pay for rent, fee and deposit
+    pub pda_bump_seed: u8,
+    pub username: String,         // stored in the user profile
+    pub referrer: Option<Pubkey>, // wallet of the user who invited this one
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct UpdateProfileInstructionData {
+    pub username: String,
+    pub bio: String,
+    pub interests: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct ResizeInstructionData {
+    pub new_size: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct ClaimUsernameInstructionData {
+    pub username: String,
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct ConfigInstructionData {
+    pub paused: bool,
+    pub max_user_pda_size: u64,        // at most MAX_USER_PDA_SIZE
+    pub registration_fee: u64,         // lamports paid to the config PDA by every new user
+    pub deposit: u64, // refundable lamports locked in the user PDA of every new user
+    pub attestation_authority: Pubkey, // backend signer of the Attest instruction
+    pub referral_reward: u64, // lamports paid from the config PDA to the referrer
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct CreateSessionInstructionData {
+    pub expires_at_slot: u64,      // the session can't be used from this slot on
+    pub allowed_instructions: u64, // combination of the SESSION_* flags
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct CreateGroupInstructionData {
+    pub group_id: u64, // chosen by the creator, unique among their groups
+    pub name: String,
+    pub invite_only: bool,
+    pub max_members: u32, // including the admin
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct CreateEventInstructionData {
+    pub event_id: u64, // chosen by the organizer, unique among their events
+    pub title: String,
+    pub starts_at: i64, // unix timestamp
+    pub location_hint: String,
+    pub capacity: u32, // attendees, the organizer not included
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct TransferOwnershipInstructionData {
+    pub pda_bump_seed: u8, // of the user PDA of the new owner
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct CreateEscrowInstructionData {
+    pub meeting_id: u64, // chosen by the proposer, unique among the meetings with the partner
+    pub stake: u64,      // lamports locked by each participant
+    pub deadline_slot: u64,
+    pub charity: Option<Pubkey>, // receives the forfeited stakes instead of the other participant
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct SetGuardiansInstructionData {
+    pub guardians: Vec<Pubkey>, // at most MAX_GUARDIANS, none turns the recovery off
+    pub threshold: u8,          // guardians needed to approve a recovery
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct ProposeRecoveryInstructionData {
+    pub new_owner: Pubkey, // wallet the profile moves to
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct AttestInstructionData {
+    pub flags: u64,     // combination of the VERIFIED_* flags
+    pub verified: bool, // sets the flags if true, clears them otherwise
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct CreatePollInstructionData {
+    pub poll_id: u64, // chosen by the creator, unique among their polls
+    pub question: String,
+    pub options: Vec<String>,  // between 2 and MAX_POLL_OPTIONS
+    pub group: Option<Pubkey>, // group PDA, only its members can vote
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct VoteInstructionData {
+    pub option: u8, // index of the chosen option
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct WithdrawInstructionData {
+    pub lamports: u64, // moved from the config PDA, which stays rent-exempt
+}
+
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, BorshSchema)]
+pub struct VerifyMemberInstructionData {
+    pub required_verifications: u64, // combination of the VERIFIED_* flags, 0 for none
+}
+
+/// Flags of the instructions a session delegate can sign in place of the owner.
+/// The flag of an instruction is the bit at the position of its tag.
+pub const SESSION_UPDATE_PROFILE: u64 = 1 << InstructionTag::UpdateProfile as u8;
+pub const SESSION_ACCEPT_CONNECTION: u64 = 1 << InstructionTag::AcceptConnection as u8;
+pub const SESSION_REJECT_CONNECTION: u64 = 1 << InstructionTag::RejectConnection as u8;
+pub const SESSION_REMOVE_CONNECTION: u64 = 1 << InstructionTag::RemoveConnection as u8;
+/// All the instructions that can be signed by a session delegate.
+/// Instructions moving the owner's lamports are never among them.
+pub const SESSION_ALL: u64 = SESSION_UPDATE_PROFILE
+    | SESSION_ACCEPT_CONNECTION
+    | SESSION_REJECT_CONNECTION
+    | SESSION_REMOVE_CONNECTION;
+
+/// Instructions supported by the program.
+/// The user instructions take the program config PDA as the last account
+/// and fail while the program is paused.
+/// The instructions in [SESSION_ALL] can also be signed by a session delegate
+/// in place of the user, with the session PDA passed after the config PDA.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProgramInstruction {
+    /// Create a PDA for the user and store the user profile in it.
+    /// The username is claimed in the same instruction, it fails if the
+    /// username is already taken.
+    /// The rent is computed by the program, the instruction fails if it
+    /// exceeds the lamports offered by the user. Lamports already held
+    /// by the PDA addresses count towards the rent.
+    /// The registration fee and the deposit of the program config are paid on top of the rent,
+    /// the deposit stays in the user PDA.
+    /// With a referrer, the user also pays for the referral PDA. The referrer's invite count
+    /// grows and the referral reward is paid from the config PDA, as long as it can afford it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account, PDA owner.
+    /// 1. `[writable]` PDA found with Pubkey::find_program_address for this user.
+    /// 2. `[writable]` Username PDA of the username
+    /// 3. `[]` System program used to create a new account.
+    /// 4. `[writable]` Program config PDA, collects the registration fee.
+    /// 5. `[writable]` Referrer account, only with a referrer
+    /// 6. `[writable]` Referrer's PDA, only with a referrer
+    /// 7. `[writable]` Referral PDA of the user, only with a referrer
+    Initialize(InitializeInstructionData),
+
+    /// Close the provided PDA account, draining lamports to recipient
+    /// account. The username claimed by the user is released.
+    /// The deposit is returned with the rent, unless it has been slashed.
+    /// Fails while a recovery of the PDA is pending.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account, PDA owner.
+    /// 1. `[writable]` User's PDA
+    /// 2. `[writable]` Username PDA of the username in the profile
+    /// 3. `[]` Program config PDA
+    /// 4. `[]` Recovery PDA of the user PDA
+    CloseAccount,
+
+    /// Overwrite the editable fields of the user profile.
+    /// The username can only change its case or surrounding spaces here,
+    /// a different username must be claimed with [ProgramInstruction::ClaimUsername].
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` User account, PDA owner, or the session delegate.
+    /// 1. `[writable]` User's PDA
+    /// 2. `[]` Program config PDA
+    /// 3. `[]` Optional session PDA of the owner and the delegate
+    UpdateProfile(UpdateProfileInstructionData),
+
+    /// Grow or shrink the user's PDA to `new_size` bytes.
+    /// The owner pays the rent of the added bytes
+    /// and is refunded the rent of the removed ones.
+    /// The PDA can't get smaller than its initial size, which leaves room for new profile fields.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account, PDA owner.
+    /// 1. `[writable]` User's PDA
+    /// 2. `[]` System program used to transfer the rent.
+    /// 3. `[]` Program config PDA
+    Resize(ResizeInstructionData),
+
+    /// Request a connection with another user.
+    /// Creates the connection PDA of both users in the pending state.
+    /// Fails if any of the users has blocked the other one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Requester, pays for the connection PDA.
+    /// 1. `[]` Requester's user PDA
+    /// 2. `[]` Recipient's user PDA
+    /// 3. `[writable]` Connection PDA of both users
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Requester's block list PDA, doesn't need to exist
+    /// 6. `[]` Recipient's block list PDA, doesn't need to exist
+    /// 7. `[]` Program config PDA
+    RequestConnection,
+
+    /// Accept a pending connection requested by the other user.
+    /// Fails if any of the users has blocked the other one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Recipient of the request, or the session delegate.
+    /// 1. `[writable]` Connection PDA of both users
+    /// 2. `[]` Recipient's block list PDA, doesn't need to exist
+    /// 3. `[]` Requester's block list PDA, doesn't need to exist
+    /// 4. `[]` Program config PDA
+    /// 5. `[]` Optional session PDA of the recipient and the delegate
+    AcceptConnection,
+
+    /// Reject a pending connection requested by the other user.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Recipient of the request, or the session delegate.
+    /// 1. `[writable]` Connection PDA of both users
+    /// 2. `[]` Program config PDA
+    /// 3. `[]` Optional session PDA of the recipient and the delegate
+    RejectConnection,
+
+    /// Close the connection PDA, whatever its status.
+    /// Can be done by any of the users, the rent goes back to the requester.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` One of the connected users, or the session delegate.
+    /// 1. `[writable]` Connection PDA of both users
+    /// 2. `[writable]` Requester of the connection
+    /// 3. `[]` Program config PDA
+    /// 4. `[]` Optional session PDA of the user and the delegate
+    RemoveConnection,
+
+    /// Claim a new username and store it in the user profile.
+    /// The username previously claimed by the user is released.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account, PDA owner.
+    /// 1. `[writable]` User's PDA
+    /// 2. `[writable]` Username PDA of the new username
+    /// 3. `[writable]` Username PDA of the username in the profile
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Program config PDA
+    ClaimUsername(ClaimUsernameInstructionData),
+
+    /// Create the program config PDA, the signer becomes the admin.
+    /// Only the upgrade authority of the program can sign, so the deployer
+    /// can't be front-run. Meant to be sent right after the program is deployed,
+    /// until then the default config applies.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Admin, the upgrade authority, pays for the config PDA.
+    /// 1. `[writable]` Program config PDA
+    /// 2. `[]` System program used to create a new account.
+    /// 3. `[]` ProgramData account of the program, holding its upgrade authority.
+    InitializeConfig(ConfigInstructionData),
+
+    /// Overwrite the program config. Also allowed while the program is paused.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Admin
+    /// 1. `[writable]` Program config PDA
+    UpdateConfig(ConfigInstructionData),
+
+    /// Hand the admin authority over to another account.
+    /// The new admin signs too, so that the authority can't be lost to a wrong key.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Current admin
+    /// 1. `[writable]` Program config PDA
+    /// 2. `[signer]` New admin
+    TransferAuthority,
+
+    /// Let the delegate sign the allowed instructions in place of the user
+    /// until the expiry slot. Creating the session again renews it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account, pays for the session PDA.
+    /// 1. `[]` User's PDA
+    /// 2. `[writable]` Session PDA of the user and the delegate
+    /// 3. `[]` Delegate
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Program config PDA
+    CreateSession(CreateSessionInstructionData),
+
+    /// Close the session PDA, the rent goes back to the user.
+    /// Can be done by the user or the delegate, also while the program is paused.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` User or delegate of the session
+    /// 1. `[writable]` Session PDA
+    /// 2. `[writable]` User account, owner of the session
+    /// 3. `[]` Program config PDA
+    RevokeSession,
+
+    /// Vouch for another registered user.
+    /// Creates the endorsement PDA and increments the endorsement count
+    /// of the endorsed user, a user can be endorsed once by each user.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Endorser, pays for the endorsement PDA.
+    /// 1. `[]` Endorser's user PDA
+    /// 2. `[writable]` Endorsed user's PDA
+    /// 3. `[writable]` Endorsement PDA
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Program config PDA
+    Endorse,
+
+    /// Withdraw an endorsement, the rent goes back to the endorser.
+    /// The endorsement count is decremented if the endorsed user still exists.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Endorser
+    /// 1. `[writable]` Endorsed user's PDA
+    /// 2. `[writable]` Endorsement PDA
+    /// 3. `[]` Program config PDA
+    RevokeEndorsement,
+
+    /// Add a user to the block list of the signer. The blocked user can't
+    /// request or accept a connection with the signer until unblocked.
+    /// Creates the block list PDA on the first block.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account, pays for the block list PDA.
+    /// 1. `[]` User's PDA
+    /// 2. `[writable]` Block list PDA of the user
+    /// 3. `[]` Wallet of the user to block
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Program config PDA
+    Block,
+
+    /// Remove a user from the block list of the signer.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` User account, owner of the block list.
+    /// 1. `[writable]` Block list PDA of the user
+    /// 2. `[]` Wallet of the user to unblock
+    /// 3. `[]` Program config PDA
+    Unblock,
+
+    /// Create a group administered by the signer, who becomes its first member.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Creator, pays for the group and membership PDAs.
+    /// 1. `[]` Creator's user PDA
+    /// 2. `[writable]` Group PDA of the creator and the group id
+    /// 3. `[writable]` Membership PDA of the creator in the group
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Program config PDA
+    CreateGroup(CreateGroupInstructionData),
+
+    /// Join a group with free places.
+    /// The admin of an invite-only group signs the instruction too.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Member, pays for the membership PDA.
+    /// 1. `[]` Member's user PDA
+    /// 2. `[writable]` Group PDA
+    /// 3. `[writable]` Membership PDA of the member in the group
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Program config PDA
+    /// 6. `[signer]` Group admin, only for invite-only groups
+    JoinGroup,
+
+    /// Leave a group, the rent of the membership PDA goes back to the member.
+    /// The admin can't leave the group.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Member
+    /// 1. `[writable]` Group PDA
+    /// 2. `[writable]` Membership PDA of the member in the group
+    /// 3. `[]` Program config PDA
+    LeaveGroup,
+
+    /// Remove a member from the group, the rent of the membership PDA goes back to the member.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Group admin
+    /// 1. `[writable]` Group PDA
+    /// 2. `[writable]` Membership PDA of the member in the group
+    /// 3. `[writable]` Member
+    /// 4. `[]` Program config PDA
+    KickMember,
+
+    /// Create a meetup event organized by the signer. The event needs to start in the future.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Organizer, pays for the event PDA.
+    /// 1. `[]` Organizer's user PDA
+    /// 2. `[writable]` Event PDA of the organizer and the event id
+    /// 3. `[]` System program used to create a new account.
+    /// 4. `[]` Program config PDA
+    CreateEvent(CreateEventInstructionData),
+
+    /// RSVP to an open event which hasn't started yet and has free places.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Attendee, pays for the RSVP PDA.
+    /// 1. `[]` Attendee's user PDA
+    /// 2. `[writable]` Event PDA
+    /// 3. `[writable]` RSVP PDA of the attendee to the event
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Program config PDA
+    Rsvp,
+
+    /// Cancel the RSVP, the rent of the RSVP PDA goes back to the attendee.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Attendee
+    /// 1. `[writable]` Event PDA
+    /// 2. `[writable]` RSVP PDA of the attendee to the event
+    /// 3. `[]` Program config PDA
+    CancelRsvp,
+
+    /// Stop taking RSVPs to the event. Only the organizer can close it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Organizer
+    /// 1. `[writable]` Event PDA
+    /// 2. `[]` Program config PDA
+    CloseEvent,
+
+    /// Take the deposit of a user who broke the rules, it goes to the config PDA.
+    /// Only the admin can slash, also while the program is paused.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Admin
+    /// 1. `[]` Wallet of the user
+    /// 2. `[writable]` User's PDA
+    /// 3. `[writable]` Program config PDA
+    SlashDeposit,
+
+    /// Propose a meeting to another user and lock the stake in the escrow PDA.
+    /// The deadline to check in needs to be in the future.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Proposer, pays for the escrow PDA and the stake.
+    /// 1. `[]` Proposer's user PDA
+    /// 2. `[]` Partner
+    /// 3. `[]` Partner's user PDA
+    /// 4. `[writable]` Escrow PDA of the proposer, the partner and the meeting id
+    /// 5. `[]` System program used to create a new account.
+    /// 6. `[]` Program config PDA
+    /// 7. `[]` Charity, if set in the instruction data. A rent exempt system account
+    ///    other than the participants, so that the forfeited stakes can always be paid out.
+    CreateEscrow(CreateEscrowInstructionData),
+
+    /// Accept the meeting and lock the same stake as the proposer, before the deadline.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Partner
+    /// 1. `[writable]` Escrow PDA
+    /// 2. `[]` System program used to transfer the stake.
+    /// 3. `[]` Program config PDA
+    JoinEscrow,
+
+    /// Confirm the participation in the meeting, before the deadline
+    /// and once both participants locked the stake.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Proposer or partner
+    /// 1. `[writable]` Escrow PDA
+    /// 2. `[]` Program config PDA
+    CheckIn,
+
+    /// Pay out the stakes and close the escrow PDA, its rent goes back to the proposer.
+    /// Possible once both participants checked in or after the deadline, by anyone,
+    /// also while the program is paused.
+    /// The stake of a participant who checked in is refunded, the stake of a no-show
+    /// goes to the charity or to the other participant if there is no charity.
+    /// The other participant gets it too if the charity would stay below the rent exemption.
+    /// If the partner never joined, the proposer gets the stake back.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Escrow PDA
+    /// 1. `[writable]` Proposer
+    /// 2. `[writable]` Partner
+    /// 3. `[]` Program config PDA
+    /// 4. `[writable]` Charity, if set in the escrow.
+    SettleEscrow,
+
+    /// Move the profile to the user PDA of a new wallet and close the current user PDA.
+    /// The deposit moves with the profile and the username claim points to the new PDA.
+    /// The rest of the current PDA's lamports go back to the current owner.
+    /// The blocked users move to the block list PDA of the new owner.
+    /// Connections, endorsements, sessions and memberships are kept by the current wallet,
+    /// the endorsement count of the profile starts over.
+    /// Fails while a recovery of the PDA is pending.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` User account, PDA owner.
+    /// 1. `[writable]` New owner, pays for the new user PDA if it signs too.
+    /// 2. `[writable]` User's PDA
+    /// 3. `[writable]` User PDA of the new owner
+    /// 4. `[writable]` Username PDA of the username in the profile
+    /// 5. `[writable]` Block list PDA of the user
+    /// 6. `[writable]` Block list PDA of the new owner
+    /// 7. `[]` System program used to create a new account.
+    /// 8. `[]` Program config PDA
+    /// 9. `[]` Recovery PDA of the user PDA
+    TransferOwnership(TransferOwnershipInstructionData),
+
+    /// Set the guardians who can recover the profile if the owner loses the key,
+    /// and how many of them need to approve a recovery.
+    /// The PDA needs to be resized first if the guardians don't fit in it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` User account, PDA owner.
+    /// 1. `[writable]` User's PDA
+    /// 2. `[]` Program config PDA
+    SetGuardians(SetGuardiansInstructionData),
+
+    /// Propose to move the profile to a new wallet, the proposal counts as an approval.
+    /// A user PDA has at most one recovery pending.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Guardian, pays for the recovery PDA.
+    /// 1. `[]` Wallet of the user, PDA owner.
+    /// 2. `[]` User's PDA
+    /// 3. `[writable]` Recovery PDA of the user PDA
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Program config PDA
+    ProposeRecovery(ProposeRecoveryInstructionData),
+
+    /// Approve the pending recovery of a user PDA.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Guardian
+    /// 1. `[]` Wallet of the user, PDA owner.
+    /// 2. `[]` User's PDA
+    /// 3. `[writable]` Recovery PDA of the user PDA
+    /// 4. `[]` Program config PDA
+    ApproveRecovery,
+
+    /// Move the profile to the new owner of the recovery like [ProgramInstruction::TransferOwnership].
+    /// Possible once the guardian threshold is reached and the timelock passed.
+    /// Only the approvals of users who are still guardians count.
+    /// The lamports of the current PDA go to the new owner, the rent of the recovery PDA
+    /// goes back to the guardian who proposed it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` New owner, pays for the new user PDA.
+    /// 1. `[]` Wallet of the user, PDA owner.
+    /// 2. `[writable]` User's PDA
+    /// 3. `[writable]` User PDA of the new owner
+    /// 4. `[writable]` Username PDA of the username in the profile
+    /// 5. `[writable]` Block list PDA of the user
+    /// 6. `[writable]` Block list PDA of the new owner
+    /// 7. `[writable]` Recovery PDA of the user PDA
+    /// 8. `[writable]` Guardian who proposed the recovery
+    /// 9. `[]` System program used to create a new account.
+    /// 10. `[]` Program config PDA
+    ExecuteRecovery(TransferOwnershipInstructionData),
+
+    /// Drop the pending recovery, its rent goes back to the guardian who proposed it.
+    /// The owner can cancel a recovery they didn't ask for, the proposer can withdraw it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Owner of the user PDA or the guardian who proposed the recovery
+    /// 1. `[]` User's PDA
+    /// 2. `[writable]` Recovery PDA of the user PDA
+    /// 3. `[writable]` Guardian who proposed the recovery
+    /// 4. `[]` Program config PDA
+    CancelRecovery,
+
+    /// Set or clear verification flags in the user's profile.
+    /// Only the attestation authority of the program config can sign,
+    /// also while the program is paused.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Attestation authority
+    /// 1. `[]` Wallet of the user
+    /// 2. `[writable]` User's PDA
+    /// 3. `[]` Program config PDA
+    Attest(AttestInstructionData),
+
+    /// Create a poll with the tallies of the votes kept in the poll PDA.
+    /// A poll of a group takes votes of its members only, the creator needs to be one of them.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Creator, pays for the poll PDA.
+    /// 1. `[]` Creator's user PDA
+    /// 2. `[writable]` Poll PDA of the creator and the poll id
+    /// 3. `[]` System program used to create a new account.
+    /// 4. `[]` Program config PDA
+    /// 5. `[]` Creator's membership PDA in the group, only for a poll of a group
+    CreatePoll(CreatePollInstructionData),
+
+    /// Vote for an option of an open poll. The vote PDA keeps the voter from voting twice.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Voter, pays for the vote PDA.
+    /// 1. `[]` Voter's user PDA
+    /// 2. `[writable]` Poll PDA
+    /// 3. `[writable]` Vote PDA of the voter in the poll
+    /// 4. `[]` System program used to create a new account.
+    /// 5. `[]` Program config PDA
+    /// 6. `[]` Voter's membership PDA in the group, only for a poll of a group
+    Vote(VoteInstructionData),
+
+    /// Stop taking votes, the tallies stay in the poll PDA. Only the creator can close it.
+    /// The voters can then close their vote PDAs with [ProgramInstruction::CloseVote].
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Creator
+    /// 1. `[writable]` Poll PDA
+    /// 2. `[]` Program config PDA
+    ClosePoll,
+
+    /// Check that the wallet is a registered member in good standing:
+    /// the user PDA exists, is not flagged and has the required verifications.
+    /// Read-only and meant to be called by other programs via CPI, see [crate::cpi].
+    /// Works also while the program is paused and emits no event.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` Wallet of the user
+    /// 1. `[]` User's PDA
+    VerifyMember(VerifyMemberInstructionData),
+
+    /// Move the registration fees and slashed deposits collected in the config PDA
+    /// to the recipient. Only the admin can withdraw, also while the program is paused.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Admin
+    /// 1. `[writable]` Program config PDA
+    /// 2. `[writable]` Recipient of the lamports
+    Withdraw(WithdrawInstructionData),
+
+    /// Flag a user who broke the rules, a flagged user fails [ProgramInstruction::VerifyMember].
+    /// The profile of a flagged user can't be closed or moved to another wallet,
+    /// registering again would drop the flag.
+    /// Only the admin can flag, also while the program is paused.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Admin
+    /// 1. `[]` Wallet of the user
+    /// 2. `[writable]` User's PDA
+    /// 3. `[]` Program config PDA
+    Flag,
+
+    /// Clear the flag of a user, with the same accounts as [ProgramInstruction::Flag].
+    Unflag,
+
+    /// Close the vote PDA once the poll is closed, the rent goes back to the voter.
+    /// The vote stays counted in the tallies of the poll.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Voter
+    /// 1. `[]` Poll PDA
+    /// 2. `[writable]` Vote PDA of the voter in the poll
+    /// 3. `[]` Program config PDA
+    CloseVote,
+}
+
+#[derive(Clone, Debug, PartialEq, num_enum::FromPrimitive, num_enum::IntoPrimitive)]
+#[repr(u8)]
+enum InstructionTag {
+    #[num_enum(default)]
+    Invalid,
+    Initialize,
+    CloseAccount,
+    UpdateProfile,
+    Resize,
+    RequestConnection,
+    AcceptConnection,
+    RejectConnection,
+    RemoveConnection,
+    ClaimUsername,
+    InitializeConfig,
+    UpdateConfig,
+    TransferAuthority,
+    CreateSession,
+    RevokeSession,
+    Endorse,
+    RevokeEndorsement,
+    Block,
+    Unblock,
+    CreateGroup,
+    JoinGroup,
+    LeaveGroup,
+    KickMember,
+    CreateEvent,
+    Rsvp,
+    CancelRsvp,
+    CloseEvent,
+    SlashDeposit,
+    CreateEscrow,
+    JoinEscrow,
+    CheckIn,
+    SettleEscrow,
+    TransferOwnership,
+    SetGuardians,
+    ProposeRecovery,
+    ApproveRecovery,
+    ExecuteRecovery,
+    CancelRecovery,
+    Attest,
+    CreatePoll,
+    Vote,
+    ClosePoll,
+    VerifyMember,
+    Withdraw,
+    Flag,
+    Unflag,
+    CloseVote,
+}
+
+impl ProgramInstruction {
+    /// Unpacks a byte buffer into a [ProgramInstruction].
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, mut data) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let tag = InstructionTag::from(tag);
+        Ok(match tag {
+            InstructionTag::Initialize => {
+                let instruction_data =
+                    InitializeInstructionData::deserialize(&mut data).map_err(|e| {
+                        msg!("Failed to deserialize instruction body: {}", e);
+                        ProgramError::InvalidInstructionData
+                    })?;
+                Self::Initialize(instruction_data)
+            }
+            InstructionTag::CloseAccount => Self::CloseAccount,
+            InstructionTag::UpdateProfile => {
+                let instruction_data = UpdateProfileInstructionData::deserialize(&mut data)
+                    .map_err(|e| {
+                        msg!("Failed to deserialize instruction body: {}", e);
+                        ProgramError::InvalidInstructionData
+                    })?;
+                Self::UpdateProfile(instruction_data)
+            }
+            InstructionTag::Resize => {
+                let instruction_data =
+                    ResizeInstructionData::deserialize(&mut data).map_err(|e| {
+                        msg!("Failed to deserialize instruction body: {}", e);
+                        ProgramError::InvalidInstructionData
+                    })?;
+                Self::Resize(instruction_data)
+            }
+            InstructionTag::RequestConnection => Self::RequestConnection,
+            InstructionTag::AcceptConnection => Self::AcceptConnection,
+            InstructionTag::RejectConnection => Self::RejectConnection,
+            InstructionTag::RemoveConnection => Self::RemoveConnection,
+            InstructionTag::ClaimUsername => {
+                let instruction_data = ClaimUsernameInstructionData::deserialize(&mut data)
+                    .map_err(|e| {
+                        msg!("Failed to deserialize instruction body: {}", e);
+                        ProgramError::InvalidInstructionData
+                    })?;
+                Self::ClaimUsername(instruction_data)
+            }
+            InstructionTag::InitializeConfig => Self::InitializeConfig(
+                ConfigInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::UpdateConfig => {
+                Self::UpdateConfig(ConfigInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?)
+            }
+            InstructionTag::TransferAuthority => Self::TransferAuthority,
+            InstructionTag::CreateSession => Self::CreateSession(
+                CreateSessionInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::RevokeSession => Self::RevokeSession,
+            InstructionTag::Endorse => Self::Endorse,
+            InstructionTag::RevokeEndorsement => Self::RevokeEndorsement,
+            InstructionTag::Block => Self::Block,
+            InstructionTag::Unblock => Self::Unblock,
+            InstructionTag::CreateGroup => Self::CreateGroup(
+                CreateGroupInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::JoinGroup => Self::JoinGroup,
+            InstructionTag::LeaveGroup => Self::LeaveGroup,
+            InstructionTag::KickMember => Self::KickMember,
+            InstructionTag::CreateEvent => Self::CreateEvent(
+                CreateEventInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::Rsvp => Self::Rsvp,
+            InstructionTag::CancelRsvp => Self::CancelRsvp,
+            InstructionTag::CloseEvent => Self::CloseEvent,
+            InstructionTag::SlashDeposit => Self::SlashDeposit,
+            InstructionTag::CreateEscrow => Self::CreateEscrow(
+                CreateEscrowInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::JoinEscrow => Self::JoinEscrow,
+            InstructionTag::CheckIn => Self::CheckIn,
+            InstructionTag::SettleEscrow => Self::SettleEscrow,
+            InstructionTag::TransferOwnership => Self::TransferOwnership(
+                TransferOwnershipInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::SetGuardians => Self::SetGuardians(
+                SetGuardiansInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::ProposeRecovery => Self::ProposeRecovery(
+                ProposeRecoveryInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::ApproveRecovery => Self::ApproveRecovery,
+            InstructionTag::ExecuteRecovery => Self::ExecuteRecovery(
+                TransferOwnershipInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::CancelRecovery => Self::CancelRecovery,
+            InstructionTag::Attest => {
+                Self::Attest(AttestInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?)
+            }
+
+            InstructionTag::CreatePoll => Self::CreatePoll(
+                CreatePollInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::Vote => {
+                Self::Vote(VoteInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?)
+            }
+            InstructionTag::ClosePoll => Self::ClosePoll,
+            InstructionTag::VerifyMember => Self::VerifyMember(
+                VerifyMemberInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::Withdraw => Self::Withdraw(
+                WithdrawInstructionData::deserialize(&mut data).map_err(|e| {
+                    msg!("Failed to deserialize instruction body: {}", e);
+                    ProgramError::InvalidInstructionData
+                })?,
+            ),
+            InstructionTag::Flag => Self::Flag,
+            InstructionTag::Unflag => Self::Unflag,
+            InstructionTag::CloseVote => Self::CloseVote,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+
+    /// Packs a [ProgramInstruction] into a byte buffer.
+    pub fn pack(&self) -> Result<Vec<u8>, borsh::io::Error> {
+        let mut buf: Vec<u8> = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::Initialize(data) => {
+                buf.push(InstructionTag::Initialize.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::CloseAccount => buf.push(InstructionTag::CloseAccount.into()),
+            Self::UpdateProfile(data) => {
+                buf.push(InstructionTag::UpdateProfile.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::Resize(data) => {
+                buf.push(InstructionTag::Resize.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::RequestConnection => buf.push(InstructionTag::RequestConnection.into()),
+            Self::AcceptConnection => buf.push(InstructionTag::AcceptConnection.into()),
+            Self::RejectConnection => buf.push(InstructionTag::RejectConnection.into()),
+            Self::RemoveConnection => buf.push(InstructionTag::RemoveConnection.into()),
+            Self::ClaimUsername(data) => {
+                buf.push(InstructionTag::ClaimUsername.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::InitializeConfig(data) => {
+                buf.push(InstructionTag::InitializeConfig.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::UpdateConfig(data) => {
+                buf.push(InstructionTag::UpdateConfig.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::TransferAuthority => buf.push(InstructionTag::TransferAuthority.into()),
+            Self::CreateSession(data) => {
+                buf.push(InstructionTag::CreateSession.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::RevokeSession => buf.push(InstructionTag::RevokeSession.into()),
+            Self::Endorse => buf.push(InstructionTag::Endorse.into()),
+            Self::RevokeEndorsement => buf.push(InstructionTag::RevokeEndorsement.into()),
+            Self::Block => buf.push(InstructionTag::Block.into()),
+            Self::Unblock => buf.push(InstructionTag::Unblock.into()),
+            Self::CreateGroup(data) => {
+                buf.push(InstructionTag::CreateGroup.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::JoinGroup => buf.push(InstructionTag::JoinGroup.into()),
+            Self::LeaveGroup => buf.push(InstructionTag::LeaveGroup.into()),
+            Self::KickMember => buf.push(InstructionTag::KickMember.into()),
+            Self::CreateEvent(data) => {
+                buf.push(InstructionTag::CreateEvent.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::Rsvp => buf.push(InstructionTag::Rsvp.into()),
+            Self::CancelRsvp => buf.push(InstructionTag::CancelRsvp.into()),
+            Self::CloseEvent => buf.push(InstructionTag::CloseEvent.into()),
+            Self::SlashDeposit => buf.push(InstructionTag::SlashDeposit.into()),
+            Self::CreateEscrow(data) => {
+                buf.push(InstructionTag::CreateEscrow.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::JoinEscrow => buf.push(InstructionTag::JoinEscrow.into()),
+            Self::CheckIn => buf.push(InstructionTag::CheckIn.into()),
+            Self::SettleEscrow => buf.push(InstructionTag::SettleEscrow.into()),
+            Self::TransferOwnership(data) => {
+                buf.push(InstructionTag::TransferOwnership.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::SetGuardians(data) => {
+                buf.push(InstructionTag::SetGuardians.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::ProposeRecovery(data) => {
+                buf.push(InstructionTag::ProposeRecovery.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::ApproveRecovery => buf.push(InstructionTag::ApproveRecovery.into()),
+            Self::ExecuteRecovery(data) => {
+                buf.push(InstructionTag::ExecuteRecovery.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::CancelRecovery => buf.push(InstructionTag::CancelRecovery.into()),
+            Self::Attest(data) => {
+                buf.push(InstructionTag::Attest.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::CreatePoll(data) => {
+                buf.push(InstructionTag::CreatePoll.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::Vote(data) => {
+                buf.push(InstructionTag::Vote.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::ClosePoll => buf.push(InstructionTag::ClosePoll.into()),
+            Self::VerifyMember(data) => {
+                buf.push(InstructionTag::VerifyMember.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::Withdraw(data) => {
+                buf.push(InstructionTag::Withdraw.into());
+                data.serialize(&mut buf)?;
+            }
+            Self::Flag => buf.push(InstructionTag::Flag.into()),
+            Self::Unflag => buf.push(InstructionTag::Unflag.into()),
+            Self::CloseVote => buf.push(InstructionTag::CloseVote.into()),
+        };
+        Ok(buf)
+    }
+}
+
+/// Creates an [ProgramInstruction::Initialize] instruction for the PDA of `payer`.
+/// `lamports` is the maximum amount `payer` agrees to transfer to the PDAs for their rent.
+pub fn initialize(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    lamports: u64,
+    username: String,
+) -> Instruction {
+    initialize_with_referrer(program_id, payer, lamports, username, None)
+}
+
+/// Creates a [ProgramInstruction::Initialize] instruction of a user invited by `referrer`.
+pub fn initialize_with_referrer(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    lamports: u64,
+    username: String,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    let (pda, pda_bump_seed) = find_user_pda(program_id, payer);
+    let (username_pda, _) = find_username_pda(program_id, &username);
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(pda, false),
+        AccountMeta::new(username_pda, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new(find_config_pda(program_id).0, false),
+    ];
+    if let Some(referrer) = referrer {
+        accounts.push(AccountMeta::new(*referrer, false));
+        accounts.push(AccountMeta::new(
+            find_user_pda(program_id, referrer).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            find_referral_pda(program_id, payer).0,
+            false,
+        ));
+    }
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Initialize(InitializeInstructionData {
+            lamports,
+            pda_bump_seed,
+            username,
+            referrer: referrer.copied(),
+        }),
+        accounts,
+    )
+}
+
+/// Creates a [ProgramInstruction::CloseAccount] instruction for the PDA of `owner`.
+/// `username` is the username stored in the profile.
+pub fn close_account(program_id: &Pubkey, owner: &Pubkey, username: &str) -> Instruction {
+    let (pda, _) = find_user_pda(program_id, owner);
+    let (username_pda, _) = find_username_pda(program_id, username);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CloseAccount,
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new(username_pda, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+            AccountMeta::new_readonly(find_recovery_pda(program_id, &pda).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::TransferOwnership] instruction moving the profile
+/// of `owner` to `new_owner`. Mark `new_owner` as a signer to make it pay for its PDA.
+pub fn transfer_ownership(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    new_owner: &Pubkey,
+    username: &str,
+) -> Instruction {
+    let (pda, _) = find_user_pda(program_id, owner);
+    let (new_pda, pda_bump_seed) = find_user_pda(program_id, new_owner);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::TransferOwnership(TransferOwnershipInstructionData { pda_bump_seed }),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(*new_owner, false),
+            AccountMeta::new(pda, false),
+            AccountMeta::new(new_pda, false),
+            AccountMeta::new(find_username_pda(program_id, username).0, false),
+            AccountMeta::new(find_block_list_pda(program_id, owner).0, false),
+            AccountMeta::new(find_block_list_pda(program_id, new_owner).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+            AccountMeta::new_readonly(find_recovery_pda(program_id, &pda).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::UpdateProfile] instruction for the PDA of `owner`.
+pub fn update_profile(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    username: String,
+    bio: String,
+    interests: Vec<String>,
+) -> Instruction {
+    let (pda, _) = find_user_pda(program_id, owner);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::UpdateProfile(UpdateProfileInstructionData {
+            username,
+            bio,
+            interests,
+        }),
+        vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::Resize] instruction for the PDA of `owner`.
+pub fn resize(program_id: &Pubkey, owner: &Pubkey, new_size: u64) -> Instruction {
+    let (pda, _) = find_user_pda(program_id, owner);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Resize(ResizeInstructionData { new_size }),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::RequestConnection] instruction.
+pub fn request_connection(
+    program_id: &Pubkey,
+    requester: &Pubkey,
+    recipient: &Pubkey,
+) -> Instruction {
+    let (connection, _) = find_connection_pda(program_id, requester, recipient);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::RequestConnection,
+        vec![
+            AccountMeta::new(*requester, true),
+            AccountMeta::new_readonly(find_user_pda(program_id, requester).0, false),
+            AccountMeta::new_readonly(find_user_pda(program_id, recipient).0, false),
+            AccountMeta::new(connection, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_block_list_pda(program_id, requester).0, false),
+            AccountMeta::new_readonly(find_block_list_pda(program_id, recipient).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::AcceptConnection] instruction.
+pub fn accept_connection(
+    program_id: &Pubkey,
+    recipient: &Pubkey,
+    requester: &Pubkey,
+) -> Instruction {
+    let (connection, _) = find_connection_pda(program_id, recipient, requester);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::AcceptConnection,
+        vec![
+            AccountMeta::new_readonly(*recipient, true),
+            AccountMeta::new(connection, false),
+            AccountMeta::new_readonly(find_block_list_pda(program_id, recipient).0, false),
+            AccountMeta::new_readonly(find_block_list_pda(program_id, requester).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::RejectConnection] instruction.
+pub fn reject_connection(
+    program_id: &Pubkey,
+    recipient: &Pubkey,
+    requester: &Pubkey,
+) -> Instruction {
+    let (connection, _) = find_connection_pda(program_id, recipient, requester);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::RejectConnection,
+        vec![
+            AccountMeta::new_readonly(*recipient, true),
+            AccountMeta::new(connection, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::RemoveConnection] instruction signed by `user`.
+/// `requester` is the user who requested the connection, one of `user` and `other_user`.
+pub fn remove_connection(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    other_user: &Pubkey,
+    requester: &Pubkey,
+) -> Instruction {
+    let (connection, _) = find_connection_pda(program_id, user, other_user);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::RemoveConnection,
+        vec![
+            AccountMeta::new_readonly(*user, true),
+            AccountMeta::new(connection, false),
+            AccountMeta::new(*requester, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::ClaimUsername] instruction for the PDA of `owner`.
+/// `current_username` is the username stored in the profile.
+pub fn claim_username(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    current_username: &str,
+    username: String,
+) -> Instruction {
+    let (pda, _) = find_user_pda(program_id, owner);
+    let (username_pda, _) = find_username_pda(program_id, &username);
+    let (current_username_pda, _) = find_username_pda(program_id, current_username);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::ClaimUsername(ClaimUsernameInstructionData { username }),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new(username_pda, false),
+            AccountMeta::new(current_username_pda, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::InitializeConfig] instruction making `admin`,
+/// the upgrade authority of the program, the admin.
+pub fn initialize_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: ConfigInstructionData,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::InitializeConfig(config),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(find_config_pda(program_id).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(
+                bpf_loader_upgradeable::get_program_data_address(program_id),
+                false,
+            ),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::UpdateConfig] instruction.
+pub fn update_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: ConfigInstructionData,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::UpdateConfig(config),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::TransferAuthority] instruction,
+/// signed by both `admin` and `new_admin`.
+pub fn transfer_authority(program_id: &Pubkey, admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::TransferAuthority,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(find_config_pda(program_id).0, false),
+            AccountMeta::new_readonly(*new_admin, true),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::SlashDeposit] instruction taking the deposit of `user`.
+pub fn slash_deposit(program_id: &Pubkey, admin: &Pubkey, user: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::SlashDeposit,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*user, false),
+            AccountMeta::new(find_user_pda(program_id, user).0, false),
+            AccountMeta::new(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::Withdraw] instruction moving `lamports`
+/// from the config PDA to `recipient`.
+pub fn withdraw(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    recipient: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Withdraw(WithdrawInstructionData { lamports }),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(find_config_pda(program_id).0, false),
+            AccountMeta::new(*recipient, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::Flag] instruction flagging `user`.
+pub fn flag(program_id: &Pubkey, admin: &Pubkey, user: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Flag,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*user, false),
+            AccountMeta::new(find_user_pda(program_id, user).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::Unflag] instruction clearing the flag of `user`.
+pub fn unflag(program_id: &Pubkey, admin: &Pubkey, user: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Unflag,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(*user, false),
+            AccountMeta::new(find_user_pda(program_id, user).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CreateSession] instruction
+/// letting `delegate` sign the `allowed_instructions` of `owner`.
+pub fn create_session(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    expires_at_slot: u64,
+    allowed_instructions: u64,
+) -> Instruction {
+    let (session, _) = find_session_pda(program_id, owner, delegate);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CreateSession(CreateSessionInstructionData {
+            expires_at_slot,
+            allowed_instructions,
+        }),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(find_user_pda(program_id, owner).0, false),
+            AccountMeta::new(session, false),
+            AccountMeta::new_readonly(*delegate, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::RevokeSession] instruction signed by `signer`,
+/// either `owner` or `delegate`.
+pub fn revoke_session(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+) -> Instruction {
+    let (session, _) = find_session_pda(program_id, owner, delegate);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::RevokeSession,
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(session, false),
+            AccountMeta::new(*owner, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates an [ProgramInstruction::Endorse] instruction.
+pub fn endorse(program_id: &Pubkey, endorser: &Pubkey, endorsed: &Pubkey) -> Instruction {
+    let (endorsement, _) = find_endorsement_pda(program_id, endorser, endorsed);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Endorse,
+        vec![
+            AccountMeta::new(*endorser, true),
+            AccountMeta::new_readonly(find_user_pda(program_id, endorser).0, false),
+            AccountMeta::new(find_user_pda(program_id, endorsed).0, false),
+            AccountMeta::new(endorsement, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::RevokeEndorsement] instruction.
+pub fn revoke_endorsement(
+    program_id: &Pubkey,
+    endorser: &Pubkey,
+    endorsed: &Pubkey,
+) -> Instruction {
+    let (endorsement, _) = find_endorsement_pda(program_id, endorser, endorsed);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::RevokeEndorsement,
+        vec![
+            AccountMeta::new(*endorser, true),
+            AccountMeta::new(find_user_pda(program_id, endorsed).0, false),
+            AccountMeta::new(endorsement, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::Block] instruction blocking `user` for `owner`.
+pub fn block(program_id: &Pubkey, owner: &Pubkey, user: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Block,
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(find_user_pda(program_id, owner).0, false),
+            AccountMeta::new(find_block_list_pda(program_id, owner).0, false),
+            AccountMeta::new_readonly(*user, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::Unblock] instruction unblocking `user` for `owner`.
+pub fn unblock(program_id: &Pubkey, owner: &Pubkey, user: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Unblock,
+        vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(find_block_list_pda(program_id, owner).0, false),
+            AccountMeta::new_readonly(*user, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CreateGroup] instruction administered by `creator`.
+pub fn create_group(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    group_id: u64,
+    name: String,
+    invite_only: bool,
+    max_members: u32,
+) -> Instruction {
+    let (group, _) = find_group_pda(program_id, creator, group_id);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CreateGroup(CreateGroupInstructionData {
+            group_id,
+            name,
+            invite_only,
+            max_members,
+        }),
+        vec![
+            AccountMeta::new(*creator, true),
+            AccountMeta::new_readonly(find_user_pda(program_id, creator).0, false),
+            AccountMeta::new(group, false),
+            AccountMeta::new(find_membership_pda(program_id, &group, creator).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::JoinGroup] instruction.
+/// `admin` co-signs the instruction of an invite-only group.
+pub fn join_group(
+    program_id: &Pubkey,
+    member: &Pubkey,
+    group: &Pubkey,
+    admin: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*member, true),
+        AccountMeta::new_readonly(find_user_pda(program_id, member).0, false),
+        AccountMeta::new(*group, false),
+        AccountMeta::new(find_membership_pda(program_id, group, member).0, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+    ];
+    if let Some(admin) = admin {
+        accounts.push(AccountMeta::new_readonly(*admin, true));
+    }
+    new_instruction(program_id, &ProgramInstruction::JoinGroup, accounts)
+}
+
+/// Creates a [ProgramInstruction::LeaveGroup] instruction.
+pub fn leave_group(program_id: &Pubkey, member: &Pubkey, group: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::LeaveGroup,
+        vec![
+            AccountMeta::new(*member, true),
+            AccountMeta::new(*group, false),
+            AccountMeta::new(find_membership_pda(program_id, group, member).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::KickMember] instruction removing `member` from the group.
+pub fn kick_member(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    group: &Pubkey,
+    member: &Pubkey,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::KickMember,
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*group, false),
+            AccountMeta::new(find_membership_pda(program_id, group, member).0, false),
+            AccountMeta::new(*member, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CreateEvent] instruction organized by `organizer`.
+pub fn create_event(
+    program_id: &Pubkey,
+    organizer: &Pubkey,
+    event_id: u64,
+    title: String,
+    starts_at: i64,
+    location_hint: String,
+    capacity: u32,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CreateEvent(CreateEventInstructionData {
+            event_id,
+            title,
+            starts_at,
+            location_hint,
+            capacity,
+        }),
+        vec![
+            AccountMeta::new(*organizer, true),
+            AccountMeta::new_readonly(find_user_pda(program_id, organizer).0, false),
+            AccountMeta::new(find_event_pda(program_id, organizer, event_id).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::Rsvp] instruction.
+pub fn rsvp(program_id: &Pubkey, attendee: &Pubkey, event: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Rsvp,
+        vec![
+            AccountMeta::new(*attendee, true),
+            AccountMeta::new_readonly(find_user_pda(program_id, attendee).0, false),
+            AccountMeta::new(*event, false),
+            AccountMeta::new(find_rsvp_pda(program_id, event, attendee).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CancelRsvp] instruction.
+pub fn cancel_rsvp(program_id: &Pubkey, attendee: &Pubkey, event: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CancelRsvp,
+        vec![
+            AccountMeta::new(*attendee, true),
+            AccountMeta::new(*event, false),
+            AccountMeta::new(find_rsvp_pda(program_id, event, attendee).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CloseEvent] instruction.
+pub fn close_event(program_id: &Pubkey, organizer: &Pubkey, event: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CloseEvent,
+        vec![
+            AccountMeta::new_readonly(*organizer, true),
+            AccountMeta::new(*event, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CreateEscrow] instruction of a meeting
+/// proposed by `proposer` to `partner`.
+pub fn create_escrow(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    partner: &Pubkey,
+    meeting_id: u64,
+    stake: u64,
+    deadline_slot: u64,
+    charity: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*proposer, true),
+        AccountMeta::new_readonly(find_user_pda(program_id, proposer).0, false),
+        AccountMeta::new_readonly(*partner, false),
+        AccountMeta::new_readonly(find_user_pda(program_id, partner).0, false),
+        AccountMeta::new(
+            find_escrow_pda(program_id, proposer, partner, meeting_id).0,
+            false,
+        ),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+    ];
+    if let Some(charity) = charity {
+        accounts.push(AccountMeta::new_readonly(*charity, false));
+    }
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CreateEscrow(CreateEscrowInstructionData {
+            meeting_id,
+            stake,
+            deadline_slot,
+            charity: charity.copied(),
+        }),
+        accounts,
+    )
+}
+
+/// Creates a [ProgramInstruction::JoinEscrow] instruction.
+pub fn join_escrow(program_id: &Pubkey, partner: &Pubkey, escrow: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::JoinEscrow,
+        vec![
+            AccountMeta::new(*partner, true),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CheckIn] instruction.
+pub fn check_in(program_id: &Pubkey, participant: &Pubkey, escrow: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CheckIn,
+        vec![
+            AccountMeta::new_readonly(*participant, true),
+            AccountMeta::new(*escrow, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::SettleEscrow] instruction.
+/// `charity` needs to match the one the escrow was created with.
+pub fn settle_escrow(
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    proposer: &Pubkey,
+    partner: &Pubkey,
+    charity: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*escrow, false),
+        AccountMeta::new(*proposer, false),
+        AccountMeta::new(*partner, false),
+        AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+    ];
+    if let Some(charity) = charity {
+        accounts.push(AccountMeta::new(*charity, false));
+    }
+    new_instruction(program_id, &ProgramInstruction::SettleEscrow, accounts)
+}
+
+/// Creates a [ProgramInstruction::SetGuardians] instruction for the PDA of `owner`.
+pub fn set_guardians(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::SetGuardians(SetGuardiansInstructionData {
+            guardians,
+            threshold,
+        }),
+        vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(find_user_pda(program_id, owner).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::ProposeRecovery] instruction of `guardian`
+/// moving the profile of `owner` to `new_owner`.
+pub fn propose_recovery(
+    program_id: &Pubkey,
+    guardian: &Pubkey,
+    owner: &Pubkey,
+    new_owner: &Pubkey,
+) -> Instruction {
+    let (pda, _) = find_user_pda(program_id, owner);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::ProposeRecovery(ProposeRecoveryInstructionData {
+            new_owner: *new_owner,
+        }),
+        vec![
+            AccountMeta::new(*guardian, true),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(pda, false),
+            AccountMeta::new(find_recovery_pda(program_id, &pda).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::ApproveRecovery] instruction of `guardian`
+/// for the pending recovery of the PDA of `owner`.
+pub fn approve_recovery(program_id: &Pubkey, guardian: &Pubkey, owner: &Pubkey) -> Instruction {
+    let (pda, _) = find_user_pda(program_id, owner);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::ApproveRecovery,
+        vec![
+            AccountMeta::new_readonly(*guardian, true),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(pda, false),
+            AccountMeta::new(find_recovery_pda(program_id, &pda).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::ExecuteRecovery] instruction moving the profile
+/// of `owner` to `new_owner`. `username` is the username stored in the profile,
+/// `proposer` the guardian who proposed the recovery.
+pub fn execute_recovery(
+    program_id: &Pubkey,
+    new_owner: &Pubkey,
+    owner: &Pubkey,
+    username: &str,
+    proposer: &Pubkey,
+) -> Instruction {
+    let (pda, _) = find_user_pda(program_id, owner);
+    let (new_pda, pda_bump_seed) = find_user_pda(program_id, new_owner);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::ExecuteRecovery(TransferOwnershipInstructionData { pda_bump_seed }),
+        vec![
+            AccountMeta::new(*new_owner, true),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new(pda, false),
+            AccountMeta::new(new_pda, false),
+            AccountMeta::new(find_username_pda(program_id, username).0, false),
+            AccountMeta::new(find_block_list_pda(program_id, owner).0, false),
+            AccountMeta::new(find_block_list_pda(program_id, new_owner).0, false),
+            AccountMeta::new(find_recovery_pda(program_id, &pda).0, false),
+            AccountMeta::new(*proposer, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CancelRecovery] instruction for the pending recovery
+/// of the PDA of `owner`. `signer` is either `owner` or `proposer`.
+pub fn cancel_recovery(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    owner: &Pubkey,
+    proposer: &Pubkey,
+) -> Instruction {
+    let (pda, _) = find_user_pda(program_id, owner);
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CancelRecovery,
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(pda, false),
+            AccountMeta::new(find_recovery_pda(program_id, &pda).0, false),
+            AccountMeta::new(*proposer, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::Attest] instruction setting or clearing
+/// the verification `flags` of `user`.
+pub fn attest(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    user: &Pubkey,
+    flags: u64,
+    verified: bool,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Attest(AttestInstructionData { flags, verified }),
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*user, false),
+            AccountMeta::new(find_user_pda(program_id, user).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CreatePoll] instruction.
+/// With a `group`, only its members can vote.
+pub fn create_poll(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    poll_id: u64,
+    question: String,
+    options: Vec<String>,
+    group: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*creator, true),
+        AccountMeta::new_readonly(find_user_pda(program_id, creator).0, false),
+        AccountMeta::new(find_poll_pda(program_id, creator, poll_id).0, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+    ];
+    if let Some(group) = group {
+        accounts.push(AccountMeta::new_readonly(
+            find_membership_pda(program_id, group, creator).0,
+            false,
+        ));
+    }
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CreatePoll(CreatePollInstructionData {
+            poll_id,
+            question,
+            options,
+            group: group.copied(),
+        }),
+        accounts,
+    )
+}
+
+/// Creates a [ProgramInstruction::Vote] instruction.
+/// `group` needs to match the one the poll was created with.
+pub fn vote(
+    program_id: &Pubkey,
+    voter: &Pubkey,
+    poll: &Pubkey,
+    group: Option<&Pubkey>,
+    option: u8,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*voter, true),
+        AccountMeta::new_readonly(find_user_pda(program_id, voter).0, false),
+        AccountMeta::new(*poll, false),
+        AccountMeta::new(find_vote_pda(program_id, poll, voter).0, false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+    ];
+    if let Some(group) = group {
+        accounts.push(AccountMeta::new_readonly(
+            find_membership_pda(program_id, group, voter).0,
+            false,
+        ));
+    }
+    new_instruction(
+        program_id,
+        &ProgramInstruction::Vote(VoteInstructionData { option }),
+        accounts,
+    )
+}
+
+/// Creates a [ProgramInstruction::ClosePoll] instruction.
+pub fn close_poll(program_id: &Pubkey, creator: &Pubkey, poll: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::ClosePoll,
+        vec![
+            AccountMeta::new_readonly(*creator, true),
+            AccountMeta::new(*poll, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::CloseVote] instruction refunding the vote PDA of `voter`.
+pub fn close_vote(program_id: &Pubkey, voter: &Pubkey, poll: &Pubkey) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::CloseVote,
+        vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new_readonly(*poll, false),
+            AccountMeta::new(find_vote_pda(program_id, poll, voter).0, false),
+            AccountMeta::new_readonly(find_config_pda(program_id).0, false),
+        ],
+    )
+}
+
+/// Creates a [ProgramInstruction::VerifyMember] instruction checking that `user`
+/// is a member in good standing with all the `required_verifications` flags.
+pub fn verify_member(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    required_verifications: u64,
+) -> Instruction {
+    new_instruction(
+        program_id,
+        &ProgramInstruction::VerifyMember(VerifyMemberInstructionData {
+            required_verifications,
+        }),
+        vec![
+            AccountMeta::new_readonly(*user, false),
+            AccountMeta::new_readonly(find_user_pda(program_id, user).0, false),
+        ],
+    )
+}
+
+/// Turns an instruction built for `owner` into one signed by `delegate`
+/// with the session of `owner`. Only the instructions in [SESSION_ALL] accept it.
+pub fn with_session(
+    mut instruction: Instruction,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+) -> Instruction {
+    let (session, _) = find_session_pda(&instruction.program_id, owner, delegate);
+    instruction.accounts[0] = AccountMeta::new_readonly(*delegate, true);
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(session, false));
+    instruction
+}
+
+pub(crate) fn new_instruction(
+    program_id: &Pubkey,
+    instruction: &ProgramInstruction,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let data = instruction
+        .pack()
+        .expect("Serializing into a vector should never fail");
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_initialize() {
+        let instruction = ProgramInstruction::Initialize(InitializeInstructionData {
+            lamports: 3213,
+            pda_bump_seed: 255,
+            username: "paulinka".to_string(),
+            referrer: Some(Pubkey::new_unique()),
+        });
+
+        let packed = instruction.pack().unwrap();
+        assert_eq!(1, *packed.first().unwrap());
+        let unpacked = ProgramInstruction::unpack(&packed).unwrap();
+        assert_eq!(instruction, unpacked);
+    }
+
+    #[test]
+    fn serialize_update_profile() {
+        let instruction = ProgramInstruction::UpdateProfile(UpdateProfileInstructionData {
+            username: "paulinka".to_string(),
+            bio: "Looking for hiking buddies".to_string(),
+            interests: vec!["hiking".to_string()],
+        });
+
+        let packed = instruction.pack().unwrap();
+        assert_eq!(3, *packed.first().unwrap());
+        let unpacked = ProgramInstruction::unpack(&packed).unwrap();
+        assert_eq!(instruction, unpacked);
+    }
+
+    #[test]
+    fn serialize_resize() {
+        let instruction = ProgramInstruction::Resize(ResizeInstructionData { new_size: 2048 });
+
+        let packed = instruction.pack().unwrap();
+        assert_eq!(4, *packed.first().unwrap());
+        let unpacked = ProgramInstruction::unpack(&packed).unwrap();
+        assert_eq!(instruction, unpacked);
+    }
+
+    #[test]
+    fn serialize_connection_instructions() {
+        for (tag, instruction) in [
+            (5, ProgramInstruction::RequestConnection),
+            (6, ProgramInstruction::AcceptConnection),
+            (7, ProgramInstruction::RejectConnection),
+            (8, ProgramInstruction::RemoveConnection),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(vec![tag], packed);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialize_claim_username() {
+        let instruction = ProgramInstruction::ClaimUsername(ClaimUsernameInstructionData {
+            username: "paulinka".to_string(),
+        });
+
+        let packed = instruction.pack().unwrap();
+        assert_eq!(9, packed[0]);
+        let unpacked = ProgramInstruction::unpack(&packed).unwrap();
+        assert_eq!(instruction, unpacked);
+    }
+
+    #[test]
+    fn serialize_config_instructions() {
+        let config = ConfigInstructionData {
+            paused: true,
+            max_user_pda_size: 4096,
+            registration_fee: 5000,
+            deposit: 10000,
+            attestation_authority: Pubkey::new_unique(),
+            referral_reward: 1000,
+        };
+        for (tag, instruction) in [
+            (10, ProgramInstruction::InitializeConfig(config.clone())),
+            (11, ProgramInstruction::UpdateConfig(config)),
+            (12, ProgramInstruction::TransferAuthority),
+            (27, ProgramInstruction::SlashDeposit),
+            (
+                38,
+                ProgramInstruction::Attest(AttestInstructionData {
+                    flags: crate::state::VERIFIED_IN_PERSON,
+                    verified: true,
+                }),
+            ),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(tag, packed[0]);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialize_session_instructions() {
+        let instruction = ProgramInstruction::CreateSession(CreateSessionInstructionData {
+            expires_at_slot: 1000,
+            allowed_instructions: SESSION_ALL,
+        });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(13, packed[0]);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+
+        let packed = ProgramInstruction::RevokeSession.pack().unwrap();
+        assert_eq!(vec![14], packed);
+        assert_eq!(
+            ProgramInstruction::RevokeSession,
+            ProgramInstruction::unpack(&packed).unwrap()
+        );
+    }
+
+    #[test]
+    fn serialize_endorsement_instructions() {
+        for (tag, instruction) in [
+            (15, ProgramInstruction::Endorse),
+            (16, ProgramInstruction::RevokeEndorsement),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(vec![tag], packed);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialize_block_instructions() {
+        for (tag, instruction) in [
+            (17, ProgramInstruction::Block),
+            (18, ProgramInstruction::Unblock),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(vec![tag], packed);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialize_group_instructions() {
+        let instruction = ProgramInstruction::CreateGroup(CreateGroupInstructionData {
+            group_id: 7,
+            name: "Hiking in Berlin".to_string(),
+            invite_only: true,
+            max_members: 20,
+        });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(19, packed[0]);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+
+        for (tag, instruction) in [
+            (20, ProgramInstruction::JoinGroup),
+            (21, ProgramInstruction::LeaveGroup),
+            (22, ProgramInstruction::KickMember),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(vec![tag], packed);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialize_event_instructions() {
+        let instruction = ProgramInstruction::CreateEvent(CreateEventInstructionData {
+            event_id: 7,
+            title: "Picnic".to_string(),
+            starts_at: 1_700_000_000,
+            location_hint: "Tempelhofer Feld".to_string(),
+            capacity: 12,
+        });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(23, packed[0]);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+
+        for (tag, instruction) in [
+            (24, ProgramInstruction::Rsvp),
+            (25, ProgramInstruction::CancelRsvp),
+            (26, ProgramInstruction::CloseEvent),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(vec![tag], packed);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialize_poll_instructions() {
+        let instruction = ProgramInstruction::CreatePoll(CreatePollInstructionData {
+            poll_id: 7,
+            question: "Where do we meet?".to_string(),
+            options: vec!["Park".to_string(), "Cafe".to_string()],
+            group: Some(Pubkey::new_unique()),
+        });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(39, packed[0]);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+
+        let instruction = ProgramInstruction::Vote(VoteInstructionData { option: 1 });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(vec![40, 1], packed);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+
+        for (instruction, tag) in [
+            (ProgramInstruction::ClosePoll, 41),
+            (ProgramInstruction::CloseVote, 46),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(vec![tag], packed);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialize_verify_member() {
+        let instruction = ProgramInstruction::VerifyMember(VerifyMemberInstructionData {
+            required_verifications: crate::state::VERIFIED_IN_PERSON,
+        });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(42, packed[0]);
+        assert_eq!(9, packed.len());
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+    }
+
+    #[test]
+    fn serialize_withdraw() {
+        let instruction = ProgramInstruction::Withdraw(WithdrawInstructionData { lamports: 5000 });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(43, packed[0]);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+    }
+
+    #[test]
+    fn serialize_flag() {
+        for (instruction, tag) in [
+            (ProgramInstruction::Flag, 44),
+            (ProgramInstruction::Unflag, 45),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(vec![tag], packed);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialize_transfer_ownership() {
+        let instruction = ProgramInstruction::TransferOwnership(TransferOwnershipInstructionData {
+            pda_bump_seed: 253,
+        });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(vec![32, 253], packed);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+    }
+
+    #[test]
+    fn serialize_escrow_instructions() {
+        for charity in [None, Some(Pubkey::new_unique())] {
+            let instruction = ProgramInstruction::CreateEscrow(CreateEscrowInstructionData {
+                meeting_id: 7,
+                stake: 1_000_000,
+                deadline_slot: 4200,
+                charity,
+            });
+            let packed = instruction.pack().unwrap();
+            assert_eq!(28, packed[0]);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+
+        for (tag, instruction) in [
+            (29, ProgramInstruction::JoinEscrow),
+            (30, ProgramInstruction::CheckIn),
+            (31, ProgramInstruction::SettleEscrow),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(vec![tag], packed);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn serialize_recovery_instructions() {
+        let instruction = ProgramInstruction::SetGuardians(SetGuardiansInstructionData {
+            guardians: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            threshold: 2,
+        });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(33, packed[0]);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+
+        let instruction = ProgramInstruction::ProposeRecovery(ProposeRecoveryInstructionData {
+            new_owner: Pubkey::new_unique(),
+        });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(34, packed[0]);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+
+        let instruction = ProgramInstruction::ExecuteRecovery(TransferOwnershipInstructionData {
+            pda_bump_seed: 254,
+        });
+        let packed = instruction.pack().unwrap();
+        assert_eq!(vec![36, 254], packed);
+        assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+
+        for (tag, instruction) in [
+            (35, ProgramInstruction::ApproveRecovery),
+            (37, ProgramInstruction::CancelRecovery),
+        ] {
+            let packed = instruction.pack().unwrap();
+            assert_eq!(vec![tag], packed);
+            assert_eq!(instruction, ProgramInstruction::unpack(&packed).unwrap());
+        }
+    }
+
+    #[test]
+    fn session_flags() {
+        assert_eq!(1 << 3, SESSION_UPDATE_PROFILE);
+        assert_eq!(1 << 6, SESSION_ACCEPT_CONNECTION);
+        assert_eq!(1 << 7, SESSION_REJECT_CONNECTION);
+        assert_eq!(1 << 8, SESSION_REMOVE_CONNECTION);
+    }
+
+    #[test]
+    fn build_with_session() {
+        let program_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let (pda, _) = find_user_pda(&program_id, &owner);
+        let (session, _) = find_session_pda(&program_id, &owner, &delegate);
+
+        let instruction = with_session(
+            update_profile(
+                &program_id,
+                &owner,
+                "paulinka".to_string(),
+                String::new(),
+                vec![],
+            ),
+            &owner,
+            &delegate,
+        );
+        assert_eq!(
+            vec![
+                AccountMeta::new_readonly(delegate, true),
+                AccountMeta::new(pda, false),
+                AccountMeta::new_readonly(find_config_pda(&program_id).0, false),
+                AccountMeta::new_readonly(session, false),
+            ],
+            instruction.accounts
+        );
+    }
+
+    #[test]
+    fn build_initialize() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let (pda, pda_bump_seed) = find_user_pda(&program_id, &payer);
+        let (username_pda, _) = find_username_pda(&program_id, "Paulinka");
+
+        let instruction = initialize(&program_id, &payer, 3213, "paulinka".to_string());
+        assert_eq!(program_id, instruction.program_id);
+        assert_eq!(
+            vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(pda, false),
+                AccountMeta::new(username_pda, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(find_config_pda(&program_id).0, false),
+            ],
+            instruction.accounts
+        );
+        assert_eq!(
+            ProgramInstruction::Initialize(InitializeInstructionData {
+                lamports: 3213,
+                pda_bump_seed,
+                username: "paulinka".to_string(),
+                referrer: None,
+            }),
+            ProgramInstruction::unpack(&instruction.data).unwrap()
+        );
+
+        // The referral accounts follow the config PDA.
+        let referrer = Pubkey::new_unique();
+        let instruction = initialize_with_referrer(
+            &program_id,
+            &payer,
+            3213,
+            "paulinka".to_string(),
+            Some(&referrer),
+        );
+        assert_eq!(
+            vec![
+                AccountMeta::new(referrer, false),
+                AccountMeta::new(find_user_pda(&program_id, &referrer).0, false),
+                AccountMeta::new(find_referral_pda(&program_id, &payer).0, false),
+            ],
+            instruction.accounts[5..]
+        );
+    }
+
+    #[test]
+    fn build_vote() {
+        let program_id = Pubkey::new_unique();
+        let voter = Pubkey::new_unique();
+        let poll = Pubkey::new_unique();
+        let group = Pubkey::new_unique();
+
+        let instruction = vote(&program_id, &voter, &poll, None, 1);
+        assert_eq!(6, instruction.accounts.len());
+        // The membership PDA follows the config PDA.
+        let instruction = vote(&program_id, &voter, &poll, Some(&group), 1);
+        assert_eq!(
+            vec![
+                AccountMeta::new_readonly(find_config_pda(&program_id).0, false),
+                AccountMeta::new_readonly(
+                    find_membership_pda(&program_id, &group, &voter).0,
+                    false
+                ),
+            ],
+            instruction.accounts[5..]
+        );
+    }
+}
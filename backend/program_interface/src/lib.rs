@@ -0,0 +1,10 @@
+//! Interface of the anti-loneliness program, shared by the program itself
+//! and its clients: instructions, account layouts, events, PDA derivation and errors.
+//! Other programs check the membership of a wallet with the [cpi] helpers.
+
+pub mod cpi;
+pub mod error;
+pub mod event;
+pub mod instruction;
+pub mod pda;
+pub mod state;
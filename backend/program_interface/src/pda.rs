@@ -0,0 +1,518 @@
+use crate::state::normalize_username;
+use solana_program::pubkey::{Pubkey, PubkeyError};
+
+/// Size of the account allocated for the user PDA.
+/// The account can be resized later in steps of this size.
+pub const USER_PDA_SIZE: u64 = 1024;
+/// Maximum size of the user PDA after resizing.
+pub const MAX_USER_PDA_SIZE: u64 = 10 * USER_PDA_SIZE;
+pub const USER_PDA_SEED_PREFIX: &[u8] = b"user";
+
+/// Size of the account allocated for the connection PDA.
+pub const CONNECTION_PDA_SIZE: u64 = 128;
+pub const CONNECTION_PDA_SEED_PREFIX: &[u8] = b"connection";
+
+/// Size of the account allocated for the program config PDA.
+pub const CONFIG_PDA_SIZE: u64 = 128;
+pub const CONFIG_PDA_SEED_PREFIX: &[u8] = b"config";
+
+/// Size of the account allocated for the username PDA.
+pub const USERNAME_PDA_SIZE: u64 = 96;
+pub const USERNAME_PDA_SEED_PREFIX: &[u8] = b"username";
+
+/// Size of the account allocated for the session PDA.
+pub const SESSION_PDA_SIZE: u64 = 128;
+pub const SESSION_PDA_SEED_PREFIX: &[u8] = b"session";
+
+/// Size of the account allocated for the endorsement PDA.
+pub const ENDORSEMENT_PDA_SIZE: u64 = 96;
+pub const ENDORSEMENT_PDA_SEED_PREFIX: &[u8] = b"endorsement";
+
+/// Size of the account allocated for the block list PDA.
+pub const BLOCK_LIST_PDA_SIZE: u64 = 1024;
+pub const BLOCK_LIST_PDA_SEED_PREFIX: &[u8] = b"blocklist";
+
+/// Size of the account allocated for the group PDA.
+pub const GROUP_PDA_SIZE: u64 = 160;
+pub const GROUP_PDA_SEED_PREFIX: &[u8] = b"group";
+
+/// Size of the account allocated for the group membership PDA.
+pub const MEMBERSHIP_PDA_SIZE: u64 = 96;
+pub const MEMBERSHIP_PDA_SEED_PREFIX: &[u8] = b"membership";
+
+/// Size of the account allocated for the event PDA.
+pub const EVENT_PDA_SIZE: u64 = 192;
+pub const EVENT_PDA_SEED_PREFIX: &[u8] = b"event";
+
+/// Size of the account allocated for the RSVP PDA.
+pub const RSVP_PDA_SIZE: u64 = 96;
+pub const RSVP_PDA_SEED_PREFIX: &[u8] = b"rsvp";
+
+/// Size of the account allocated for the meetup escrow PDA.
+pub const ESCROW_PDA_SIZE: u64 = 160;
+pub const ESCROW_PDA_SEED_PREFIX: &[u8] = b"escrow";
+
+/// Size of the account allocated for the recovery PDA.
+pub const RECOVERY_PDA_SIZE: u64 = 288;
+pub const RECOVERY_PDA_SEED_PREFIX: &[u8] = b"recovery";
+
+/// Size of the account allocated for the referral PDA.
+pub const REFERRAL_PDA_SIZE: u64 = 128;
+pub const REFERRAL_PDA_SEED_PREFIX: &[u8] = b"referral";
+
+/// Size of the account allocated for the poll PDA.
+pub const POLL_PDA_SIZE: u64 = 512;
+pub const POLL_PDA_SEED_PREFIX: &[u8] = b"poll";
+
+/// Size of the account allocated for the vote PDA.
+pub const VOTE_PDA_SIZE: u64 = 96;
+pub const VOTE_PDA_SEED_PREFIX: &[u8] = b"vote";
+
+/// Finds the canonical user PDA of `wallet` and its bump seed.
+pub fn find_user_pda(program_id: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[USER_PDA_SEED_PREFIX, wallet.as_ref()], program_id)
+}
+
+/// Derives the user PDA of `wallet` with a known bump seed.
+pub fn create_user_pda(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[USER_PDA_SEED_PREFIX, wallet.as_ref(), &[bump_seed]],
+        program_id,
+    )
+}
+
+/// Orders the pubkeys of two users, so that both users derive the same PDA.
+pub fn canonical_pair(user: &Pubkey, other_user: &Pubkey) -> (Pubkey, Pubkey) {
+    if user <= other_user {
+        (*user, *other_user)
+    } else {
+        (*other_user, *user)
+    }
+}
+
+/// Finds the canonical connection PDA of two users and its bump seed.
+/// The order of the users doesn't matter.
+pub fn find_connection_pda(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    other_user: &Pubkey,
+) -> (Pubkey, u8) {
+    let (user_a, user_b) = canonical_pair(user, other_user);
+    Pubkey::find_program_address(
+        &[CONNECTION_PDA_SEED_PREFIX, user_a.as_ref(), user_b.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the connection PDA of two users with a known bump seed.
+pub fn create_connection_pda(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    other_user: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    let (user_a, user_b) = canonical_pair(user, other_user);
+    Pubkey::create_program_address(
+        &[
+            CONNECTION_PDA_SEED_PREFIX,
+            user_a.as_ref(),
+            user_b.as_ref(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+/// Finds the canonical program config PDA and its bump seed.
+pub fn find_config_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_PDA_SEED_PREFIX], program_id)
+}
+
+/// Derives the program config PDA with a known bump seed.
+pub fn create_config_pda(program_id: &Pubkey, bump_seed: u8) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(&[CONFIG_PDA_SEED_PREFIX, &[bump_seed]], program_id)
+}
+
+/// Finds the canonical username PDA and its bump seed.
+/// The username is normalized first, see [normalize_username].
+pub fn find_username_pda(program_id: &Pubkey, username: &str) -> (Pubkey, u8) {
+    let username = normalize_username(username);
+    Pubkey::find_program_address(&[USERNAME_PDA_SEED_PREFIX, username.as_bytes()], program_id)
+}
+
+/// Derives the username PDA with a known bump seed.
+pub fn create_username_pda(
+    program_id: &Pubkey,
+    username: &str,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    let username = normalize_username(username);
+    Pubkey::create_program_address(
+        &[USERNAME_PDA_SEED_PREFIX, username.as_bytes(), &[bump_seed]],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the session of `owner` with `delegate` and its bump seed.
+pub fn find_session_pda(program_id: &Pubkey, owner: &Pubkey, delegate: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SESSION_PDA_SEED_PREFIX, owner.as_ref(), delegate.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the session PDA of `owner` with `delegate` with a known bump seed.
+pub fn create_session_pda(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[
+            SESSION_PDA_SEED_PREFIX,
+            owner.as_ref(),
+            delegate.as_ref(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the endorsement of `endorsed` by `endorser` and its bump seed.
+/// Unlike the connection PDA, the order of the users matters.
+pub fn find_endorsement_pda(
+    program_id: &Pubkey,
+    endorser: &Pubkey,
+    endorsed: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ENDORSEMENT_PDA_SEED_PREFIX,
+            endorser.as_ref(),
+            endorsed.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the endorsement PDA with a known bump seed.
+pub fn create_endorsement_pda(
+    program_id: &Pubkey,
+    endorser: &Pubkey,
+    endorsed: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[
+            ENDORSEMENT_PDA_SEED_PREFIX,
+            endorser.as_ref(),
+            endorsed.as_ref(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+/// Finds the canonical block list PDA of `owner` and its bump seed.
+pub fn find_block_list_pda(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BLOCK_LIST_PDA_SEED_PREFIX, owner.as_ref()], program_id)
+}
+
+/// Derives the block list PDA of `owner` with a known bump seed.
+pub fn create_block_list_pda(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[BLOCK_LIST_PDA_SEED_PREFIX, owner.as_ref(), &[bump_seed]],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the group created by `creator` with `group_id` and its bump seed.
+/// The id is chosen by the creator, each of their groups needs a different one.
+pub fn find_group_pda(program_id: &Pubkey, creator: &Pubkey, group_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            GROUP_PDA_SEED_PREFIX,
+            creator.as_ref(),
+            &group_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the group PDA with a known bump seed.
+pub fn create_group_pda(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    group_id: u64,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[
+            GROUP_PDA_SEED_PREFIX,
+            creator.as_ref(),
+            &group_id.to_le_bytes(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the membership of `member` in `group` and its bump seed.
+pub fn find_membership_pda(program_id: &Pubkey, group: &Pubkey, member: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[MEMBERSHIP_PDA_SEED_PREFIX, group.as_ref(), member.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the membership PDA with a known bump seed.
+pub fn create_membership_pda(
+    program_id: &Pubkey,
+    group: &Pubkey,
+    member: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[
+            MEMBERSHIP_PDA_SEED_PREFIX,
+            group.as_ref(),
+            member.as_ref(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the event organized by `organizer` with `event_id` and its bump seed.
+/// The id is chosen by the organizer, each of their events needs a different one.
+pub fn find_event_pda(program_id: &Pubkey, organizer: &Pubkey, event_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            EVENT_PDA_SEED_PREFIX,
+            organizer.as_ref(),
+            &event_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the event PDA with a known bump seed.
+pub fn create_event_pda(
+    program_id: &Pubkey,
+    organizer: &Pubkey,
+    event_id: u64,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[
+            EVENT_PDA_SEED_PREFIX,
+            organizer.as_ref(),
+            &event_id.to_le_bytes(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the RSVP of `attendee` to `event` and its bump seed.
+pub fn find_rsvp_pda(program_id: &Pubkey, event: &Pubkey, attendee: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RSVP_PDA_SEED_PREFIX, event.as_ref(), attendee.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the RSVP PDA with a known bump seed.
+pub fn create_rsvp_pda(
+    program_id: &Pubkey,
+    event: &Pubkey,
+    attendee: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[
+            RSVP_PDA_SEED_PREFIX,
+            event.as_ref(),
+            attendee.as_ref(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the escrow of a meeting proposed by `proposer` to `partner`
+/// and its bump seed.
+pub fn find_escrow_pda(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    partner: &Pubkey,
+    meeting_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ESCROW_PDA_SEED_PREFIX,
+            proposer.as_ref(),
+            partner.as_ref(),
+            &meeting_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the escrow PDA with a known bump seed.
+pub fn create_escrow_pda(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    partner: &Pubkey,
+    meeting_id: u64,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[
+            ESCROW_PDA_SEED_PREFIX,
+            proposer.as_ref(),
+            partner.as_ref(),
+            &meeting_id.to_le_bytes(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the pending recovery of `user_pda` and its bump seed.
+/// A user PDA has at most one recovery pending.
+pub fn find_recovery_pda(program_id: &Pubkey, user_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECOVERY_PDA_SEED_PREFIX, user_pda.as_ref()], program_id)
+}
+
+/// Derives the recovery PDA with a known bump seed.
+pub fn create_recovery_pda(
+    program_id: &Pubkey,
+    user_pda: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[RECOVERY_PDA_SEED_PREFIX, user_pda.as_ref(), &[bump_seed]],
+        program_id,
+    )
+}
+
+/// Finds the canonical referral PDA of the new user with the wallet `user` and its bump seed.
+pub fn find_referral_pda(program_id: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REFERRAL_PDA_SEED_PREFIX, user.as_ref()], program_id)
+}
+
+/// Derives the referral PDA with a known bump seed.
+pub fn create_referral_pda(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[REFERRAL_PDA_SEED_PREFIX, user.as_ref(), &[bump_seed]],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the poll created by `creator` with `poll_id` and its bump seed.
+/// The id is chosen by the creator, each of their polls needs a different one.
+pub fn find_poll_pda(program_id: &Pubkey, creator: &Pubkey, poll_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            POLL_PDA_SEED_PREFIX,
+            creator.as_ref(),
+            &poll_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Derives the poll PDA with a known bump seed.
+pub fn create_poll_pda(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    poll_id: u64,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[
+            POLL_PDA_SEED_PREFIX,
+            creator.as_ref(),
+            &poll_id.to_le_bytes(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+/// Finds the canonical PDA of the vote of `voter` in `poll` and its bump seed.
+pub fn find_vote_pda(program_id: &Pubkey, poll: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VOTE_PDA_SEED_PREFIX, poll.as_ref(), voter.as_ref()],
+        program_id,
+    )
+}
+
+/// Derives the vote PDA with a known bump seed.
+pub fn create_vote_pda(
+    program_id: &Pubkey,
+    poll: &Pubkey,
+    voter: &Pubkey,
+    bump_seed: u8,
+) -> Result<Pubkey, PubkeyError> {
+    Pubkey::create_program_address(
+        &[
+            VOTE_PDA_SEED_PREFIX,
+            poll.as_ref(),
+            voter.as_ref(),
+            &[bump_seed],
+        ],
+        program_id,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_pda_order() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let other_user = Pubkey::new_unique();
+
+        let (pda, bump_seed) = find_connection_pda(&program_id, &user, &other_user);
+        assert_eq!(
+            (pda, bump_seed),
+            find_connection_pda(&program_id, &other_user, &user)
+        );
+        assert_eq!(
+            Ok(pda),
+            create_connection_pda(&program_id, &other_user, &user, bump_seed)
+        );
+    }
+
+    #[test]
+    fn username_pda_normalized() {
+        let program_id = Pubkey::new_unique();
+        let (pda, bump_seed) = find_username_pda(&program_id, "paulinka");
+        assert_eq!(
+            (pda, bump_seed),
+            find_username_pda(&program_id, " Paulinka")
+        );
+        assert_ne!(pda, find_username_pda(&program_id, "paulinka2").0);
+        assert_eq!(
+            Ok(pda),
+            create_username_pda(&program_id, "PAULINKA", bump_seed)
+        );
+    }
+}
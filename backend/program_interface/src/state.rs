@@ -0,0 +1,1603 @@
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{msg, program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// Marks the account data as a [UserProfile].
+pub const USER_PROFILE_DISCRIMINATOR: [u8; 8] = *b"userprof";
+/// Current schema version of [UserProfile].
+/// New fields are only ever appended, so older versions can still be decoded:
+/// the zeroed tail of the account deserializes to default values.
+///
+/// Versions:
+/// 1. owner, bump seed, username, created-at slot
+/// 2. bio, interests
+/// 3. endorsement count
+/// 4. guardians, guardian threshold
+/// 5. verifications
+/// 6. invite count
+/// 7. flagged
+pub const USER_PROFILE_VERSION: u8 = 7;
+
+/// Marks the account data as a [Connection].
+pub const CONNECTION_DISCRIMINATOR: [u8; 8] = *b"connectn";
+/// Current schema version of [Connection].
+pub const CONNECTION_VERSION: u8 = 1;
+
+/// Marks the account data as a [ProgramConfig].
+pub const PROGRAM_CONFIG_DISCRIMINATOR: [u8; 8] = *b"progconf";
+/// Current schema version of [ProgramConfig].
+///
+/// Versions:
+/// 1. admin, paused, max user PDA size, registration fee, bump seed
+/// 2. deposit
+/// 3. attestation authority
+/// 4. referral reward
+pub const PROGRAM_CONFIG_VERSION: u8 = 4;
+
+/// Marks the account data as a [UsernameClaim].
+pub const USERNAME_CLAIM_DISCRIMINATOR: [u8; 8] = *b"username";
+/// Current schema version of [UsernameClaim].
+pub const USERNAME_CLAIM_VERSION: u8 = 1;
+
+/// Marks the account data as a [Session].
+pub const SESSION_DISCRIMINATOR: [u8; 8] = *b"usession";
+/// Current schema version of [Session].
+pub const SESSION_VERSION: u8 = 1;
+
+/// Marks the account data as an [Endorsement].
+pub const ENDORSEMENT_DISCRIMINATOR: [u8; 8] = *b"endorsmt";
+/// Current schema version of [Endorsement].
+pub const ENDORSEMENT_VERSION: u8 = 1;
+
+/// Marks the account data as a [BlockList].
+pub const BLOCK_LIST_DISCRIMINATOR: [u8; 8] = *b"blocklst";
+/// Current schema version of [BlockList].
+pub const BLOCK_LIST_VERSION: u8 = 1;
+
+/// Marks the account data as a [Group].
+pub const GROUP_DISCRIMINATOR: [u8; 8] = *b"usrgroup";
+/// Current schema version of [Group].
+pub const GROUP_VERSION: u8 = 1;
+
+/// Marks the account data as a [Membership].
+pub const MEMBERSHIP_DISCRIMINATOR: [u8; 8] = *b"grmember";
+/// Current schema version of [Membership].
+pub const MEMBERSHIP_VERSION: u8 = 1;
+
+/// Marks the account data as an [Event].
+pub const EVENT_DISCRIMINATOR: [u8; 8] = *b"meetupev";
+/// Current schema version of [Event].
+pub const EVENT_VERSION: u8 = 1;
+
+/// Marks the account data as an [Rsvp].
+pub const RSVP_DISCRIMINATOR: [u8; 8] = *b"evntrsvp";
+/// Current schema version of [Rsvp].
+pub const RSVP_VERSION: u8 = 1;
+
+/// Marks the account data as an [Escrow].
+pub const ESCROW_DISCRIMINATOR: [u8; 8] = *b"mtescrow";
+/// Current schema version of [Escrow].
+pub const ESCROW_VERSION: u8 = 1;
+
+/// Marks the account data as a [Recovery].
+pub const RECOVERY_DISCRIMINATOR: [u8; 8] = *b"recovery";
+/// Current schema version of [Recovery].
+pub const RECOVERY_VERSION: u8 = 1;
+
+/// Marks the account data as a [Referral].
+pub const REFERRAL_DISCRIMINATOR: [u8; 8] = *b"referral";
+/// Current schema version of [Referral].
+pub const REFERRAL_VERSION: u8 = 1;
+
+/// Marks the account data as a [Poll].
+pub const POLL_DISCRIMINATOR: [u8; 8] = *b"cmtypoll";
+/// Current schema version of [Poll].
+pub const POLL_VERSION: u8 = 1;
+
+/// Marks the account data as a [Vote].
+pub const VOTE_DISCRIMINATOR: [u8; 8] = *b"pollvote";
+/// Current schema version of [Vote].
+pub const VOTE_VERSION: u8 = 1;
+
+pub const MAX_USERNAME_LEN: usize = 32;
+pub const MAX_BIO_LEN: usize = 256;
+pub const MAX_INTERESTS: usize = 8;
+pub const MAX_INTEREST_LEN: usize = 32;
+/// Maximum number of users in a [BlockList], limited by the size of the block list PDA.
+pub const MAX_BLOCKED_USERS: usize = 30;
+pub const MAX_GROUP_NAME_LEN: usize = 32;
+pub const MAX_EVENT_TITLE_LEN: usize = 32;
+pub const MAX_LOCATION_HINT_LEN: usize = 64;
+pub const MAX_POLL_QUESTION_LEN: usize = 64;
+pub const MAX_POLL_OPTIONS: usize = 8;
+pub const MAX_POLL_OPTION_LEN: usize = 32;
+/// Maximum number of guardians of a user, limited by the size of the recovery PDA.
+pub const MAX_GUARDIANS: usize = 5;
+/// Slots between proposing a recovery and executing it, about two days.
+/// Leaves the owner time to cancel a recovery they didn't ask for.
+pub const RECOVERY_TIMELOCK_SLOTS: u64 = 432_000;
+
+/// Verification flags of a [UserProfile], attested by the backend authority.
+pub const VERIFIED_ONBOARDING: u64 = 1 << 0;
+pub const VERIFIED_MODERATOR: u64 = 1 << 1;
+pub const VERIFIED_IN_PERSON: u64 = 1 << 2;
+/// Combination of all the known verification flags.
+pub const VERIFIED_ALL: u64 = VERIFIED_ONBOARDING | VERIFIED_MODERATOR | VERIFIED_IN_PERSON;
+
+/// Data stored in the user's PDA.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct UserProfile {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// Wallet the PDA was derived from.
+    pub owner: Pubkey,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    pub username: String,
+    pub created_at_slot: u64,
+    pub bio: String,
+    pub interests: Vec<String>,
+    /// Number of users currently endorsing this user.
+    pub endorsement_count: u64,
+    /// Refundable lamports locked in the PDA on top of the rent,
+    /// returned when the account is closed unless slashed before.
+    pub deposit: u64,
+    /// Users who can move the profile to a new wallet if the owner loses the key.
+    pub guardians: Vec<Pubkey>,
+    /// Number of guardians needed to approve a recovery, 0 if there are no guardians.
+    pub guardian_threshold: u8,
+    /// Combination of the VERIFIED_* flags, only set by the attestation authority.
+    pub verifications: u64,
+    /// Number of users who registered with this user as the referrer.
+    pub invite_count: u64,
+    /// Set by the admin when the user broke the rules,
+    /// a flagged user is no longer verified as a member.
+    pub flagged: bool,
+}
+
+impl UserProfile {
+    pub fn new(owner: Pubkey, bump_seed: u8, username: String, created_at_slot: u64) -> Self {
+        UserProfile {
+            discriminator: USER_PROFILE_DISCRIMINATOR,
+            version: USER_PROFILE_VERSION,
+            owner,
+            bump_seed,
+            username,
+            created_at_slot,
+            bio: String::new(),
+            interests: vec![],
+            endorsement_count: 0,
+            deposit: 0,
+            guardians: vec![],
+            guardian_threshold: 0,
+            verifications: 0,
+            invite_count: 0,
+            flagged: false,
+        }
+    }
+
+    /// Whether all the given VERIFIED_* flags are attested.
+    pub fn is_verified(&self, flags: u64) -> bool {
+        self.verifications & flags == flags
+    }
+
+    pub fn is_guardian(&self, user: &Pubkey) -> bool {
+        self.guardians.contains(user)
+    }
+
+    /// Unpacks the account data into a [UserProfile].
+    /// Trailing bytes of the account are ignored.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &USER_PROFILE_DISCRIMINATOR, USER_PROFILE_VERSION)
+    }
+
+    /// Number of bytes taken by the packed [UserProfile].
+    pub fn packed_len(&self) -> usize {
+        borsh::object_length(self).unwrap_or(usize::MAX)
+    }
+
+    /// Packs the [UserProfile] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Status of a [Connection] between two users.
+#[derive(Clone, Copy, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum ConnectionStatus {
+    /// Requested by one user, waiting for the other one.
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+/// Data stored in the connection PDA of two users.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Connection {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// The user with the lower pubkey, first in the PDA seeds.
+    pub user_a: Pubkey,
+    /// The user with the higher pubkey, second in the PDA seeds.
+    pub user_b: Pubkey,
+    /// User who requested the connection and paid for the PDA.
+    pub requester: Pubkey,
+    pub status: ConnectionStatus,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    pub created_at_slot: u64,
+    pub updated_at_slot: u64,
+}
+
+impl Connection {
+    pub fn new(requester: Pubkey, recipient: Pubkey, bump_seed: u8, created_at_slot: u64) -> Self {
+        let (user_a, user_b) = crate::pda::canonical_pair(&requester, &recipient);
+        Connection {
+            discriminator: CONNECTION_DISCRIMINATOR,
+            version: CONNECTION_VERSION,
+            user_a,
+            user_b,
+            requester,
+            status: ConnectionStatus::Pending,
+            bump_seed,
+            created_at_slot,
+            updated_at_slot: created_at_slot,
+        }
+    }
+
+    pub fn is_member(&self, user: &Pubkey) -> bool {
+        self.user_a == *user || self.user_b == *user
+    }
+
+    /// The user who received the connection request.
+    pub fn recipient(&self) -> Pubkey {
+        if self.requester == self.user_a {
+            self.user_b
+        } else {
+            self.user_a
+        }
+    }
+
+    /// Unpacks the account data into a [Connection].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &CONNECTION_DISCRIMINATOR, CONNECTION_VERSION)
+    }
+
+    /// Packs the [Connection] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the singleton config PDA of the program.
+/// The config PDA also collects the registration fees and the slashed deposits,
+/// the admin moves them out with Withdraw.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ProgramConfig {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// Authority allowed to update the config.
+    pub admin: Pubkey,
+    /// No user instruction is processed while the program is paused.
+    pub paused: bool,
+    /// The user PDA can't be resized above this size.
+    pub max_user_pda_size: u64,
+    /// Lamports paid by every new user on top of the rent.
+    pub registration_fee: u64,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    /// Refundable lamports every new user locks in the user PDA on top of the rent.
+    pub deposit: u64,
+    /// Backend signer allowed to attest the verifications of the users.
+    pub attestation_authority: Pubkey,
+    /// Lamports paid from the config PDA to the referrer of every new user.
+    /// Can't exceed the registration fee, so that referring one's own wallets doesn't pay.
+    pub referral_reward: u64,
+}
+
+impl ProgramConfig {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        admin: Pubkey,
+        bump_seed: u8,
+        paused: bool,
+        max_user_pda_size: u64,
+        registration_fee: u64,
+        deposit: u64,
+        attestation_authority: Pubkey,
+        referral_reward: u64,
+    ) -> Self {
+        ProgramConfig {
+            discriminator: PROGRAM_CONFIG_DISCRIMINATOR,
+            version: PROGRAM_CONFIG_VERSION,
+            admin,
+            paused,
+            max_user_pda_size,
+            registration_fee,
+            bump_seed,
+            deposit,
+            attestation_authority,
+            referral_reward,
+        }
+    }
+
+    /// Unpacks the account data into a [ProgramConfig].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &PROGRAM_CONFIG_DISCRIMINATOR, PROGRAM_CONFIG_VERSION)
+    }
+
+    /// Packs the [ProgramConfig] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Config in effect until the config PDA is initialized:
+/// the program runs without an admin, fees, rewards and an attestation authority.
+impl Default for ProgramConfig {
+    fn default() -> Self {
+        ProgramConfig::new(
+            Pubkey::default(),
+            0,
+            false,
+            crate::pda::MAX_USER_PDA_SIZE,
+            0,
+            0,
+            Pubkey::default(),
+            0,
+        )
+    }
+}
+
+/// Data stored in the username PDA, which reserves a username for one user.
+/// The PDA is derived from the normalized username, see [normalize_username].
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct UsernameClaim {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// Wallet of the user who claimed the username.
+    pub owner: Pubkey,
+    /// User PDA of the owner.
+    pub user_pda: Pubkey,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+}
+
+impl UsernameClaim {
+    pub fn new(owner: Pubkey, user_pda: Pubkey, bump_seed: u8) -> Self {
+        UsernameClaim {
+            discriminator: USERNAME_CLAIM_DISCRIMINATOR,
+            version: USERNAME_CLAIM_VERSION,
+            owner,
+            user_pda,
+            bump_seed,
+        }
+    }
+
+    /// Unpacks the account data into a [UsernameClaim].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &USERNAME_CLAIM_DISCRIMINATOR, USERNAME_CLAIM_VERSION)
+    }
+
+    /// Packs the [UsernameClaim] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the session PDA, which lets a delegate sign some instructions
+/// in place of the owner until the session expires.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Session {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// Wallet of the user who created the session.
+    pub owner: Pubkey,
+    /// Key allowed to sign in place of the owner.
+    pub delegate: Pubkey,
+    /// First slot in which the session is no longer valid.
+    pub expires_at_slot: u64,
+    /// Instructions the delegate can sign, see [crate::instruction::SESSION_ALL].
+    pub allowed_instructions: u64,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+}
+
+impl Session {
+    pub fn new(
+        owner: Pubkey,
+        delegate: Pubkey,
+        bump_seed: u8,
+        expires_at_slot: u64,
+        allowed_instructions: u64,
+    ) -> Self {
+        Session {
+            discriminator: SESSION_DISCRIMINATOR,
+            version: SESSION_VERSION,
+            owner,
+            delegate,
+            expires_at_slot,
+            allowed_instructions,
+            bump_seed,
+        }
+    }
+
+    pub fn is_expired(&self, slot: u64) -> bool {
+        slot >= self.expires_at_slot
+    }
+
+    /// Checks that all the instructions in `instructions` are allowed in the session.
+    pub fn allows(&self, instructions: u64) -> bool {
+        self.allowed_instructions & instructions == instructions
+    }
+
+    /// Unpacks the account data into a [Session].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &SESSION_DISCRIMINATOR, SESSION_VERSION)
+    }
+
+    /// Packs the [Session] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the endorsement PDA, one user vouching for another.
+/// The PDA exists as long as the endorsement holds, so a user can't endorse the same user twice.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Endorsement {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// User who vouches and paid for the PDA.
+    pub endorser: Pubkey,
+    /// User who is vouched for.
+    pub endorsed: Pubkey,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    pub created_at_slot: u64,
+}
+
+impl Endorsement {
+    pub fn new(endorser: Pubkey, endorsed: Pubkey, bump_seed: u8, created_at_slot: u64) -> Self {
+        Endorsement {
+            discriminator: ENDORSEMENT_DISCRIMINATOR,
+            version: ENDORSEMENT_VERSION,
+            endorser,
+            endorsed,
+            bump_seed,
+            created_at_slot,
+        }
+    }
+
+    /// Unpacks the account data into an [Endorsement].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &ENDORSEMENT_DISCRIMINATOR, ENDORSEMENT_VERSION)
+    }
+
+    /// Packs the [Endorsement] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the block list PDA, users its owner doesn't want to be contacted by.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct BlockList {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// Wallet of the user who blocks, paid for the PDA.
+    pub owner: Pubkey,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    /// Wallets of the blocked users, at most [MAX_BLOCKED_USERS].
+    pub blocked: Vec<Pubkey>,
+}
+
+impl BlockList {
+    pub fn new(owner: Pubkey, bump_seed: u8) -> Self {
+        BlockList {
+            discriminator: BLOCK_LIST_DISCRIMINATOR,
+            version: BLOCK_LIST_VERSION,
+            owner,
+            bump_seed,
+            blocked: Vec::new(),
+        }
+    }
+
+    pub fn is_blocked(&self, user: &Pubkey) -> bool {
+        self.blocked.contains(user)
+    }
+
+    /// Unpacks the account data into a [BlockList].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &BLOCK_LIST_DISCRIMINATOR, BLOCK_LIST_VERSION)
+    }
+
+    /// Packs the [BlockList] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the group PDA.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Group {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// User who created the group, the PDA is derived from it.
+    pub creator: Pubkey,
+    /// Id of the group among the groups of the creator.
+    pub group_id: u64,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    /// User managing the members, the creator at first.
+    pub admin: Pubkey,
+    pub name: String,
+    /// New members need to be let in by the admin.
+    pub invite_only: bool,
+    pub max_members: u32,
+    pub member_count: u32,
+    pub created_at_slot: u64,
+}
+
+impl Group {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        creator: Pubkey,
+        group_id: u64,
+        bump_seed: u8,
+        name: String,
+        invite_only: bool,
+        max_members: u32,
+        created_at_slot: u64,
+    ) -> Self {
+        Group {
+            discriminator: GROUP_DISCRIMINATOR,
+            version: GROUP_VERSION,
+            creator,
+            group_id,
+            bump_seed,
+            admin: creator,
+            name,
+            invite_only,
+            max_members,
+            member_count: 0,
+            created_at_slot,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.member_count >= self.max_members
+    }
+
+    /// Unpacks the account data into a [Group].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &GROUP_DISCRIMINATOR, GROUP_VERSION)
+    }
+
+    /// Packs the [Group] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the membership PDA, it exists as long as the user is a member of the group.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Membership {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// Group PDA
+    pub group: Pubkey,
+    /// Wallet of the member, paid for the PDA.
+    pub member: Pubkey,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    pub joined_at_slot: u64,
+}
+
+impl Membership {
+    pub fn new(group: Pubkey, member: Pubkey, bump_seed: u8, joined_at_slot: u64) -> Self {
+        Membership {
+            discriminator: MEMBERSHIP_DISCRIMINATOR,
+            version: MEMBERSHIP_VERSION,
+            group,
+            member,
+            bump_seed,
+            joined_at_slot,
+        }
+    }
+
+    /// Unpacks the account data into a [Membership].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &MEMBERSHIP_DISCRIMINATOR, MEMBERSHIP_VERSION)
+    }
+
+    /// Packs the [Membership] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the event PDA.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Event {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// User who organizes the event, the PDA is derived from it.
+    pub organizer: Pubkey,
+    /// Id of the event among the events of the organizer.
+    pub event_id: u64,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    pub title: String,
+    /// Unix timestamp of the start of the event.
+    pub starts_at: i64,
+    /// Where to meet, kept vague on purpose, e.g. a district or a park.
+    pub location_hint: String,
+    /// Maximum number of attendees, the organizer not included.
+    pub capacity: u32,
+    pub attendee_count: u32,
+    /// A closed event takes no more RSVPs.
+    pub closed: bool,
+    pub created_at_slot: u64,
+}
+
+impl Event {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        organizer: Pubkey,
+        event_id: u64,
+        bump_seed: u8,
+        title: String,
+        starts_at: i64,
+        location_hint: String,
+        capacity: u32,
+        created_at_slot: u64,
+    ) -> Self {
+        Event {
+            discriminator: EVENT_DISCRIMINATOR,
+            version: EVENT_VERSION,
+            organizer,
+            event_id,
+            bump_seed,
+            title,
+            starts_at,
+            location_hint,
+            capacity,
+            attendee_count: 0,
+            closed: false,
+            created_at_slot,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.attendee_count >= self.capacity
+    }
+
+    /// Unpacks the account data into an [Event].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &EVENT_DISCRIMINATOR, EVENT_VERSION)
+    }
+
+    /// Packs the [Event] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the RSVP PDA, it exists as long as the user attends the event.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Rsvp {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// Event PDA
+    pub event: Pubkey,
+    /// Wallet of the attendee, paid for the PDA.
+    pub attendee: Pubkey,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    pub created_at_slot: u64,
+}
+
+impl Rsvp {
+    pub fn new(event: Pubkey, attendee: Pubkey, bump_seed: u8, created_at_slot: u64) -> Self {
+        Rsvp {
+            discriminator: RSVP_DISCRIMINATOR,
+            version: RSVP_VERSION,
+            event,
+            attendee,
+            bump_seed,
+            created_at_slot,
+        }
+    }
+
+    /// Unpacks the account data into an [Rsvp].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &RSVP_DISCRIMINATOR, RSVP_VERSION)
+    }
+
+    /// Packs the [Rsvp] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the escrow PDA of a planned meeting of two users.
+/// Both participants lock the stake in the PDA and check in at the meeting,
+/// the stake of a participant who didn't check in before the deadline is forfeited.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Escrow {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// User who proposed the meeting, paid for the PDA.
+    pub proposer: Pubkey,
+    /// User invited to the meeting.
+    pub partner: Pubkey,
+    /// Id of the meeting among the meetings of the proposer and the partner.
+    pub meeting_id: u64,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    /// Lamports locked by each participant.
+    pub stake: u64,
+    /// Last slot to check in.
+    pub deadline_slot: u64,
+    /// Receives the forfeited stakes, the other participant if not set.
+    pub charity: Option<Pubkey>,
+    /// The proposer stakes when creating the escrow, the partner when joining it.
+    pub partner_staked: bool,
+    pub proposer_checked_in: bool,
+    pub partner_checked_in: bool,
+    pub created_at_slot: u64,
+}
+
+impl Escrow {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        proposer: Pubkey,
+        partner: Pubkey,
+        meeting_id: u64,
+        bump_seed: u8,
+        stake: u64,
+        deadline_slot: u64,
+        charity: Option<Pubkey>,
+        created_at_slot: u64,
+    ) -> Self {
+        Escrow {
+            discriminator: ESCROW_DISCRIMINATOR,
+            version: ESCROW_VERSION,
+            proposer,
+            partner,
+            meeting_id,
+            bump_seed,
+            stake,
+            deadline_slot,
+            charity,
+            partner_staked: false,
+            proposer_checked_in: false,
+            partner_checked_in: false,
+            created_at_slot,
+        }
+    }
+
+    pub fn is_participant(&self, user: &Pubkey) -> bool {
+        self.proposer == *user || self.partner == *user
+    }
+
+    /// Both participants checked in, the stakes can be refunded before the deadline.
+    pub fn is_met(&self) -> bool {
+        self.proposer_checked_in && self.partner_checked_in
+    }
+
+    /// Unpacks the account data into an [Escrow].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &ESCROW_DISCRIMINATOR, ESCROW_VERSION)
+    }
+
+    /// Packs the [Escrow] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the recovery PDA of a user PDA.
+/// A guardian proposes to move the profile to a new wallet, the other guardians approve it.
+/// The recovery can be executed once enough guardians approved it and the timelock passed.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Recovery {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// User PDA holding the profile to recover.
+    pub user_pda: Pubkey,
+    /// Wallet the profile moves to.
+    pub new_owner: Pubkey,
+    /// Guardian who proposed the recovery, paid for the PDA.
+    pub proposer: Pubkey,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    /// Guardians who approved the recovery, the proposer included.
+    pub approvals: Vec<Pubkey>,
+    pub proposed_at_slot: u64,
+}
+
+impl Recovery {
+    pub fn new(
+        user_pda: Pubkey,
+        new_owner: Pubkey,
+        proposer: Pubkey,
+        bump_seed: u8,
+        proposed_at_slot: u64,
+    ) -> Self {
+        Recovery {
+            discriminator: RECOVERY_DISCRIMINATOR,
+            version: RECOVERY_VERSION,
+            user_pda,
+            new_owner,
+            proposer,
+            bump_seed,
+            approvals: vec![proposer],
+            proposed_at_slot,
+        }
+    }
+
+    /// Number of approvals given by users who are still among the `guardians`.
+    pub fn approval_count(&self, guardians: &[Pubkey]) -> usize {
+        self.approvals
+            .iter()
+            .filter(|approval| guardians.contains(approval))
+            .count()
+    }
+
+    /// First slot the recovery can be executed at.
+    pub fn executable_at_slot(&self) -> u64 {
+        self.proposed_at_slot
+            .saturating_add(RECOVERY_TIMELOCK_SLOTS)
+    }
+
+    /// Unpacks the account data into a [Recovery].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &RECOVERY_DISCRIMINATOR, RECOVERY_VERSION)
+    }
+
+    /// Packs the [Recovery] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the referral PDA of a user who registered with a referrer.
+/// The PDA is derived from the wallet of the new user, who can only be referred once.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Referral {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// Wallet of the user who invited the new user.
+    pub referrer: Pubkey,
+    /// Wallet of the new user.
+    pub user: Pubkey,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    pub created_at_slot: u64,
+    /// Lamports paid to the referrer, 0 if the config PDA couldn't afford the reward.
+    pub reward: u64,
+}
+
+impl Referral {
+    pub fn new(
+        referrer: Pubkey,
+        user: Pubkey,
+        bump_seed: u8,
+        created_at_slot: u64,
+        reward: u64,
+    ) -> Self {
+        Referral {
+            discriminator: REFERRAL_DISCRIMINATOR,
+            version: REFERRAL_VERSION,
+            referrer,
+            user,
+            bump_seed,
+            created_at_slot,
+            reward,
+        }
+    }
+
+    /// Unpacks the account data into a [Referral].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &REFERRAL_DISCRIMINATOR, REFERRAL_VERSION)
+    }
+
+    /// Packs the [Referral] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the poll PDA, the votes are tallied in it.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Poll {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// User who created the poll, the PDA is derived from it.
+    pub creator: Pubkey,
+    /// Id of the poll among the polls of the creator.
+    pub poll_id: u64,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    /// Group PDA, only its members can vote. Any registered user can vote if not set.
+    pub group: Option<Pubkey>,
+    pub question: String,
+    pub options: Vec<String>,
+    /// Votes for each of the options, in the same order.
+    pub tallies: Vec<u32>,
+    /// A closed poll takes no more votes.
+    pub closed: bool,
+    pub created_at_slot: u64,
+}
+
+impl Poll {
+    pub fn new(
+        creator: Pubkey,
+        poll_id: u64,
+        bump_seed: u8,
+        group: Option<Pubkey>,
+        question: String,
+        options: Vec<String>,
+        created_at_slot: u64,
+    ) -> Self {
+        Poll {
+            discriminator: POLL_DISCRIMINATOR,
+            version: POLL_VERSION,
+            creator,
+            poll_id,
+            bump_seed,
+            group,
+            question,
+            tallies: vec![0; options.len()],
+            options,
+            closed: false,
+            created_at_slot,
+        }
+    }
+
+    pub fn vote_count(&self) -> u64 {
+        self.tallies.iter().map(|&votes| votes as u64).sum()
+    }
+
+    /// Unpacks the account data into a [Poll].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &POLL_DISCRIMINATOR, POLL_VERSION)
+    }
+
+    /// Packs the [Poll] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+/// Data stored in the vote PDA, its existence keeps the voter from voting twice.
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct Vote {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    /// Poll PDA
+    pub poll: Pubkey,
+    /// Wallet of the voter, paid for the PDA.
+    pub voter: Pubkey,
+    /// Bump seed used to derive the PDA.
+    pub bump_seed: u8,
+    /// Index of the chosen option.
+    pub option: u8,
+    pub created_at_slot: u64,
+}
+
+impl Vote {
+    pub fn new(
+        poll: Pubkey,
+        voter: Pubkey,
+        bump_seed: u8,
+        option: u8,
+        created_at_slot: u64,
+    ) -> Self {
+        Vote {
+            discriminator: VOTE_DISCRIMINATOR,
+            version: VOTE_VERSION,
+            poll,
+            voter,
+            bump_seed,
+            option,
+            created_at_slot,
+        }
+    }
+
+    /// Unpacks the account data into a [Vote].
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        unpack_account(data, &VOTE_DISCRIMINATOR, VOTE_VERSION)
+    }
+
+    /// Packs the [Vote] into the beginning of the account data.
+    pub fn pack(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        pack_account(self, dst)
+    }
+}
+
+// Every account starts with a discriminator and a version,
+// check them before deserializing the rest.
+fn unpack_account<T: BorshDeserialize>(
+    data: &[u8],
+    discriminator: &[u8; 8],
+    current_version: u8,
+) -> Result<T, ProgramError> {
+    if data.len() <= discriminator.len() || data[..discriminator.len()] != discriminator[..] {
+        msg!(
+            "Account data is not a {}",
+            String::from_utf8_lossy(discriminator)
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let version = data[discriminator.len()];
+    if version == 0 || version > current_version {
+        msg!("Unsupported account version: {}", version);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    T::deserialize(&mut &data[..]).map_err(|e| {
+        msg!("Failed to deserialize account data: {}", e);
+        ProgramError::InvalidAccountData
+    })
+}
+
+fn pack_account<T: BorshSerialize>(account: &T, dst: &mut [u8]) -> Result<(), ProgramError> {
+    let buf = borsh::to_vec(account).map_err(|_| ProgramError::InvalidAccountData)?;
+    if buf.len() > dst.len() {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    dst[..buf.len()].copy_from_slice(&buf);
+    // Fields appended by later versions decode from the tail, so it must not keep stale bytes.
+    dst[buf.len()..].fill(0);
+    Ok(())
+}
+
+/// Checks that the username can be stored in a [UserProfile].
+pub fn validate_username(username: &str) -> Result<(), ProgramError> {
+    if username.trim().is_empty() || username.len() > MAX_USERNAME_LEN {
+        msg!(
+            "Username must be between 1 and {} bytes long",
+            MAX_USERNAME_LEN
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Normalizes the username before deriving its PDA,
+/// so that usernames differing only in case or surrounding spaces can't coexist.
+pub fn normalize_username(username: &str) -> String {
+    username.trim().to_ascii_lowercase()
+}
+
+/// Checks that the bio can be stored in a [UserProfile].
+pub fn validate_bio(bio: &str) -> Result<(), ProgramError> {
+    if bio.len() > MAX_BIO_LEN {
+        msg!("Bio must be at most {} bytes long", MAX_BIO_LEN);
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Checks that the interests can be stored in a [UserProfile].
+pub fn validate_interests(interests: &[String]) -> Result<(), ProgramError> {
+    if interests.len() > MAX_INTERESTS {
+        msg!("At most {} interests are allowed", MAX_INTERESTS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if interests
+        .iter()
+        .any(|i| i.is_empty() || i.len() > MAX_INTEREST_LEN)
+    {
+        msg!(
+            "Each interest must be between 1 and {} bytes long",
+            MAX_INTEREST_LEN
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Checks that the name can be stored in a [Group].
+pub fn validate_group_name(name: &str) -> Result<(), ProgramError> {
+    if name.trim().is_empty() || name.len() > MAX_GROUP_NAME_LEN {
+        msg!(
+            "Group name must be between 1 and {} bytes long",
+            MAX_GROUP_NAME_LEN
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Checks that the title and the location hint can be stored in an [Event].
+pub fn validate_event(title: &str, location_hint: &str) -> Result<(), ProgramError> {
+    if title.trim().is_empty() || title.len() > MAX_EVENT_TITLE_LEN {
+        msg!(
+            "Event title must be between 1 and {} bytes long",
+            MAX_EVENT_TITLE_LEN
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    if location_hint.len() > MAX_LOCATION_HINT_LEN {
+        msg!(
+            "Location hint can't be longer than {} bytes",
+            MAX_LOCATION_HINT_LEN
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Checks that the question and the options can be stored in a [Poll].
+/// A poll offers between 2 and [MAX_POLL_OPTIONS] distinct options.
+pub fn validate_poll(question: &str, options: &[String]) -> Result<(), ProgramError> {
+    if question.trim().is_empty() || question.len() > MAX_POLL_QUESTION_LEN {
+        msg!(
+            "Poll question must be between 1 and {} bytes long",
+            MAX_POLL_QUESTION_LEN
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    if options.len() < 2 || options.len() > MAX_POLL_OPTIONS {
+        msg!("A poll needs between 2 and {} options", MAX_POLL_OPTIONS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if options.iter().enumerate().any(|(i, option)| {
+        option.trim().is_empty()
+            || option.len() > MAX_POLL_OPTION_LEN
+            || options[..i].contains(option)
+    }) {
+        msg!(
+            "Poll options must be distinct and between 1 and {} bytes long",
+            MAX_POLL_OPTION_LEN
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// Checks that the guardians of `owner` can be stored in a [UserProfile]
+/// and that `threshold` of them can approve a recovery.
+/// No guardians with a zero threshold turn the recovery off.
+pub fn validate_guardians(
+    owner: &Pubkey,
+    guardians: &[Pubkey],
+    threshold: u8,
+) -> Result<(), ProgramError> {
+    if guardians.len() > MAX_GUARDIANS {
+        msg!("At most {} guardians are allowed", MAX_GUARDIANS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if guardians.contains(owner)
+        || guardians
+            .iter()
+            .enumerate()
+            .any(|(i, guardian)| guardians[..i].contains(guardian))
+    {
+        msg!("Guardians must be distinct users other than the owner");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if (threshold == 0) != guardians.is_empty() || threshold as usize > guardians.len() {
+        msg!(
+            "Threshold must be between 1 and the number of guardians, {}",
+            guardians.len()
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_unpack_user_profile() {
+        let mut profile = UserProfile::new(Pubkey::new_unique(), 254, "paulinka".to_string(), 42);
+        profile.bio = "Looking for hiking buddies".to_string();
+        profile.interests = vec!["hiking".to_string(), "chess".to_string()];
+        profile.guardians = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        profile.guardian_threshold = 2;
+        profile.verifications = VERIFIED_ONBOARDING | VERIFIED_IN_PERSON;
+        assert!(profile.is_verified(VERIFIED_ONBOARDING));
+        assert!(!profile.is_verified(VERIFIED_ONBOARDING | VERIFIED_MODERATOR));
+
+        let mut data = vec![0u8; 1024];
+        profile.pack(&mut data).unwrap();
+        assert_eq!(&USER_PROFILE_DISCRIMINATOR, &data[..8]);
+        assert_eq!(borsh::to_vec(&profile).unwrap().len(), profile.packed_len());
+        let unpacked = UserProfile::unpack(&data).unwrap();
+        assert_eq!(profile, unpacked);
+    }
+
+    #[test]
+    fn unpack_previous_version() {
+        #[derive(BorshSerialize)]
+        struct UserProfileV1 {
+            discriminator: [u8; 8],
+            version: u8,
+            owner: Pubkey,
+            bump_seed: u8,
+            username: String,
+            created_at_slot: u64,
+        }
+        let v1 = UserProfileV1 {
+            discriminator: USER_PROFILE_DISCRIMINATOR,
+            version: 1,
+            owner: Pubkey::new_unique(),
+            bump_seed: 254,
+            username: "paulinka".to_string(),
+            created_at_slot: 42,
+        };
+
+        let mut data = vec![0u8; 1024];
+        let buf = borsh::to_vec(&v1).unwrap();
+        data[..buf.len()].copy_from_slice(&buf);
+
+        let unpacked = UserProfile::unpack(&data).unwrap();
+        assert_eq!(1, unpacked.version);
+        assert_eq!(v1.owner, unpacked.owner);
+        assert_eq!(v1.username, unpacked.username);
+        assert!(unpacked.bio.is_empty());
+        assert!(unpacked.interests.is_empty());
+        assert_eq!(0, unpacked.endorsement_count);
+        assert!(unpacked.guardians.is_empty());
+        assert_eq!(0, unpacked.guardian_threshold);
+        assert_eq!(0, unpacked.verifications);
+        assert_eq!(0, unpacked.invite_count);
+        assert!(!unpacked.flagged);
+    }
+
+    #[test]
+    fn pack_zeroes_tail() {
+        let mut profile = UserProfile::new(Pubkey::new_unique(), 254, "paulinka".to_string(), 42);
+        profile.bio = "Looking for hiking buddies".to_string();
+        profile.interests = vec!["hiking".to_string(), "chess".to_string()];
+        let mut data = vec![0u8; 1024];
+        profile.pack(&mut data).unwrap();
+
+        profile.bio.clear();
+        profile.interests.clear();
+        profile.pack(&mut data).unwrap();
+        assert!(data[profile.packed_len()..].iter().all(|byte| *byte == 0));
+        assert_eq!(profile, UserProfile::unpack(&data).unwrap());
+    }
+
+    #[test]
+    fn validate_profile_fields() {
+        assert!(validate_username("paulinka").is_ok());
+        assert!(validate_username("").is_err());
+        assert!(validate_username("  ").is_err());
+        assert!(validate_username(&"a".repeat(MAX_USERNAME_LEN + 1)).is_err());
+        assert!(validate_bio(&"a".repeat(MAX_BIO_LEN)).is_ok());
+        assert!(validate_bio(&"a".repeat(MAX_BIO_LEN + 1)).is_err());
+        assert!(validate_interests(&vec!["chess".to_string(); MAX_INTERESTS]).is_ok());
+        assert!(validate_interests(&vec!["chess".to_string(); MAX_INTERESTS + 1]).is_err());
+        assert!(validate_interests(&["".to_string()]).is_err());
+    }
+
+    #[test]
+    fn unpack_zeroed_account() {
+        let data = vec![0u8; 1024];
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            UserProfile::unpack(&data)
+        );
+    }
+
+    #[test]
+    fn pack_unpack_connection() {
+        let requester = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let connection = Connection::new(requester, recipient, 253, 42);
+        assert!(connection.user_a < connection.user_b);
+        assert!(connection.is_member(&requester));
+        assert!(connection.is_member(&recipient));
+        assert!(!connection.is_member(&Pubkey::new_unique()));
+        assert_eq!(recipient, connection.recipient());
+
+        let mut data = vec![0u8; 128];
+        connection.pack(&mut data).unwrap();
+        assert_eq!(connection, Connection::unpack(&data).unwrap());
+        // A connection is not a user profile.
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            UserProfile::unpack(&data)
+        );
+    }
+
+    #[test]
+    fn pack_unpack_username_claim() {
+        let claim = UsernameClaim::new(Pubkey::new_unique(), Pubkey::new_unique(), 252);
+        let mut data = vec![0u8; 96];
+        claim.pack(&mut data).unwrap();
+        assert_eq!(claim, UsernameClaim::unpack(&data).unwrap());
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            Connection::unpack(&data)
+        );
+    }
+
+    #[test]
+    fn pack_unpack_program_config() {
+        let config = ProgramConfig::new(
+            Pubkey::new_unique(),
+            251,
+            true,
+            4096,
+            1000,
+            2000,
+            Pubkey::new_unique(),
+            500,
+        );
+        let mut data = vec![0u8; 128];
+        config.pack(&mut data).unwrap();
+        assert_eq!(config, ProgramConfig::unpack(&data).unwrap());
+    }
+
+    #[test]
+    fn unpack_previous_config_version() {
+        #[derive(BorshSerialize)]
+        struct ProgramConfigV1 {
+            discriminator: [u8; 8],
+            version: u8,
+            admin: Pubkey,
+            paused: bool,
+            max_user_pda_size: u64,
+            registration_fee: u64,
+            bump_seed: u8,
+        }
+        let v1 = ProgramConfigV1 {
+            discriminator: PROGRAM_CONFIG_DISCRIMINATOR,
+            version: 1,
+            admin: Pubkey::new_unique(),
+            paused: true,
+            max_user_pda_size: 4096,
+            registration_fee: 1000,
+            bump_seed: 251,
+        };
+
+        let mut data = vec![0u8; 128];
+        let buf = borsh::to_vec(&v1).unwrap();
+        data[..buf.len()].copy_from_slice(&buf);
+
+        let unpacked = ProgramConfig::unpack(&data).unwrap();
+        assert_eq!(1, unpacked.version);
+        assert_eq!(v1.admin, unpacked.admin);
+        assert_eq!(v1.registration_fee, unpacked.registration_fee);
+        assert_eq!(v1.bump_seed, unpacked.bump_seed);
+        assert_eq!(0, unpacked.deposit);
+    }
+
+    #[test]
+    fn pack_unpack_session() {
+        let session = Session::new(Pubkey::new_unique(), Pubkey::new_unique(), 250, 100, 0b1010);
+        let mut data = vec![0u8; 128];
+        session.pack(&mut data).unwrap();
+        assert_eq!(session, Session::unpack(&data).unwrap());
+
+        assert!(!session.is_expired(99));
+        assert!(session.is_expired(100));
+        assert!(session.allows(0b1000));
+        assert!(session.allows(0b1010));
+        assert!(!session.allows(0b0100));
+        assert!(!session.allows(0b1100));
+    }
+
+    #[test]
+    fn pack_unpack_endorsement() {
+        let endorsement = Endorsement::new(Pubkey::new_unique(), Pubkey::new_unique(), 249, 42);
+        let mut data = vec![0u8; 96];
+        endorsement.pack(&mut data).unwrap();
+        assert_eq!(endorsement, Endorsement::unpack(&data).unwrap());
+    }
+
+    #[test]
+    fn pack_unpack_block_list() {
+        let mut block_list = BlockList::new(Pubkey::new_unique(), 248);
+        let blocked = Pubkey::new_unique();
+        block_list.blocked.push(blocked);
+        let mut data = vec![0u8; 128];
+        block_list.pack(&mut data).unwrap();
+        assert_eq!(block_list, BlockList::unpack(&data).unwrap());
+        assert!(block_list.is_blocked(&blocked));
+        assert!(!block_list.is_blocked(&block_list.owner));
+
+        // A full block list fits into the PDA.
+        block_list.blocked = (0..MAX_BLOCKED_USERS)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        let mut data = vec![0u8; crate::pda::BLOCK_LIST_PDA_SIZE as usize];
+        block_list.pack(&mut data).unwrap();
+    }
+
+    #[test]
+    fn pack_unpack_group() {
+        let creator = Pubkey::new_unique();
+        let mut group = Group::new(creator, 7, 247, "x".repeat(MAX_GROUP_NAME_LEN), true, 2, 42);
+        assert_eq!(creator, group.admin);
+        assert!(!group.is_full());
+        group.member_count = 2;
+        assert!(group.is_full());
+
+        let mut data = vec![0u8; crate::pda::GROUP_PDA_SIZE as usize];
+        group.pack(&mut data).unwrap();
+        assert_eq!(group, Group::unpack(&data).unwrap());
+
+        let membership = Membership::new(Pubkey::new_unique(), creator, 246, 42);
+        let mut data = vec![0u8; crate::pda::MEMBERSHIP_PDA_SIZE as usize];
+        membership.pack(&mut data).unwrap();
+        assert_eq!(membership, Membership::unpack(&data).unwrap());
+    }
+
+    #[test]
+    fn pack_unpack_event() {
+        let mut event = Event::new(
+            Pubkey::new_unique(),
+            u64::MAX,
+            247,
+            "x".repeat(MAX_EVENT_TITLE_LEN),
+            1_700_000_000,
+            "y".repeat(MAX_LOCATION_HINT_LEN),
+            1,
+            42,
+        );
+        assert!(!event.is_full());
+        event.attendee_count = 1;
+        assert!(event.is_full());
+
+        let mut data = vec![0u8; crate::pda::EVENT_PDA_SIZE as usize];
+        event.pack(&mut data).unwrap();
+        assert_eq!(event, Event::unpack(&data).unwrap());
+
+        let rsvp = Rsvp::new(Pubkey::new_unique(), Pubkey::new_unique(), 246, 42);
+        let mut data = vec![0u8; crate::pda::RSVP_PDA_SIZE as usize];
+        rsvp.pack(&mut data).unwrap();
+        assert_eq!(rsvp, Rsvp::unpack(&data).unwrap());
+    }
+
+    #[test]
+    fn pack_unpack_escrow() {
+        let mut escrow = Escrow::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            u64::MAX,
+            245,
+            1_000_000,
+            4200,
+            Some(Pubkey::new_unique()),
+            42,
+        );
+        assert!(escrow.is_participant(&escrow.partner));
+        assert!(!escrow.is_participant(&Pubkey::new_unique()));
+        escrow.partner_staked = true;
+        escrow.proposer_checked_in = true;
+        assert!(!escrow.is_met());
+        escrow.partner_checked_in = true;
+        assert!(escrow.is_met());
+
+        let mut data = vec![0u8; crate::pda::ESCROW_PDA_SIZE as usize];
+        escrow.pack(&mut data).unwrap();
+        assert_eq!(escrow, Escrow::unpack(&data).unwrap());
+    }
+
+    #[test]
+    fn pack_unpack_recovery() {
+        let guardians = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut recovery = Recovery::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            guardians[0],
+            253,
+            42,
+        );
+        recovery.approvals.push(Pubkey::new_unique());
+        assert_eq!(1, recovery.approval_count(&guardians));
+        recovery.approvals.push(guardians[1]);
+        assert_eq!(2, recovery.approval_count(&guardians));
+        assert_eq!(42 + RECOVERY_TIMELOCK_SLOTS, recovery.executable_at_slot());
+
+        recovery.approvals.truncate(1);
+        recovery
+            .approvals
+            .extend((1..MAX_GUARDIANS).map(|_| Pubkey::new_unique()));
+        let mut data = vec![0u8; crate::pda::RECOVERY_PDA_SIZE as usize];
+        recovery.pack(&mut data).unwrap();
+        assert_eq!(recovery, Recovery::unpack(&data).unwrap());
+    }
+
+    #[test]
+    fn pack_unpack_referral() {
+        let referral = Referral::new(Pubkey::new_unique(), Pubkey::new_unique(), 252, 42, 1000);
+        let mut data = vec![0u8; crate::pda::REFERRAL_PDA_SIZE as usize];
+        referral.pack(&mut data).unwrap();
+        assert_eq!(referral, Referral::unpack(&data).unwrap());
+    }
+
+    #[test]
+    fn pack_unpack_poll() {
+        let options: Vec<String> = (0..MAX_POLL_OPTIONS)
+            .map(|i| format!("{}", i).repeat(MAX_POLL_OPTION_LEN))
+            .collect();
+        let mut poll = Poll::new(
+            Pubkey::new_unique(),
+            u64::MAX,
+            247,
+            Some(Pubkey::new_unique()),
+            "x".repeat(MAX_POLL_QUESTION_LEN),
+            options,
+            42,
+        );
+        assert_eq!(vec![0; MAX_POLL_OPTIONS], poll.tallies);
+        poll.tallies[0] = u32::MAX;
+        poll.tallies[1] = 2;
+        assert_eq!(u32::MAX as u64 + 2, poll.vote_count());
+
+        let mut data = vec![0u8; crate::pda::POLL_PDA_SIZE as usize];
+        poll.pack(&mut data).unwrap();
+        assert_eq!(poll, Poll::unpack(&data).unwrap());
+
+        let vote = Vote::new(Pubkey::new_unique(), Pubkey::new_unique(), 246, 7, 42);
+        let mut data = vec![0u8; crate::pda::VOTE_PDA_SIZE as usize];
+        vote.pack(&mut data).unwrap();
+        assert_eq!(vote, Vote::unpack(&data).unwrap());
+    }
+
+    #[test]
+    fn validate_polls() {
+        let options = vec!["Park".to_string(), "Cafe".to_string()];
+        assert!(validate_poll("Where do we meet?", &options).is_ok());
+        assert!(validate_poll(" ", &options).is_err());
+        assert!(validate_poll("Where do we meet?", &options[..1]).is_err());
+        assert!(validate_poll("Where?", &["Park".to_string(), "Park".to_string()]).is_err());
+        assert!(validate_poll("Where?", &["Park".to_string(), "".to_string()]).is_err());
+        let too_many: Vec<String> = (0..=MAX_POLL_OPTIONS).map(|i| i.to_string()).collect();
+        assert!(validate_poll("Where?", &too_many).is_err());
+    }
+
+    #[test]
+    fn validate_guardian_sets() {
+        let owner = Pubkey::new_unique();
+        let guardians: Vec<Pubkey> = (0..MAX_GUARDIANS).map(|_| Pubkey::new_unique()).collect();
+        assert!(validate_guardians(&owner, &[], 0).is_ok());
+        assert!(validate_guardians(&owner, &guardians, 1).is_ok());
+        assert!(validate_guardians(&owner, &guardians, MAX_GUARDIANS as u8).is_ok());
+        assert!(validate_guardians(&owner, &guardians, 0).is_err());
+        assert!(validate_guardians(&owner, &guardians, MAX_GUARDIANS as u8 + 1).is_err());
+        assert!(validate_guardians(&owner, &[], 1).is_err());
+        assert!(validate_guardians(&owner, &[owner], 1).is_err());
+        assert!(validate_guardians(&owner, &[guardians[0], guardians[0]], 1).is_err());
+        let mut too_many = guardians.clone();
+        too_many.push(Pubkey::new_unique());
+        assert!(validate_guardians(&owner, &too_many, 1).is_err());
+    }
+
+    #[test]
+    fn validate_events() {
+        assert!(validate_event("Picnic", "").is_ok());
+        assert!(validate_event(" ", "Tempelhofer Feld").is_err());
+        assert!(validate_event("Picnic", &"y".repeat(MAX_LOCATION_HINT_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn normalize_usernames() {
+        assert_eq!("paulinka", normalize_username("Paulinka"));
+        assert_eq!("paulinka", normalize_username("  PAULINKA "));
+        assert_eq!("paulinka_2", normalize_username("paulinka_2"));
+    }
+
+    #[test]
+    fn pack_into_small_account() {
+        let profile = UserProfile::new(Pubkey::new_unique(), 254, "paulinka".to_string(), 42);
+        let mut data = vec![0u8; 16];
+        assert_eq!(
+            Err(ProgramError::AccountDataTooSmall),
+            profile.pack(&mut data)
+        );
+    }
+}
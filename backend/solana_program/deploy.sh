@@ -6,5 +6,5 @@ cargo build-sbf
 # This default keypair is generated during the first program compilation
 # in the same directory as the program's shared object (.so).
 # When using different program keys: https://docs.solanalabs.com/cli/examples/deploy-a-program
-solana program deploy ./target/deploy/solana_program.so
+solana program deploy ./target/deploy/anti_loneliness_solana_program.so
 solana program show --programs
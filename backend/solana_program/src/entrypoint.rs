@@ -1,7 +1,9 @@
 use {
     crate::error::Error,
     solana_program::{
-        account_info::AccountInfo, entrypoint::ProgramResult, program_error::PrintProgramError,
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        program_error::{PrintProgramError, ProgramError},
         pubkey::Pubkey,
     },
 };
@@ -15,7 +17,10 @@ fn process_instruction(
     if let Err(error) =
         crate::processor::process_instruction(program_id, accounts, instruction_data)
     {
-        error.print::<Error>();
+        // Custom errors are logged together with the account when they are created.
+        if !matches!(error, ProgramError::Custom(_)) {
+            error.print::<Error>();
+        }
         return Err(error);
     };
     Ok(())
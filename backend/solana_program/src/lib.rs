@@ -1,6 +1,7 @@
-pub mod error;
-pub mod instruction;
 pub mod processor;
+pub mod validation;
+
+pub use anti_loneliness_program_interface::{cpi, error, event, instruction, pda, state};
 
 #[cfg(not(feature = "no-entrypoint"))]
 mod entrypoint;
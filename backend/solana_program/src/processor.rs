@@ -1,14 +1,39 @@
-use crate::instruction::{InitializeInstructionData, ProgramInstruction};
+use crate::error::{Error, ErrorCause};
+use crate::event::{
+    AccountClosed, OwnershipTransferred, ProfileUpdated, ProgramEvent, UserInitialized,
+    UserPdaResized,
+};
+use crate::instruction::{
+    InitializeInstructionData, ProgramInstruction, ResizeInstructionData,
+    TransferOwnershipInstructionData, UpdateProfileInstructionData, VerifyMemberInstructionData,
+    SESSION_UPDATE_PROFILE,
+};
+use crate::pda::{MAX_USER_PDA_SIZE, USERNAME_PDA_SIZE, USER_PDA_SEED_PREFIX, USER_PDA_SIZE};
+use crate::state::{self, UserProfile};
+use crate::validation;
 use solana_program::account_info::next_account_info;
 use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
 use solana_program::program_error::ProgramError;
 use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
 use solana_program::system_instruction;
 use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
 
-pub const USER_PDA_SIZE: u64 = 1024;
-pub const USER_PDA_SEED_PREFIX: &[u8] = b"user";
+mod block;
+mod config;
+mod connection;
+mod endorsement;
+mod escrow;
+mod group;
+mod meetup;
+mod poll;
+mod recovery;
+mod referral;
+mod session;
+mod username;
 
 /// Instruction processor
 pub fn process_instruction(
@@ -16,10 +41,97 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     input: &[u8],
 ) -> ProgramResult {
-    let instr = crate::instruction::ProgramInstruction::unpack(input)?;
+    let instr = crate::instruction::ProgramInstruction::unpack(input)
+        .map_err(|_| Error::new(ErrorCause::InvalidInstructionData))?;
     match instr {
         ProgramInstruction::Initialize(data) => process_initialize(program_id, accounts, data),
         ProgramInstruction::CloseAccount => process_close_account(program_id, accounts),
+        ProgramInstruction::UpdateProfile(data) => {
+            process_update_profile(program_id, accounts, data)
+        }
+        ProgramInstruction::Resize(data) => process_resize(program_id, accounts, data),
+        ProgramInstruction::RequestConnection => {
+            connection::process_request_connection(program_id, accounts)
+        }
+        ProgramInstruction::AcceptConnection => {
+            connection::process_accept_connection(program_id, accounts)
+        }
+        ProgramInstruction::RejectConnection => {
+            connection::process_reject_connection(program_id, accounts)
+        }
+        ProgramInstruction::RemoveConnection => {
+            connection::process_remove_connection(program_id, accounts)
+        }
+        ProgramInstruction::ClaimUsername(data) => {
+            username::process_claim_username(program_id, accounts, data)
+        }
+        ProgramInstruction::InitializeConfig(data) => {
+            config::process_initialize_config(program_id, accounts, data)
+        }
+        ProgramInstruction::UpdateConfig(data) => {
+            config::process_update_config(program_id, accounts, data)
+        }
+        ProgramInstruction::TransferAuthority => {
+            config::process_transfer_authority(program_id, accounts)
+        }
+        ProgramInstruction::SlashDeposit => config::process_slash_deposit(program_id, accounts),
+        ProgramInstruction::Attest(data) => config::process_attest(program_id, accounts, data),
+        ProgramInstruction::CreateSession(data) => {
+            session::process_create_session(program_id, accounts, data)
+        }
+        ProgramInstruction::RevokeSession => session::process_revoke_session(program_id, accounts),
+        ProgramInstruction::Endorse => endorsement::process_endorse(program_id, accounts),
+        ProgramInstruction::RevokeEndorsement => {
+            endorsement::process_revoke_endorsement(program_id, accounts)
+        }
+        ProgramInstruction::Block => block::process_block(program_id, accounts),
+        ProgramInstruction::Unblock => block::process_unblock(program_id, accounts),
+        ProgramInstruction::CreateGroup(data) => {
+            group::process_create_group(program_id, accounts, data)
+        }
+        ProgramInstruction::JoinGroup => group::process_join_group(program_id, accounts),
+        ProgramInstruction::LeaveGroup => group::process_leave_group(program_id, accounts),
+        ProgramInstruction::KickMember => group::process_kick_member(program_id, accounts),
+        ProgramInstruction::CreateEvent(data) => {
+            meetup::process_create_event(program_id, accounts, data)
+        }
+        ProgramInstruction::Rsvp => meetup::process_rsvp(program_id, accounts),
+        ProgramInstruction::CancelRsvp => meetup::process_cancel_rsvp(program_id, accounts),
+        ProgramInstruction::CloseEvent => meetup::process_close_event(program_id, accounts),
+        ProgramInstruction::CreateEscrow(data) => {
+            escrow::process_create_escrow(program_id, accounts, data)
+        }
+        ProgramInstruction::JoinEscrow => escrow::process_join_escrow(program_id, accounts),
+        ProgramInstruction::CheckIn => escrow::process_check_in(program_id, accounts),
+        ProgramInstruction::SettleEscrow => escrow::process_settle_escrow(program_id, accounts),
+        ProgramInstruction::TransferOwnership(data) => {
+            process_transfer_ownership(program_id, accounts, data)
+        }
+        ProgramInstruction::SetGuardians(data) => {
+            recovery::process_set_guardians(program_id, accounts, data)
+        }
+        ProgramInstruction::ProposeRecovery(data) => {
+            recovery::process_propose_recovery(program_id, accounts, data)
+        }
+        ProgramInstruction::ApproveRecovery => {
+            recovery::process_approve_recovery(program_id, accounts)
+        }
+        ProgramInstruction::ExecuteRecovery(data) => {
+            recovery::process_execute_recovery(program_id, accounts, data)
+        }
+        ProgramInstruction::CancelRecovery => {
+            recovery::process_cancel_recovery(program_id, accounts)
+        }
+        ProgramInstruction::CreatePoll(data) => {
+            poll::process_create_poll(program_id, accounts, data)
+        }
+        ProgramInstruction::Vote(data) => poll::process_vote(program_id, accounts, data),
+        ProgramInstruction::ClosePoll => poll::process_close_poll(program_id, accounts),
+        ProgramInstruction::VerifyMember(data) => process_verify_member(program_id, accounts, data),
+        ProgramInstruction::Withdraw(data) => config::process_withdraw(program_id, accounts, data),
+        ProgramInstruction::Flag => config::process_flag(program_id, accounts),
+        ProgramInstruction::Unflag => config::process_unflag(program_id, accounts),
+        ProgramInstruction::CloseVote => poll::process_close_vote(program_id, accounts),
     }
 }
 
@@ -30,13 +142,54 @@ pub fn process_initialize(
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let payer = next_account_info(account_info_iter)?;
-    validate_payer_account(payer)?;
+    validation::check_signer(payer)?;
+    validation::check_writable(payer)?;
     let pda = next_account_info(account_info_iter)?;
-    if !pda.is_writable {
-        return Err(ProgramError::Immutable);
-    }
+    validation::check_writable(pda)?;
+    validation::check_new_user_pda(program_id, payer.key, pda, input.pda_bump_seed)?;
+    validation::check_uninitialized(pda)?;
+    // The username is a seed of the username PDA, validate it first.
+    state::validate_username(&input.username).map_err(|_| invalid_profile_data(pda.key))?;
+    let username_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(username_pda)?;
+    let username_bump_seed =
+        validation::check_username_pda(program_id, &input.username, username_pda)?;
     // System program needs to come from the outside
     let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    let config = validation::check_not_paused(program_id, config_pda)?;
+    if validation::check_username_claim(program_id, username_pda)?.is_some() {
+        return Err(username::username_taken(username_pda.key).into());
+    }
+    let referral = match input.referrer {
+        Some(referrer) => Some(referral::check_referral_accounts(
+            program_id,
+            payer.key,
+            &referrer,
+            account_info_iter,
+        )?),
+        None => None,
+    };
+
+    let rent = Rent::get()?;
+    let required_lamports = rent
+        .minimum_balance(USER_PDA_SIZE as usize)
+        .saturating_sub(pda.lamports())
+        + rent
+            .minimum_balance(USERNAME_PDA_SIZE as usize)
+            .saturating_sub(username_pda.lamports())
+        + config.registration_fee
+        + config.deposit
+        + referral
+            .as_ref()
+            .map_or(0, |referral| referral.required_lamports(&rent));
+    if required_lamports > input.lamports {
+        return Err(Error::new(ErrorCause::InsufficientRent)
+            .with_account(pda.key)
+            .with_message(format!("Required {} lamports", required_lamports))
+            .into());
+    }
 
     // Used to uniquely identify this PDA among others.
     let pda_seed = &[
@@ -46,27 +199,68 @@ pub fn process_initialize(
         &[input.pda_bump_seed],
     ];
 
-    // Invoke the system program to create an account while virtually
-    // signing with the vault PDA, which is owned by this caller program.
-    solana_program::program::invoke_signed(
-        &system_instruction::create_account(
-            payer.key,
-            pda.key,
-            input.lamports,
-            USER_PDA_SIZE,
-            program_id,
-        ),
-        &[payer.clone(), pda.clone(), system_program.clone()],
-        &[pda_seed],
-    )
-}
+    create_pda_account(
+        program_id,
+        payer,
+        pda,
+        system_program,
+        USER_PDA_SIZE,
+        pda_seed,
+    )?;
 
-fn validate_payer_account(payer: &AccountInfo) -> Result<(), ProgramError> {
-    if !payer.is_signer || payer.signer_key().is_none() {
-        return Err(ProgramError::MissingRequiredSignature);
+    // The account is created, now store the user profile in it.
+    let mut profile = UserProfile::new(
+        *payer.key,
+        input.pda_bump_seed,
+        input.username,
+        Clock::get()?.slot,
+    );
+    // The deposit stays in the user PDA until the account is closed or the deposit slashed.
+    if config.deposit > 0 {
+        solana_program::program::invoke(
+            &system_instruction::transfer(payer.key, pda.key, config.deposit),
+            &[payer.clone(), pda.clone(), system_program.clone()],
+        )?;
+        profile.deposit = config.deposit;
     }
-    if !payer.is_writable {
-        return Err(ProgramError::Immutable);
+    pack_profile(&profile, pda)?;
+
+    username::create_username_claim(
+        program_id,
+        payer,
+        pda.key,
+        username_pda,
+        system_program,
+        &profile.username,
+        username_bump_seed,
+    )?;
+
+    // The fee is collected by the config PDA.
+    if config.registration_fee > 0 {
+        validation::check_writable(config_pda)?;
+        solana_program::program::invoke(
+            &system_instruction::transfer(payer.key, config_pda.key, config.registration_fee),
+            &[payer.clone(), config_pda.clone(), system_program.clone()],
+        )?;
+    }
+
+    ProgramEvent::UserInitialized(UserInitialized {
+        owner: *payer.key,
+        user_pda: *pda.key,
+        username: profile.username,
+    })
+    .emit();
+
+    // The fee is collected first, it can fund the reward.
+    if let Some(referral) = referral {
+        referral::create_referral(
+            program_id,
+            referral,
+            payer,
+            system_program,
+            config_pda,
+            &config,
+        )?;
     }
     Ok(())
 }
@@ -74,22 +268,344 @@ fn validate_payer_account(payer: &AccountInfo) -> Result<(), ProgramError> {
 fn process_close_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let payer = next_account_info(account_info_iter)?;
-    validate_payer_account(payer)?;
+    validation::check_signer(payer)?;
+    validation::check_writable(payer)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let profile = validation::check_user_pda(program_id, payer.key, pda)?;
+    validation::check_not_flagged(&profile, pda)?;
+    let username_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(username_pda)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    let recovery_pda = next_account_info(account_info_iter)?;
+    validation::check_no_recovery(program_id, pda.key, recovery_pda)?;
+
+    username::release_username(program_id, payer, &profile.username, username_pda)?;
+    close_pda_account(pda, payer)?;
+
+    ProgramEvent::AccountClosed(AccountClosed {
+        owner: *payer.key,
+        user_pda: *pda.key,
+    })
+    .emit();
+    Ok(())
+}
+
+fn process_transfer_ownership(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: TransferOwnershipInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    validation::check_signer(owner)?;
+    validation::check_writable(owner)?;
+    let new_owner = next_account_info(account_info_iter)?;
+    if new_owner.key == owner.key {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(new_owner.key)
+            .with_message("The new owner can't be the current one".to_string())
+            .into());
+    }
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let profile = validation::check_user_pda(program_id, owner.key, pda)?;
+    let new_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(new_pda)?;
+    validation::check_new_user_pda(program_id, new_owner.key, new_pda, input.pda_bump_seed)?;
+    // A wallet has one profile, the new owner can't be registered yet.
+    validation::check_uninitialized(new_pda)?;
+    let username_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(username_pda)?;
+    let block_list_pda = next_account_info(account_info_iter)?;
+    let new_block_list_pda = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    let recovery_pda = next_account_info(account_info_iter)?;
+    validation::check_no_recovery(program_id, pda.key, recovery_pda)?;
+
+    // The new owner pays for its PDA if it signs, the current owner otherwise.
+    let payer = if new_owner.is_signer {
+        validation::check_writable(new_owner)?;
+        new_owner
+    } else {
+        owner
+    };
+    move_profile(
+        program_id,
+        profile,
+        pda,
+        new_owner.key,
+        new_pda,
+        input.pda_bump_seed,
+        username_pda,
+        block_list_pda,
+        new_block_list_pda,
+        payer,
+        owner,
+        system_program,
+    )
+}
+
+/// Moves the profile stored in `pda` to the new user PDA of `new_owner` and closes `pda`.
+/// The deposit moves with the profile, the rest of the lamports go to `recipient`.
+/// The username claim of the profile points to the new PDA afterwards.
+/// The endorsement PDAs stay keyed by the current wallet, so the endorsement count starts over.
+/// The blocked users move to the block list PDA of `new_owner`.
+#[allow(clippy::too_many_arguments)]
+fn move_profile<'a>(
+    program_id: &Pubkey,
+    mut profile: UserProfile,
+    pda: &AccountInfo<'a>,
+    new_owner: &Pubkey,
+    new_pda: &AccountInfo<'a>,
+    pda_bump_seed: u8,
+    username_pda: &AccountInfo<'a>,
+    block_list_pda: &AccountInfo<'a>,
+    new_block_list_pda: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    recipient: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    // The flag stays with the wallet the admin flagged.
+    validation::check_not_flagged(&profile, pda)?;
+    let pda_seed = &[USER_PDA_SEED_PREFIX, new_owner.as_ref(), &[pda_bump_seed]];
+    // A resized PDA keeps its size.
+    create_pda_account(
+        program_id,
+        payer,
+        new_pda,
+        system_program,
+        pda.data_len() as u64,
+        pda_seed,
+    )?;
+    let owner = profile.owner;
+    profile.version = state::USER_PROFILE_VERSION;
+    profile.owner = *new_owner;
+    profile.bump_seed = pda_bump_seed;
+    profile.endorsement_count = 0;
+    pack_profile(&profile, new_pda)?;
+    username::transfer_username_claim(
+        program_id,
+        &owner,
+        new_owner,
+        new_pda.key,
+        &profile.username,
+        username_pda,
+    )?;
+    block::transfer_block_list(
+        program_id,
+        &owner,
+        new_owner,
+        block_list_pda,
+        new_block_list_pda,
+        payer,
+        recipient,
+        system_program,
+    )?;
+
+    // Both PDAs are owned by this program, the deposit can be moved directly.
+    **pda.lamports.borrow_mut() = pda
+        .lamports()
+        .checked_sub(profile.deposit)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **new_pda.lamports.borrow_mut() = new_pda
+        .lamports()
+        .checked_add(profile.deposit)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    close_pda_account(pda, recipient)?;
+
+    ProgramEvent::OwnershipTransferred(OwnershipTransferred {
+        owner,
+        new_owner: *new_owner,
+        user_pda: *new_pda.key,
+    })
+    .emit();
+    Ok(())
+}
+
+fn process_update_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: UpdateProfileInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer = next_account_info(account_info_iter)?;
     let pda = next_account_info(account_info_iter)?;
-    if !pda.is_writable {
-        return Err(ProgramError::Immutable);
+    validation::check_writable(pda)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    let owner = session::check_authority(
+        program_id,
+        signer,
+        account_info_iter.next(),
+        SESSION_UPDATE_PROFILE,
+    )?;
+    let mut profile = validation::check_user_pda(program_id, &owner, pda)?;
+
+    state::validate_username(&input.username)
+        .and_then(|_| state::validate_bio(&input.bio))
+        .and_then(|_| state::validate_interests(&input.interests))
+        .map_err(|_| invalid_profile_data(pda.key))?;
+    if state::normalize_username(&input.username) != state::normalize_username(&profile.username) {
+        return Err(invalid_profile_data(pda.key)
+            .with_message("Use ClaimUsername to change the username".to_string())
+            .into());
     }
 
-    let source_account_info = pda;
-    let dest_account_info = payer;
+    profile.version = state::USER_PROFILE_VERSION;
+    profile.username = input.username;
+    profile.bio = input.bio;
+    profile.interests = input.interests;
+    pack_profile(&profile, pda)?;
+
+    ProgramEvent::ProfileUpdated(ProfileUpdated { owner }).emit();
+    Ok(())
+}
+
+fn process_resize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: ResizeInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    validation::check_signer(owner)?;
+    validation::check_writable(owner)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let profile = validation::check_user_pda(program_id, owner.key, pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    let config = validation::check_not_paused(program_id, config_pda)?;
+
+    // The stored profile must still fit after shrinking, with room left
+    // for the fields appended by later versions.
+    let min_size = USER_PDA_SIZE.max(profile.packed_len() as u64);
+    let max_size = config.max_user_pda_size.min(MAX_USER_PDA_SIZE);
+    if input.new_size > max_size || input.new_size < min_size {
+        return Err(Error::new(ErrorCause::InvalidAccountSize)
+            .with_account(pda.key)
+            .with_message(format!(
+                "Size must be between {} and {}",
+                min_size, max_size
+            ))
+            .into());
+    }
+
+    // The deposit stays locked in the PDA whatever its size.
+    let required_lamports = Rent::get()?
+        .minimum_balance(input.new_size as usize)
+        .checked_add(profile.deposit)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let current_lamports = pda.lamports();
+    if required_lamports > current_lamports {
+        solana_program::program::invoke(
+            &system_instruction::transfer(owner.key, pda.key, required_lamports - current_lamports),
+            &[owner.clone(), pda.clone(), system_program.clone()],
+        )?;
+    } else if required_lamports < current_lamports {
+        // The PDA is owned by this program, the lamports can be moved directly.
+        **pda.lamports.borrow_mut() = required_lamports;
+        **owner.lamports.borrow_mut() = owner
+            .lamports()
+            .checked_add(current_lamports - required_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    // Bytes added after the profile must be zeroed to be decoded as defaults.
+    pda.realloc(input.new_size as usize, true)?;
+
+    ProgramEvent::UserPdaResized(UserPdaResized {
+        owner: *owner.key,
+        new_size: input.new_size,
+    })
+    .emit();
+    Ok(())
+}
+
+/// Creates a PDA account of `size` bytes owned by this program,
+/// `payer` pays for its rent.
+pub(crate) fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    size: u64,
+    pda_seed: &[&[u8]],
+) -> ProgramResult {
+    let rent = Rent::get()?.minimum_balance(size as usize);
 
-    let dest_starting_lamports = dest_account_info.lamports();
-    **dest_account_info.lamports.borrow_mut() = dest_starting_lamports
-        .checked_add(source_account_info.lamports())
+    if pda.lamports() == 0 {
+        // Invoke the system program to create an account while virtually
+        // signing with the vault PDA, which is owned by this caller program.
+        return solana_program::program::invoke_signed(
+            &system_instruction::create_account(payer.key, pda.key, rent, size, program_id),
+            &[payer.clone(), pda.clone(), system_program.clone()],
+            &[pda_seed],
+        );
+    }
+
+    // Anyone can transfer lamports to the PDA address before it is created,
+    // which makes create_account fail. Top it up to the rent instead
+    // and create the account step by step.
+    let required_lamports = rent.saturating_sub(pda.lamports());
+    if required_lamports > 0 {
+        solana_program::program::invoke(
+            &system_instruction::transfer(payer.key, pda.key, required_lamports),
+            &[payer.clone(), pda.clone(), system_program.clone()],
+        )?;
+    }
+    solana_program::program::invoke_signed(
+        &system_instruction::allocate(pda.key, size),
+        &[pda.clone(), system_program.clone()],
+        &[pda_seed],
+    )?;
+    solana_program::program::invoke_signed(
+        &system_instruction::assign(pda.key, program_id),
+        &[pda.clone(), system_program.clone()],
+        &[pda_seed],
+    )
+}
+
+/// Closes a PDA account owned by this program, draining its lamports to `recipient`.
+pub(crate) fn close_pda_account(pda: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    let dest_starting_lamports = recipient.lamports();
+    **recipient.lamports.borrow_mut() = dest_starting_lamports
+        .checked_add(pda.lamports())
         .or(Some(u64::MAX))
         .expect("u64::MAX should never overflow u64");
-    **source_account_info.lamports.borrow_mut() = 0;
+    **pda.lamports.borrow_mut() = 0;
+
+    pda.assign(&system_program::ID);
+    pda.realloc(0, false)
+}
+
+fn process_verify_member(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: VerifyMemberInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user = next_account_info(account_info_iter)?;
+    let pda = next_account_info(account_info_iter)?;
+    // Same check as the one partner programs run themselves.
+    crate::cpi::check_member(program_id, user.key, pda, input.required_verifications)?;
+    Ok(())
+}
+
+fn invalid_profile_data(pda: &Pubkey) -> Error {
+    Error::new(ErrorCause::InvalidProfileData).with_account(pda)
+}
 
-    source_account_info.assign(&system_program::ID);
-    source_account_info.realloc(0, false).map_err(Into::into)
+fn pack_profile(profile: &UserProfile, pda: &AccountInfo) -> ProgramResult {
+    profile.pack(&mut pda.try_borrow_mut_data()?).map_err(|_| {
+        Error::new(ErrorCause::AccountDataTooSmall)
+            .with_account(pda.key)
+            .into()
+    })
 }
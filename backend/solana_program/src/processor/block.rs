@@ -0,0 +1,200 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{BlockChanged, ProgramEvent};
+use crate::pda::{BLOCK_LIST_PDA_SEED_PREFIX, BLOCK_LIST_PDA_SIZE};
+use crate::state::{self, BlockList, MAX_BLOCKED_USERS};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+use super::{close_pda_account, create_pda_account};
+
+pub fn process_block(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    validation::check_signer(owner)?;
+    validation::check_writable(owner)?;
+    let owner_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, owner.key, owner_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let bump_seed = validation::check_new_block_list_pda(program_id, owner.key, pda)?;
+    // Any wallet can be blocked, registered or not.
+    let user = next_account_info(account_info_iter)?;
+    if user.key == owner.key {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(user.key)
+            .with_message("Can't block yourself".to_string())
+            .into());
+    }
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    // The block list PDA is created on the first block and kept from then on.
+    let mut block_list = if pda.owner == program_id {
+        validation::check_block_list_pda(program_id, owner.key, pda)?
+    } else {
+        validation::check_uninitialized(pda)?;
+        let pda_seed = &[BLOCK_LIST_PDA_SEED_PREFIX, owner.key.as_ref(), &[bump_seed]];
+        create_pda_account(
+            program_id,
+            owner,
+            pda,
+            system_program,
+            BLOCK_LIST_PDA_SIZE,
+            pda_seed,
+        )?;
+        BlockList::new(*owner.key, bump_seed)
+    };
+    if block_list.is_blocked(user.key) {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(user.key)
+            .with_message("Already blocked".to_string())
+            .into());
+    }
+    if block_list.blocked.len() >= MAX_BLOCKED_USERS {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message(format!("Can't block more than {} users", MAX_BLOCKED_USERS))
+            .into());
+    }
+
+    block_list.blocked.push(*user.key);
+    pack_block_list(&block_list, pda)?;
+
+    ProgramEvent::UserBlocked(BlockChanged {
+        owner: *owner.key,
+        user: *user.key,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_unblock(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    validation::check_signer(owner)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut block_list = validation::check_block_list_pda(program_id, owner.key, pda)?;
+    let user = next_account_info(account_info_iter)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    let Some(index) = block_list.blocked.iter().position(|b| b == user.key) else {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(user.key)
+            .with_message("Not blocked".to_string())
+            .into());
+    };
+    block_list.blocked.remove(index);
+    pack_block_list(&block_list, pda)?;
+
+    ProgramEvent::UserUnblocked(BlockChanged {
+        owner: *owner.key,
+        user: *user.key,
+    })
+    .emit();
+    Ok(())
+}
+
+/// Fails if `user` has blocked `other_user` or the other way round.
+/// The block list PDAs of both users are checked, a missing one blocks nobody.
+pub(crate) fn check_not_blocked(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_block_list: &AccountInfo,
+    other_user: &Pubkey,
+    other_user_block_list: &AccountInfo,
+) -> ProgramResult {
+    check_not_blocked_by(program_id, user, user_block_list, other_user)?;
+    check_not_blocked_by(program_id, other_user, other_user_block_list, user)
+}
+
+/// Moves the users blocked by `owner` to the block list PDA of `new_owner`,
+/// creating it if needed, and closes the block list PDA of `owner`.
+/// Nothing is moved if `owner` never blocked anyone.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn transfer_block_list<'a>(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    new_owner: &Pubkey,
+    pda: &AccountInfo<'a>,
+    new_pda: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    recipient: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    validation::check_new_block_list_pda(program_id, owner, pda)?;
+    let bump_seed = validation::check_new_block_list_pda(program_id, new_owner, new_pda)?;
+    if pda.owner != program_id {
+        return Ok(());
+    }
+    validation::check_writable(pda)?;
+    validation::check_writable(new_pda)?;
+    let block_list = validation::check_block_list_pda(program_id, owner, pda)?;
+
+    // The new wallet may have blocked users with a profile it closed since.
+    let mut new_block_list = if new_pda.owner == program_id {
+        validation::check_block_list_pda(program_id, new_owner, new_pda)?
+    } else {
+        validation::check_uninitialized(new_pda)?;
+        let pda_seed = &[BLOCK_LIST_PDA_SEED_PREFIX, new_owner.as_ref(), &[bump_seed]];
+        create_pda_account(
+            program_id,
+            payer,
+            new_pda,
+            system_program,
+            BLOCK_LIST_PDA_SIZE,
+            pda_seed,
+        )?;
+        BlockList::new(*new_owner, bump_seed)
+    };
+    for user in block_list.blocked {
+        if user != *new_owner && !new_block_list.is_blocked(&user) {
+            new_block_list.blocked.push(user);
+        }
+    }
+    if new_block_list.blocked.len() > MAX_BLOCKED_USERS {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(new_pda.key)
+            .with_message(format!("Can't block more than {} users", MAX_BLOCKED_USERS))
+            .into());
+    }
+    new_block_list.version = state::BLOCK_LIST_VERSION;
+    pack_block_list(&new_block_list, new_pda)?;
+    close_pda_account(pda, recipient)
+}
+
+fn check_not_blocked_by(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    pda: &AccountInfo,
+    user: &Pubkey,
+) -> ProgramResult {
+    validation::check_new_block_list_pda(program_id, owner, pda)?;
+    if pda.owner != program_id {
+        return Ok(());
+    }
+    let block_list = validation::check_block_list_pda(program_id, owner, pda)?;
+    if block_list.is_blocked(user) {
+        return Err(Error::new(ErrorCause::Blocked)
+            .with_account(pda.key)
+            .with_message(format!("{} has blocked {}", owner, user))
+            .into());
+    }
+    Ok(())
+}
+
+fn pack_block_list(block_list: &BlockList, pda: &AccountInfo) -> ProgramResult {
+    block_list
+        .pack(&mut pda.try_borrow_mut_data()?)
+        .map_err(|_| {
+            Error::new(ErrorCause::AccountDataTooSmall)
+                .with_account(pda.key)
+                .into()
+        })
+}
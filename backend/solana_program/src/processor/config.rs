@@ -0,0 +1,318 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{Attested, ConfigUpdated, DepositSlashed, FlagChanged, ProgramEvent, Withdrawn};
+use crate::instruction::{AttestInstructionData, ConfigInstructionData, WithdrawInstructionData};
+use crate::pda::{CONFIG_PDA_SEED_PREFIX, CONFIG_PDA_SIZE, MAX_USER_PDA_SIZE, USER_PDA_SIZE};
+use crate::state::{self, ProgramConfig};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+
+use super::{create_pda_account, pack_profile};
+
+pub fn process_initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: ConfigInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    validation::check_signer(admin)?;
+    validation::check_writable(admin)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    validation::check_config_pda(program_id, pda)?;
+    validation::check_uninitialized(pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let program_data = next_account_info(account_info_iter)?;
+    validation::check_upgrade_authority(program_id, program_data, admin)?;
+    validate_config(&input, pda)?;
+
+    let (_, bump_seed) = crate::pda::find_config_pda(program_id);
+    create_pda_account(
+        program_id,
+        admin,
+        pda,
+        system_program,
+        CONFIG_PDA_SIZE,
+        &[CONFIG_PDA_SEED_PREFIX, &[bump_seed]],
+    )?;
+
+    let config = ProgramConfig::new(
+        *admin.key,
+        bump_seed,
+        input.paused,
+        input.max_user_pda_size,
+        input.registration_fee,
+        input.deposit,
+        input.attestation_authority,
+        input.referral_reward,
+    );
+    pack_config(&config, pda)?;
+
+    config_updated(&config).emit();
+    Ok(())
+}
+
+pub fn process_update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: ConfigInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    validation::check_owner(pda, program_id)?;
+    let mut config = validation::check_config_pda(program_id, pda)?;
+    validation::check_admin(&config, pda, admin)?;
+    validate_config(&input, pda)?;
+
+    config.paused = input.paused;
+    config.max_user_pda_size = input.max_user_pda_size;
+    config.registration_fee = input.registration_fee;
+    config.version = state::PROGRAM_CONFIG_VERSION;
+    config.deposit = input.deposit;
+    config.attestation_authority = input.attestation_authority;
+    config.referral_reward = input.referral_reward;
+    pack_config(&config, pda)?;
+
+    config_updated(&config).emit();
+    Ok(())
+}
+
+pub fn process_transfer_authority(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    validation::check_owner(pda, program_id)?;
+    let mut config = validation::check_config_pda(program_id, pda)?;
+    validation::check_admin(&config, pda, admin)?;
+    let new_admin = next_account_info(account_info_iter)?;
+    validation::check_signer(new_admin)?;
+
+    config.version = state::PROGRAM_CONFIG_VERSION;
+    config.admin = *new_admin.key;
+    pack_config(&config, pda)?;
+
+    config_updated(&config).emit();
+    Ok(())
+}
+
+pub fn process_slash_deposit(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let user = next_account_info(account_info_iter)?;
+    let user_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(user_pda)?;
+    let mut profile = validation::check_user_pda(program_id, user.key, user_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    validation::check_owner(pda, program_id)?;
+    let config = validation::check_config_pda(program_id, pda)?;
+    validation::check_admin(&config, pda, admin)?;
+    if profile.deposit == 0 {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(user_pda.key)
+            .with_message("No deposit to slash".to_string())
+            .into());
+    }
+
+    // Both PDAs are owned by this program, the lamports can be moved directly.
+    let lamports = profile.deposit;
+    **user_pda.lamports.borrow_mut() = user_pda
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **pda.lamports.borrow_mut() = pda
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    profile.deposit = 0;
+    pack_profile(&profile, user_pda)?;
+
+    ProgramEvent::DepositSlashed(DepositSlashed {
+        owner: *user.key,
+        lamports,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_flag(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    set_flagged(program_id, accounts, true)
+}
+
+pub fn process_unflag(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    set_flagged(program_id, accounts, false)
+}
+
+pub fn process_attest(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: AttestInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let authority = next_account_info(account_info_iter)?;
+    let user = next_account_info(account_info_iter)?;
+    let user_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(user_pda)?;
+    let mut profile = validation::check_user_pda(program_id, user.key, user_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_owner(pda, program_id)?;
+    let config = validation::check_config_pda(program_id, pda)?;
+    validation::check_attestation_authority(&config, pda, authority)?;
+    if input.flags == 0 || input.flags & !state::VERIFIED_ALL != 0 {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(user_pda.key)
+            .with_message(format!("Unknown verification flags: {:#b}", input.flags))
+            .into());
+    }
+
+    profile.version = state::USER_PROFILE_VERSION;
+    if input.verified {
+        profile.verifications |= input.flags;
+    } else {
+        profile.verifications &= !input.flags;
+    }
+    pack_profile(&profile, user_pda)?;
+
+    ProgramEvent::Attested(Attested {
+        owner: *user.key,
+        authority: *authority.key,
+        verifications: profile.verifications,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_withdraw(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: WithdrawInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    validation::check_owner(pda, program_id)?;
+    let config = validation::check_config_pda(program_id, pda)?;
+    validation::check_admin(&config, pda, admin)?;
+    let recipient = next_account_info(account_info_iter)?;
+    validation::check_writable(recipient)?;
+
+    if input.lamports == 0 {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("Nothing to withdraw".to_string())
+            .into());
+    }
+    let available = pda
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(pda.data_len()));
+    if input.lamports > available {
+        return Err(Error::new(ErrorCause::InsufficientRent)
+            .with_account(pda.key)
+            .with_message(format!(
+                "Can't withdraw {} lamports, {} available above the rent",
+                input.lamports, available
+            ))
+            .into());
+    }
+
+    // The config PDA is owned by this program, the lamports can be moved directly.
+    **pda.lamports.borrow_mut() -= input.lamports;
+    **recipient.lamports.borrow_mut() = recipient
+        .lamports()
+        .checked_add(input.lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    ProgramEvent::Withdrawn(Withdrawn {
+        admin: *admin.key,
+        recipient: *recipient.key,
+        lamports: input.lamports,
+    })
+    .emit();
+    Ok(())
+}
+
+fn set_flagged(program_id: &Pubkey, accounts: &[AccountInfo], flagged: bool) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    let user = next_account_info(account_info_iter)?;
+    let user_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(user_pda)?;
+    let mut profile = validation::check_user_pda(program_id, user.key, user_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_owner(pda, program_id)?;
+    let config = validation::check_config_pda(program_id, pda)?;
+    validation::check_admin(&config, pda, admin)?;
+    if profile.flagged == flagged {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(user_pda.key)
+            .with_message(if flagged {
+                "Already flagged".to_string()
+            } else {
+                "Not flagged".to_string()
+            })
+            .into());
+    }
+
+    profile.version = state::USER_PROFILE_VERSION;
+    profile.flagged = flagged;
+    pack_profile(&profile, user_pda)?;
+
+    let event = FlagChanged {
+        owner: *user.key,
+        admin: *admin.key,
+    };
+    if flagged {
+        ProgramEvent::UserFlagged(event).emit();
+    } else {
+        ProgramEvent::UserUnflagged(event).emit();
+    }
+    Ok(())
+}
+
+fn validate_config(input: &ConfigInstructionData, pda: &AccountInfo) -> Result<(), Error> {
+    if input.max_user_pda_size < USER_PDA_SIZE || input.max_user_pda_size > MAX_USER_PDA_SIZE {
+        return Err(Error::new(ErrorCause::InvalidAccountSize)
+            .with_account(pda.key)
+            .with_message(format!(
+                "Max user PDA size must be between {} and {}",
+                USER_PDA_SIZE, MAX_USER_PDA_SIZE
+            )));
+    }
+    if input.referral_reward > input.registration_fee {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("Referral reward can't exceed the registration fee".to_string()));
+    }
+    Ok(())
+}
+
+fn config_updated(config: &ProgramConfig) -> ProgramEvent {
+    ProgramEvent::ConfigUpdated(ConfigUpdated {
+        admin: config.admin,
+        paused: config.paused,
+        max_user_pda_size: config.max_user_pda_size,
+        registration_fee: config.registration_fee,
+        deposit: config.deposit,
+        attestation_authority: config.attestation_authority,
+        referral_reward: config.referral_reward,
+    })
+}
+
+fn pack_config(config: &ProgramConfig, pda: &AccountInfo) -> ProgramResult {
+    config.pack(&mut pda.try_borrow_mut_data()?).map_err(|_| {
+        Error::new(ErrorCause::AccountDataTooSmall)
+            .with_account(pda.key)
+            .into()
+    })
+}
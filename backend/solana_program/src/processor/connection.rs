@@ -0,0 +1,203 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{ConnectionChanged, ProgramEvent};
+use crate::instruction::{
+    SESSION_ACCEPT_CONNECTION, SESSION_REJECT_CONNECTION, SESSION_REMOVE_CONNECTION,
+};
+use crate::pda::{CONNECTION_PDA_SEED_PREFIX, CONNECTION_PDA_SIZE};
+use crate::state::{Connection, ConnectionStatus};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+use super::{block, close_pda_account, create_pda_account, session};
+
+pub fn process_request_connection(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let requester = next_account_info(account_info_iter)?;
+    validation::check_signer(requester)?;
+    validation::check_writable(requester)?;
+    // Both users need to be registered.
+    let requester_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, requester.key, requester_pda)?;
+    let recipient_pda = next_account_info(account_info_iter)?;
+    let recipient = validation::check_any_user_pda(program_id, recipient_pda)?.owner;
+    if recipient == *requester.key {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(recipient_pda.key)
+            .with_message("Can't connect with yourself".to_string())
+            .into());
+    }
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let bump_seed =
+        validation::check_new_connection_pda(program_id, requester.key, &recipient, pda)?;
+    validation::check_uninitialized(pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let requester_block_list = next_account_info(account_info_iter)?;
+    let recipient_block_list = next_account_info(account_info_iter)?;
+    block::check_not_blocked(
+        program_id,
+        requester.key,
+        requester_block_list,
+        &recipient,
+        recipient_block_list,
+    )?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    let connection = Connection::new(*requester.key, recipient, bump_seed, Clock::get()?.slot);
+    let pda_seed = &[
+        CONNECTION_PDA_SEED_PREFIX,
+        connection.user_a.as_ref(),
+        connection.user_b.as_ref(),
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        requester,
+        pda,
+        system_program,
+        CONNECTION_PDA_SIZE,
+        pda_seed,
+    )?;
+    pack_connection(&connection, pda)?;
+
+    ProgramEvent::ConnectionRequested(connection_changed(&connection, requester.key)).emit();
+    Ok(())
+}
+
+pub fn process_accept_connection(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    respond_to_connection(
+        program_id,
+        accounts,
+        ConnectionStatus::Accepted,
+        SESSION_ACCEPT_CONNECTION,
+    )
+}
+
+pub fn process_reject_connection(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    respond_to_connection(
+        program_id,
+        accounts,
+        ConnectionStatus::Rejected,
+        SESSION_REJECT_CONNECTION,
+    )
+}
+
+pub fn process_remove_connection(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer = next_account_info(account_info_iter)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let connection = validation::check_connection_pda(program_id, pda)?;
+    // The requester paid for the PDA, the rent goes back to them.
+    let requester = next_account_info(account_info_iter)?;
+    validation::check_writable(requester)?;
+    if *requester.key != connection.requester {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(requester.key)
+            .with_message(format!("Expected requester {}", connection.requester))
+            .into());
+    }
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    let user = session::check_authority(
+        program_id,
+        signer,
+        account_info_iter.next(),
+        SESSION_REMOVE_CONNECTION,
+    )?;
+    validation::check_connection_member(&connection, pda, &user)?;
+
+    close_pda_account(pda, requester)?;
+
+    ProgramEvent::ConnectionRemoved(connection_changed(&connection, &user)).emit();
+    Ok(())
+}
+
+// Only the recipient of a pending request can respond to it.
+// Accepting also takes the block lists of both users, rejecting is always possible.
+fn respond_to_connection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    status: ConnectionStatus,
+    session_instruction: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer = next_account_info(account_info_iter)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut connection = validation::check_connection_pda(program_id, pda)?;
+    let block_lists = match status {
+        ConnectionStatus::Accepted => Some((
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+        )),
+        _ => None,
+    };
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    let recipient = session::check_authority(
+        program_id,
+        signer,
+        account_info_iter.next(),
+        session_instruction,
+    )?;
+    validation::check_connection_member(&connection, pda, &recipient)?;
+    if connection.requester == recipient {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message("Requester can't respond to their own request".to_string())
+            .into());
+    }
+    if connection.status != ConnectionStatus::Pending {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message(format!("Connection is {:?}", connection.status))
+            .into());
+    }
+    if let Some((recipient_block_list, requester_block_list)) = block_lists {
+        block::check_not_blocked(
+            program_id,
+            &recipient,
+            recipient_block_list,
+            &connection.requester,
+            requester_block_list,
+        )?;
+    }
+
+    connection.status = status;
+    connection.updated_at_slot = Clock::get()?.slot;
+    pack_connection(&connection, pda)?;
+
+    let event = connection_changed(&connection, &recipient);
+    match status {
+        ConnectionStatus::Accepted => ProgramEvent::ConnectionAccepted(event),
+        _ => ProgramEvent::ConnectionRejected(event),
+    }
+    .emit();
+    Ok(())
+}
+
+fn connection_changed(connection: &Connection, changed_by: &Pubkey) -> ConnectionChanged {
+    ConnectionChanged {
+        requester: connection.requester,
+        recipient: connection.recipient(),
+        changed_by: *changed_by,
+    }
+}
+
+fn pack_connection(connection: &Connection, pda: &AccountInfo) -> ProgramResult {
+    connection
+        .pack(&mut pda.try_borrow_mut_data()?)
+        .map_err(|_| {
+            Error::new(ErrorCause::AccountDataTooSmall)
+                .with_account(pda.key)
+                .into()
+        })
+}
@@ -0,0 +1,137 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{EndorsementChanged, ProgramEvent};
+use crate::pda::{self, ENDORSEMENT_PDA_SEED_PREFIX, ENDORSEMENT_PDA_SIZE};
+use crate::state::{self, Endorsement};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+use super::{close_pda_account, create_pda_account, pack_profile};
+
+pub fn process_endorse(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let endorser = next_account_info(account_info_iter)?;
+    validation::check_signer(endorser)?;
+    validation::check_writable(endorser)?;
+    // Only registered users can endorse, and be endorsed.
+    let endorser_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, endorser.key, endorser_pda)?;
+    let endorsed_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(endorsed_pda)?;
+    let mut profile = validation::check_any_user_pda(program_id, endorsed_pda)?;
+    if profile.owner == *endorser.key {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(endorsed_pda.key)
+            .with_message("Can't endorse yourself".to_string())
+            .into());
+    }
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let bump_seed =
+        validation::check_new_endorsement_pda(program_id, endorser.key, &profile.owner, pda)?;
+    // The PDA exists while the endorsement holds, a user endorses another user once.
+    validation::check_uninitialized(pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    let endorsement = Endorsement::new(*endorser.key, profile.owner, bump_seed, Clock::get()?.slot);
+    let pda_seed = &[
+        ENDORSEMENT_PDA_SEED_PREFIX,
+        endorsement.endorser.as_ref(),
+        endorsement.endorsed.as_ref(),
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        endorser,
+        pda,
+        system_program,
+        ENDORSEMENT_PDA_SIZE,
+        pda_seed,
+    )?;
+    pack_endorsement(&endorsement, pda)?;
+
+    profile.version = state::USER_PROFILE_VERSION;
+    profile.endorsement_count = profile
+        .endorsement_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    pack_profile(&profile, endorsed_pda)?;
+
+    ProgramEvent::Endorsed(EndorsementChanged {
+        endorser: endorsement.endorser,
+        endorsed: endorsement.endorsed,
+        endorsement_count: profile.endorsement_count,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_revoke_endorsement(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let endorser = next_account_info(account_info_iter)?;
+    validation::check_signer(endorser)?;
+    validation::check_writable(endorser)?;
+    let endorsed_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(endorsed_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let endorsement = validation::check_endorsement_pda(program_id, pda)?;
+    if endorsement.endorser != *endorser.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message(format!("{} is not the endorser", endorser.key))
+            .into());
+    }
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    // The endorsed user may have closed the account since,
+    // and a new profile doesn't count the endorsements of the old one.
+    let mut endorsement_count = 0;
+    if endorsed_pda.owner == program_id {
+        let mut profile =
+            validation::check_user_pda(program_id, &endorsement.endorsed, endorsed_pda)?;
+        if profile.created_at_slot <= endorsement.created_at_slot {
+            profile.version = state::USER_PROFILE_VERSION;
+            profile.endorsement_count = profile.endorsement_count.saturating_sub(1);
+            pack_profile(&profile, endorsed_pda)?;
+        }
+        endorsement_count = profile.endorsement_count;
+    } else {
+        let (expected_pda, _) = pda::find_user_pda(program_id, &endorsement.endorsed);
+        if expected_pda != *endorsed_pda.key {
+            return Err(Error::new(ErrorCause::InvalidPda)
+                .with_account(endorsed_pda.key)
+                .with_message(format!("Expected {}", expected_pda))
+                .into());
+        }
+    }
+
+    close_pda_account(pda, endorser)?;
+
+    ProgramEvent::EndorsementRevoked(EndorsementChanged {
+        endorser: endorsement.endorser,
+        endorsed: endorsement.endorsed,
+        endorsement_count,
+    })
+    .emit();
+    Ok(())
+}
+
+fn pack_endorsement(endorsement: &Endorsement, pda: &AccountInfo) -> ProgramResult {
+    endorsement
+        .pack(&mut pda.try_borrow_mut_data()?)
+        .map_err(|_| {
+            Error::new(ErrorCause::AccountDataTooSmall)
+                .with_account(pda.key)
+                .into()
+        })
+}
@@ -0,0 +1,344 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{EscrowChanged, EscrowCreated, EscrowSettled, ProgramEvent};
+use crate::instruction::CreateEscrowInstructionData;
+use crate::pda::{ESCROW_PDA_SEED_PREFIX, ESCROW_PDA_SIZE};
+use crate::state::Escrow;
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::system_instruction;
+use solana_program::system_program;
+use solana_program::sysvar::Sysvar;
+
+use super::{close_pda_account, create_pda_account};
+
+pub fn process_create_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: CreateEscrowInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let proposer = next_account_info(account_info_iter)?;
+    validation::check_signer(proposer)?;
+    validation::check_writable(proposer)?;
+    // Meetings are planned by registered users only.
+    let proposer_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, proposer.key, proposer_pda)?;
+    let partner = next_account_info(account_info_iter)?;
+    if partner.key == proposer.key {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(partner.key)
+            .with_message("The proposer can't be the partner".to_string())
+            .into());
+    }
+    let partner_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, partner.key, partner_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let bump_seed = validation::check_new_escrow_pda(
+        program_id,
+        proposer.key,
+        partner.key,
+        input.meeting_id,
+        pda,
+    )?;
+    validation::check_uninitialized(pda)?;
+    if input.stake == 0 {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("The stake can't be zero".to_string())
+            .into());
+    }
+    let clock = Clock::get()?;
+    if input.deadline_slot <= clock.slot {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("The deadline needs to be in the future".to_string())
+            .into());
+    }
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    if let Some(key) = input.charity {
+        let charity = next_account_info(account_info_iter)?;
+        validation::check_key(charity, &key)?;
+        check_charity(charity, proposer.key, partner.key)?;
+    }
+
+    let escrow = Escrow::new(
+        *proposer.key,
+        *partner.key,
+        input.meeting_id,
+        bump_seed,
+        input.stake,
+        input.deadline_slot,
+        input.charity,
+        clock.slot,
+    );
+    let meeting_id = input.meeting_id.to_le_bytes();
+    let pda_seed = &[
+        ESCROW_PDA_SEED_PREFIX,
+        proposer.key.as_ref(),
+        partner.key.as_ref(),
+        &meeting_id,
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        proposer,
+        pda,
+        system_program,
+        ESCROW_PDA_SIZE,
+        pda_seed,
+    )?;
+    // The stake is locked on top of the rent.
+    solana_program::program::invoke(
+        &system_instruction::transfer(proposer.key, pda.key, escrow.stake),
+        &[proposer.clone(), pda.clone(), system_program.clone()],
+    )?;
+    pack_escrow(&escrow, pda)?;
+
+    ProgramEvent::EscrowCreated(EscrowCreated {
+        escrow: *pda.key,
+        proposer: escrow.proposer,
+        partner: escrow.partner,
+        stake: escrow.stake,
+        deadline_slot: escrow.deadline_slot,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_join_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let partner = next_account_info(account_info_iter)?;
+    validation::check_signer(partner)?;
+    validation::check_writable(partner)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut escrow = validation::check_escrow_pda(program_id, pda)?;
+    if escrow.partner != *partner.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message(format!("{} is not the partner", partner.key))
+            .into());
+    }
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    if escrow.partner_staked {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message("The partner already joined".to_string())
+            .into());
+    }
+    check_before_deadline(&escrow, pda)?;
+
+    solana_program::program::invoke(
+        &system_instruction::transfer(partner.key, pda.key, escrow.stake),
+        &[partner.clone(), pda.clone(), system_program.clone()],
+    )?;
+    escrow.partner_staked = true;
+    pack_escrow(&escrow, pda)?;
+
+    ProgramEvent::EscrowJoined(EscrowChanged {
+        escrow: *pda.key,
+        participant: *partner.key,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_check_in(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let participant = next_account_info(account_info_iter)?;
+    validation::check_signer(participant)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut escrow = validation::check_escrow_pda(program_id, pda)?;
+    if !escrow.is_participant(participant.key) {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message(format!("{} is not a participant", participant.key))
+            .into());
+    }
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    if !escrow.partner_staked {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message("The partner didn't join yet".to_string())
+            .into());
+    }
+    check_before_deadline(&escrow, pda)?;
+    let checked_in = if *participant.key == escrow.proposer {
+        &mut escrow.proposer_checked_in
+    } else {
+        &mut escrow.partner_checked_in
+    };
+    if *checked_in {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message(format!("{} already checked in", participant.key))
+            .into());
+    }
+    *checked_in = true;
+    pack_escrow(&escrow, pda)?;
+
+    ProgramEvent::CheckedIn(EscrowChanged {
+        escrow: *pda.key,
+        participant: *participant.key,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_settle_escrow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let escrow = validation::check_escrow_pda(program_id, pda)?;
+    let proposer = next_account_info(account_info_iter)?;
+    validation::check_writable(proposer)?;
+    validation::check_key(proposer, &escrow.proposer)?;
+    let partner = next_account_info(account_info_iter)?;
+    validation::check_writable(partner)?;
+    validation::check_key(partner, &escrow.partner)?;
+    // The stakes must not stay locked while the program is paused.
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_config_pda(program_id, config_pda)?;
+    let charity = match escrow.charity {
+        Some(key) => {
+            let charity = next_account_info(account_info_iter)?;
+            validation::check_writable(charity)?;
+            validation::check_key(charity, &key)?;
+            Some(charity)
+        }
+        None => None,
+    };
+
+    if !escrow.is_met() && Clock::get()?.slot <= escrow.deadline_slot {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message(format!(
+                "The participants can check in until slot {}",
+                escrow.deadline_slot
+            ))
+            .into());
+    }
+
+    // The escrow PDA is owned by this program, the stakes can be moved directly.
+    let mut refunded = 0;
+    let mut forfeited = 0;
+    if escrow.partner_staked {
+        // The charity may have been drained since the escrow was created,
+        // it can't take stakes leaving it below the rent exemption.
+        let no_shows = [escrow.proposer_checked_in, escrow.partner_checked_in]
+            .into_iter()
+            .filter(|checked_in| !checked_in)
+            .count() as u64;
+        let charity = match charity {
+            Some(charity)
+                if Rent::get()?.is_exempt(
+                    charity
+                        .lamports()
+                        .saturating_add(escrow.stake.saturating_mul(no_shows)),
+                    charity.data_len(),
+                ) =>
+            {
+                Some(charity)
+            }
+            _ => None,
+        };
+        for (checked_in, participant, other) in [
+            (escrow.proposer_checked_in, proposer, partner),
+            (escrow.partner_checked_in, partner, proposer),
+        ] {
+            if checked_in {
+                move_lamports(pda, participant, escrow.stake)?;
+                refunded += escrow.stake;
+            } else {
+                move_lamports(pda, charity.unwrap_or(other), escrow.stake)?;
+                forfeited += escrow.stake;
+            }
+        }
+    } else {
+        move_lamports(pda, proposer, escrow.stake)?;
+        refunded = escrow.stake;
+    }
+    close_pda_account(pda, proposer)?;
+
+    ProgramEvent::EscrowSettled(EscrowSettled {
+        escrow: *pda.key,
+        refunded,
+        forfeited,
+    })
+    .emit();
+    Ok(())
+}
+
+/// The forfeited stakes are moved to the charity on settlement,
+/// make sure it can hold lamports and is rent exempt when the escrow is created.
+fn check_charity(charity: &AccountInfo, proposer: &Pubkey, partner: &Pubkey) -> ProgramResult {
+    if charity.key == proposer || charity.key == partner {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(charity.key)
+            .with_message("A participant can't be the charity".to_string())
+            .into());
+    }
+    if *charity.owner != system_program::ID || charity.executable {
+        return Err(Error::new(ErrorCause::InvalidAccountOwner)
+            .with_account(charity.key)
+            .with_message("The charity needs to be a system account".to_string())
+            .into());
+    }
+    if !Rent::get()?.is_exempt(charity.lamports(), charity.data_len()) {
+        return Err(Error::new(ErrorCause::InsufficientRent)
+            .with_account(charity.key)
+            .with_message("The charity needs to be rent exempt".to_string())
+            .into());
+    }
+    Ok(())
+}
+
+fn check_before_deadline(escrow: &Escrow, pda: &AccountInfo) -> ProgramResult {
+    if Clock::get()?.slot > escrow.deadline_slot {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message(format!(
+                "The deadline passed at slot {}",
+                escrow.deadline_slot
+            ))
+            .into());
+    }
+    Ok(())
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    **from.lamports.borrow_mut() = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **to.lamports.borrow_mut() = to
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+fn pack_escrow(escrow: &Escrow, pda: &AccountInfo) -> ProgramResult {
+    escrow.pack(&mut pda.try_borrow_mut_data()?).map_err(|_| {
+        Error::new(ErrorCause::AccountDataTooSmall)
+            .with_account(pda.key)
+            .into()
+    })
+}
@@ -0,0 +1,277 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{GroupCreated, MembershipChanged, ProgramEvent};
+use crate::instruction::CreateGroupInstructionData;
+use crate::pda::{
+    GROUP_PDA_SEED_PREFIX, GROUP_PDA_SIZE, MEMBERSHIP_PDA_SEED_PREFIX, MEMBERSHIP_PDA_SIZE,
+};
+use crate::state::{self, Group, Membership};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+use super::{close_pda_account, create_pda_account};
+
+pub fn process_create_group(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: CreateGroupInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let creator = next_account_info(account_info_iter)?;
+    validation::check_signer(creator)?;
+    validation::check_writable(creator)?;
+    // Groups are made of registered users only.
+    let creator_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, creator.key, creator_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let bump_seed = validation::check_new_group_pda(program_id, creator.key, input.group_id, pda)?;
+    validation::check_uninitialized(pda)?;
+    state::validate_group_name(&input.name).map_err(|_| {
+        Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("Invalid group name".to_string())
+    })?;
+    if input.max_members == 0 {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("The group needs at least one member".to_string())
+            .into());
+    }
+    let membership_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(membership_pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    let slot = Clock::get()?.slot;
+    let mut group = Group::new(
+        *creator.key,
+        input.group_id,
+        bump_seed,
+        input.name,
+        input.invite_only,
+        input.max_members,
+        slot,
+    );
+    let group_id = input.group_id.to_le_bytes();
+    let pda_seed = &[
+        GROUP_PDA_SEED_PREFIX,
+        creator.key.as_ref(),
+        &group_id,
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        creator,
+        pda,
+        system_program,
+        GROUP_PDA_SIZE,
+        pda_seed,
+    )?;
+    // The admin is the first member.
+    create_membership(
+        program_id,
+        pda.key,
+        creator,
+        membership_pda,
+        system_program,
+        slot,
+    )?;
+    group.member_count = 1;
+    pack_group(&group, pda)?;
+
+    ProgramEvent::GroupCreated(GroupCreated {
+        group: *pda.key,
+        admin: group.admin,
+        name: group.name,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_join_group(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let member = next_account_info(account_info_iter)?;
+    validation::check_signer(member)?;
+    validation::check_writable(member)?;
+    let member_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, member.key, member_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut group = validation::check_group_pda(program_id, pda)?;
+    let membership_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(membership_pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    // Only the admin lets new members into an invite-only group.
+    if group.invite_only {
+        let admin = account_info_iter.next();
+        if !admin.is_some_and(|a| a.is_signer && *a.key == group.admin) {
+            return Err(Error::new(ErrorCause::Unauthorized)
+                .with_account(pda.key)
+                .with_message("The group is invite-only, the admin needs to sign".to_string())
+                .into());
+        }
+    }
+    if group.is_full() {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message(format!("The group has {} members", group.member_count))
+            .into());
+    }
+
+    create_membership(
+        program_id,
+        pda.key,
+        member,
+        membership_pda,
+        system_program,
+        Clock::get()?.slot,
+    )?;
+    group.member_count = group
+        .member_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    pack_group(&group, pda)?;
+
+    ProgramEvent::MemberJoined(membership_changed(pda.key, member.key, &group)).emit();
+    Ok(())
+}
+
+pub fn process_leave_group(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let member = next_account_info(account_info_iter)?;
+    validation::check_signer(member)?;
+    validation::check_writable(member)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut group = validation::check_group_pda(program_id, pda)?;
+    let membership_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(membership_pda)?;
+    let membership = validation::check_membership_pda(program_id, pda.key, membership_pda)?;
+    if membership.member != *member.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(membership_pda.key)
+            .with_message(format!("{} is not the member", member.key))
+            .into());
+    }
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    check_not_admin(&group, &membership, pda)?;
+
+    close_pda_account(membership_pda, member)?;
+    group.member_count = group.member_count.saturating_sub(1);
+    pack_group(&group, pda)?;
+
+    ProgramEvent::MemberLeft(membership_changed(pda.key, member.key, &group)).emit();
+    Ok(())
+}
+
+pub fn process_kick_member(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let admin = next_account_info(account_info_iter)?;
+    validation::check_signer(admin)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut group = validation::check_group_pda(program_id, pda)?;
+    if group.admin != *admin.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message(format!("{} is not the admin", admin.key))
+            .into());
+    }
+    let membership_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(membership_pda)?;
+    let membership = validation::check_membership_pda(program_id, pda.key, membership_pda)?;
+    // The member paid for the PDA, the rent goes back to them.
+    let member = next_account_info(account_info_iter)?;
+    validation::check_writable(member)?;
+    if *member.key != membership.member {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(member.key)
+            .with_message(format!("Expected member {}", membership.member))
+            .into());
+    }
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    check_not_admin(&group, &membership, pda)?;
+
+    close_pda_account(membership_pda, member)?;
+    group.member_count = group.member_count.saturating_sub(1);
+    pack_group(&group, pda)?;
+
+    ProgramEvent::MemberKicked(membership_changed(pda.key, member.key, &group)).emit();
+    Ok(())
+}
+
+// Creates the membership PDA of `member` in the group, paid by the member.
+fn create_membership<'a>(
+    program_id: &Pubkey,
+    group: &Pubkey,
+    member: &AccountInfo<'a>,
+    pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    slot: u64,
+) -> ProgramResult {
+    let bump_seed = validation::check_new_membership_pda(program_id, group, member.key, pda)?;
+    // The PDA exists while the user is a member, a user joins a group once.
+    validation::check_uninitialized(pda)?;
+    let pda_seed = &[
+        MEMBERSHIP_PDA_SEED_PREFIX,
+        group.as_ref(),
+        member.key.as_ref(),
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        member,
+        pda,
+        system_program,
+        MEMBERSHIP_PDA_SIZE,
+        pda_seed,
+    )?;
+    let membership = Membership::new(*group, *member.key, bump_seed, slot);
+    membership
+        .pack(&mut pda.try_borrow_mut_data()?)
+        .map_err(|_| {
+            Error::new(ErrorCause::AccountDataTooSmall)
+                .with_account(pda.key)
+                .into()
+        })
+}
+
+// The group would be left without an admin.
+fn check_not_admin(group: &Group, membership: &Membership, pda: &AccountInfo) -> ProgramResult {
+    if membership.member == group.admin {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message("The admin can't leave the group".to_string())
+            .into());
+    }
+    Ok(())
+}
+
+fn membership_changed(pda: &Pubkey, member: &Pubkey, group: &Group) -> MembershipChanged {
+    MembershipChanged {
+        group: *pda,
+        member: *member,
+        member_count: group.member_count,
+    }
+}
+
+fn pack_group(group: &Group, pda: &AccountInfo) -> ProgramResult {
+    group.pack(&mut pda.try_borrow_mut_data()?).map_err(|_| {
+        Error::new(ErrorCause::AccountDataTooSmall)
+            .with_account(pda.key)
+            .into()
+    })
+}
@@ -0,0 +1,231 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{EventClosed, EventCreated, ProgramEvent, RsvpChanged};
+use crate::instruction::CreateEventInstructionData;
+use crate::pda::{EVENT_PDA_SEED_PREFIX, EVENT_PDA_SIZE, RSVP_PDA_SEED_PREFIX, RSVP_PDA_SIZE};
+use crate::state::{self, Event, Rsvp};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+use super::{close_pda_account, create_pda_account};
+
+pub fn process_create_event(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: CreateEventInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let organizer = next_account_info(account_info_iter)?;
+    validation::check_signer(organizer)?;
+    validation::check_writable(organizer)?;
+    // Events are organized by registered users only.
+    let organizer_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, organizer.key, organizer_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let bump_seed =
+        validation::check_new_event_pda(program_id, organizer.key, input.event_id, pda)?;
+    validation::check_uninitialized(pda)?;
+    state::validate_event(&input.title, &input.location_hint).map_err(|_| {
+        Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("Invalid title or location hint".to_string())
+    })?;
+    if input.capacity == 0 {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("The event needs at least one attendee".to_string())
+            .into());
+    }
+    let clock = Clock::get()?;
+    if input.starts_at <= clock.unix_timestamp {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("The event needs to start in the future".to_string())
+            .into());
+    }
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    let event = Event::new(
+        *organizer.key,
+        input.event_id,
+        bump_seed,
+        input.title,
+        input.starts_at,
+        input.location_hint,
+        input.capacity,
+        clock.slot,
+    );
+    let event_id = input.event_id.to_le_bytes();
+    let pda_seed = &[
+        EVENT_PDA_SEED_PREFIX,
+        organizer.key.as_ref(),
+        &event_id,
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        organizer,
+        pda,
+        system_program,
+        EVENT_PDA_SIZE,
+        pda_seed,
+    )?;
+    pack_event(&event, pda)?;
+
+    ProgramEvent::EventCreated(EventCreated {
+        event: *pda.key,
+        organizer: event.organizer,
+        starts_at: event.starts_at,
+        capacity: event.capacity,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_rsvp(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let attendee = next_account_info(account_info_iter)?;
+    validation::check_signer(attendee)?;
+    validation::check_writable(attendee)?;
+    let attendee_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, attendee.key, attendee_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut event = validation::check_event_pda(program_id, pda)?;
+    let rsvp_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(rsvp_pda)?;
+    let bump_seed = validation::check_new_rsvp_pda(program_id, pda.key, attendee.key, rsvp_pda)?;
+    // The PDA exists while the user attends, a user RSVPs once.
+    validation::check_uninitialized(rsvp_pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    let clock = Clock::get()?;
+    if event.closed || event.starts_at <= clock.unix_timestamp {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message("The event takes no more RSVPs".to_string())
+            .into());
+    }
+    if event.is_full() {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message(format!("The event has {} attendees", event.attendee_count))
+            .into());
+    }
+
+    let pda_seed = &[
+        RSVP_PDA_SEED_PREFIX,
+        pda.key.as_ref(),
+        attendee.key.as_ref(),
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        attendee,
+        rsvp_pda,
+        system_program,
+        RSVP_PDA_SIZE,
+        pda_seed,
+    )?;
+    let rsvp = Rsvp::new(*pda.key, *attendee.key, bump_seed, clock.slot);
+    rsvp.pack(&mut rsvp_pda.try_borrow_mut_data()?)
+        .map_err(|_| Error::new(ErrorCause::AccountDataTooSmall).with_account(rsvp_pda.key))?;
+    event.attendee_count = event
+        .attendee_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    pack_event(&event, pda)?;
+
+    ProgramEvent::Rsvped(rsvp_changed(pda.key, attendee.key, &event)).emit();
+    Ok(())
+}
+
+pub fn process_cancel_rsvp(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let attendee = next_account_info(account_info_iter)?;
+    validation::check_signer(attendee)?;
+    validation::check_writable(attendee)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut event = validation::check_event_pda(program_id, pda)?;
+    let rsvp_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(rsvp_pda)?;
+    let rsvp = validation::check_rsvp_pda(program_id, pda.key, rsvp_pda)?;
+    if rsvp.attendee != *attendee.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(rsvp_pda.key)
+            .with_message(format!("{} is not the attendee", attendee.key))
+            .into());
+    }
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    // Also possible once the event is closed or over, to get the rent back.
+    close_pda_account(rsvp_pda, attendee)?;
+    event.attendee_count = event.attendee_count.saturating_sub(1);
+    pack_event(&event, pda)?;
+
+    ProgramEvent::RsvpCancelled(rsvp_changed(pda.key, attendee.key, &event)).emit();
+    Ok(())
+}
+
+pub fn process_close_event(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let organizer = next_account_info(account_info_iter)?;
+    validation::check_signer(organizer)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut event = validation::check_event_pda(program_id, pda)?;
+    if event.organizer != *organizer.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message(format!("{} is not the organizer", organizer.key))
+            .into());
+    }
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    if event.closed {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message("The event is already closed".to_string())
+            .into());
+    }
+
+    event.closed = true;
+    pack_event(&event, pda)?;
+
+    ProgramEvent::EventClosed(EventClosed {
+        event: *pda.key,
+        attendee_count: event.attendee_count,
+    })
+    .emit();
+    Ok(())
+}
+
+fn rsvp_changed(pda: &Pubkey, attendee: &Pubkey, event: &Event) -> RsvpChanged {
+    RsvpChanged {
+        event: *pda,
+        attendee: *attendee,
+        attendee_count: event.attendee_count,
+    }
+}
+
+fn pack_event(event: &Event, pda: &AccountInfo) -> ProgramResult {
+    event.pack(&mut pda.try_borrow_mut_data()?).map_err(|_| {
+        Error::new(ErrorCause::AccountDataTooSmall)
+            .with_account(pda.key)
+            .into()
+    })
+}
@@ -0,0 +1,254 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{PollClosed, PollCreated, ProgramEvent, VoteClosed, Voted};
+use crate::instruction::{CreatePollInstructionData, VoteInstructionData};
+use crate::pda::{POLL_PDA_SEED_PREFIX, POLL_PDA_SIZE, VOTE_PDA_SEED_PREFIX, VOTE_PDA_SIZE};
+use crate::state::{self, Poll, Vote};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+use super::{close_pda_account, create_pda_account};
+
+pub fn process_create_poll(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: CreatePollInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let creator = next_account_info(account_info_iter)?;
+    validation::check_signer(creator)?;
+    validation::check_writable(creator)?;
+    // Polls are created by registered users only.
+    let creator_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, creator.key, creator_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let bump_seed = validation::check_new_poll_pda(program_id, creator.key, input.poll_id, pda)?;
+    validation::check_uninitialized(pda)?;
+    state::validate_poll(&input.question, &input.options).map_err(|_| {
+        Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("Invalid question or options".to_string())
+    })?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    if let Some(group) = &input.group {
+        let membership_pda = next_account_info(account_info_iter)?;
+        check_member(program_id, group, creator.key, membership_pda)?;
+    }
+
+    let poll = Poll::new(
+        *creator.key,
+        input.poll_id,
+        bump_seed,
+        input.group,
+        input.question,
+        input.options,
+        Clock::get()?.slot,
+    );
+    let poll_id = input.poll_id.to_le_bytes();
+    let pda_seed = &[
+        POLL_PDA_SEED_PREFIX,
+        creator.key.as_ref(),
+        &poll_id,
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        creator,
+        pda,
+        system_program,
+        POLL_PDA_SIZE,
+        pda_seed,
+    )?;
+    pack_poll(&poll, pda)?;
+
+    ProgramEvent::PollCreated(PollCreated {
+        poll: *pda.key,
+        creator: poll.creator,
+        group: poll.group,
+        question: poll.question,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: VoteInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let voter = next_account_info(account_info_iter)?;
+    validation::check_signer(voter)?;
+    validation::check_writable(voter)?;
+    let voter_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, voter.key, voter_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut poll = validation::check_poll_pda(program_id, pda)?;
+    let vote_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(vote_pda)?;
+    let bump_seed = validation::check_new_vote_pda(program_id, pda.key, voter.key, vote_pda)?;
+    // The PDA is closed only after the poll, a user votes once.
+    validation::check_uninitialized(vote_pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    if let Some(group) = &poll.group {
+        let membership_pda = next_account_info(account_info_iter)?;
+        check_member(program_id, group, voter.key, membership_pda)?;
+    }
+
+    if poll.closed {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message("The poll takes no more votes".to_string())
+            .into());
+    }
+    let votes = poll.tallies.get_mut(input.option as usize).ok_or_else(|| {
+        Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message(format!("The poll has no option {}", input.option))
+    })?;
+    *votes = votes
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let votes = *votes;
+
+    let pda_seed = &[
+        VOTE_PDA_SEED_PREFIX,
+        pda.key.as_ref(),
+        voter.key.as_ref(),
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        voter,
+        vote_pda,
+        system_program,
+        VOTE_PDA_SIZE,
+        pda_seed,
+    )?;
+    let vote = Vote::new(
+        *pda.key,
+        *voter.key,
+        bump_seed,
+        input.option,
+        Clock::get()?.slot,
+    );
+    vote.pack(&mut vote_pda.try_borrow_mut_data()?)
+        .map_err(|_| Error::new(ErrorCause::AccountDataTooSmall).with_account(vote_pda.key))?;
+    pack_poll(&poll, pda)?;
+
+    ProgramEvent::Voted(Voted {
+        poll: *pda.key,
+        voter: *voter.key,
+        option: input.option,
+        votes,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_close_poll(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let creator = next_account_info(account_info_iter)?;
+    validation::check_signer(creator)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut poll = validation::check_poll_pda(program_id, pda)?;
+    if poll.creator != *creator.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message(format!("{} is not the creator", creator.key))
+            .into());
+    }
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    if poll.closed {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message("The poll is already closed".to_string())
+            .into());
+    }
+
+    poll.closed = true;
+    pack_poll(&poll, pda)?;
+
+    ProgramEvent::PollClosed(PollClosed {
+        poll: *pda.key,
+        tallies: poll.tallies,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_close_vote(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let voter = next_account_info(account_info_iter)?;
+    validation::check_signer(voter)?;
+    validation::check_writable(voter)?;
+    let pda = next_account_info(account_info_iter)?;
+    let poll = validation::check_poll_pda(program_id, pda)?;
+    let vote_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(vote_pda)?;
+    let vote = validation::check_vote_pda(program_id, pda.key, vote_pda)?;
+    if vote.voter != *voter.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(vote_pda.key)
+            .with_message(format!("{} is not the voter", voter.key))
+            .into());
+    }
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+    // The vote PDA keeps the voter from voting again while the poll is open.
+    if !poll.closed {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message("The poll still takes votes".to_string())
+            .into());
+    }
+
+    close_pda_account(vote_pda, voter)?;
+
+    ProgramEvent::VoteClosed(VoteClosed {
+        poll: *pda.key,
+        voter: *voter.key,
+    })
+    .emit();
+    Ok(())
+}
+
+// Checks that `user` is a member of the group the poll is restricted to.
+fn check_member(
+    program_id: &Pubkey,
+    group: &Pubkey,
+    user: &Pubkey,
+    membership_pda: &AccountInfo,
+) -> ProgramResult {
+    let membership = validation::check_membership_pda(program_id, group, membership_pda)?;
+    if membership.member != *user {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(membership_pda.key)
+            .with_message(format!("{} is not a member of group {}", user, group))
+            .into());
+    }
+    Ok(())
+}
+
+fn pack_poll(poll: &Poll, pda: &AccountInfo) -> ProgramResult {
+    poll.pack(&mut pda.try_borrow_mut_data()?).map_err(|_| {
+        Error::new(ErrorCause::AccountDataTooSmall)
+            .with_account(pda.key)
+            .into()
+    })
+}
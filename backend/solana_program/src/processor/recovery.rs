@@ -0,0 +1,267 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{GuardiansSet, ProgramEvent, RecoveryChanged, RecoveryProposed};
+use crate::instruction::{
+    ProposeRecoveryInstructionData, SetGuardiansInstructionData, TransferOwnershipInstructionData,
+};
+use crate::pda::{RECOVERY_PDA_SEED_PREFIX, RECOVERY_PDA_SIZE};
+use crate::state::{self, Recovery, UserProfile};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+use super::{close_pda_account, create_pda_account, move_profile, pack_profile};
+
+pub fn process_set_guardians(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: SetGuardiansInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    validation::check_signer(owner)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut profile = validation::check_user_pda(program_id, owner.key, pda)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    state::validate_guardians(owner.key, &input.guardians, input.threshold).map_err(|_| {
+        Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("Invalid guardians or threshold".to_string())
+    })?;
+
+    profile.version = state::USER_PROFILE_VERSION;
+    profile.guardians = input.guardians;
+    profile.guardian_threshold = input.threshold;
+    pack_profile(&profile, pda)?;
+
+    ProgramEvent::GuardiansSet(GuardiansSet {
+        owner: *owner.key,
+        guardians: profile.guardians,
+        threshold: profile.guardian_threshold,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_propose_recovery(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: ProposeRecoveryInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let guardian = next_account_info(account_info_iter)?;
+    validation::check_signer(guardian)?;
+    validation::check_writable(guardian)?;
+    let owner = next_account_info(account_info_iter)?;
+    if input.new_owner == *owner.key {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(&input.new_owner)
+            .with_message("The new owner can't be the current one".to_string())
+            .into());
+    }
+    let pda = next_account_info(account_info_iter)?;
+    let profile = validation::check_user_pda(program_id, owner.key, pda)?;
+    check_guardian(&profile, guardian.key, pda)?;
+    let recovery_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(recovery_pda)?;
+    let bump_seed = validation::check_new_recovery_pda(program_id, pda.key, recovery_pda)?;
+    // One recovery at a time, the pending one needs to be cancelled first.
+    validation::check_uninitialized(recovery_pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    let recovery = Recovery::new(
+        *pda.key,
+        input.new_owner,
+        *guardian.key,
+        bump_seed,
+        Clock::get()?.slot,
+    );
+    let pda_seed = &[RECOVERY_PDA_SEED_PREFIX, pda.key.as_ref(), &[bump_seed]];
+    create_pda_account(
+        program_id,
+        guardian,
+        recovery_pda,
+        system_program,
+        RECOVERY_PDA_SIZE,
+        pda_seed,
+    )?;
+    pack_recovery(&recovery, recovery_pda)?;
+
+    ProgramEvent::RecoveryProposed(RecoveryProposed {
+        recovery: *recovery_pda.key,
+        user_pda: *pda.key,
+        guardian: *guardian.key,
+        new_owner: recovery.new_owner,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_approve_recovery(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let guardian = next_account_info(account_info_iter)?;
+    validation::check_signer(guardian)?;
+    let owner = next_account_info(account_info_iter)?;
+    let pda = next_account_info(account_info_iter)?;
+    let profile = validation::check_user_pda(program_id, owner.key, pda)?;
+    check_guardian(&profile, guardian.key, pda)?;
+    let recovery_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(recovery_pda)?;
+    let mut recovery = validation::check_recovery_pda(program_id, pda.key, recovery_pda)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    if recovery.approvals.contains(guardian.key) {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(recovery_pda.key)
+            .with_message(format!("{} already approved", guardian.key))
+            .into());
+    }
+    // Approvals of former guardians don't count, dropping them keeps the list
+    // within the guardian limit.
+    recovery
+        .approvals
+        .retain(|approval| profile.is_guardian(approval));
+    recovery.approvals.push(*guardian.key);
+    pack_recovery(&recovery, recovery_pda)?;
+
+    ProgramEvent::RecoveryApproved(RecoveryChanged {
+        recovery: *recovery_pda.key,
+        user_pda: *pda.key,
+        changed_by: *guardian.key,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_execute_recovery(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: TransferOwnershipInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let new_owner = next_account_info(account_info_iter)?;
+    validation::check_signer(new_owner)?;
+    validation::check_writable(new_owner)?;
+    let owner = next_account_info(account_info_iter)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let profile = validation::check_user_pda(program_id, owner.key, pda)?;
+    let new_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(new_pda)?;
+    validation::check_new_user_pda(program_id, new_owner.key, new_pda, input.pda_bump_seed)?;
+    validation::check_uninitialized(new_pda)?;
+    let username_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(username_pda)?;
+    let block_list_pda = next_account_info(account_info_iter)?;
+    let new_block_list_pda = next_account_info(account_info_iter)?;
+    let recovery_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(recovery_pda)?;
+    let recovery = validation::check_recovery_pda(program_id, pda.key, recovery_pda)?;
+    validation::check_key(new_owner, &recovery.new_owner)?;
+    let proposer = next_account_info(account_info_iter)?;
+    validation::check_writable(proposer)?;
+    validation::check_key(proposer, &recovery.proposer)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    let approvals = recovery.approval_count(&profile.guardians);
+    if profile.guardian_threshold == 0 || approvals < profile.guardian_threshold as usize {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(recovery_pda.key)
+            .with_message(format!(
+                "{} of {} required guardians approved",
+                approvals, profile.guardian_threshold
+            ))
+            .into());
+    }
+    if Clock::get()?.slot < recovery.executable_at_slot() {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(recovery_pda.key)
+            .with_message(format!(
+                "The recovery can be executed from slot {}",
+                recovery.executable_at_slot()
+            ))
+            .into());
+    }
+
+    // The current owner lost the key, the lamports of the PDA go to the new wallet.
+    move_profile(
+        program_id,
+        profile,
+        pda,
+        new_owner.key,
+        new_pda,
+        input.pda_bump_seed,
+        username_pda,
+        block_list_pda,
+        new_block_list_pda,
+        new_owner,
+        new_owner,
+        system_program,
+    )?;
+    close_pda_account(recovery_pda, proposer)
+}
+
+pub fn process_cancel_recovery(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer = next_account_info(account_info_iter)?;
+    validation::check_signer(signer)?;
+    let pda = next_account_info(account_info_iter)?;
+    let recovery_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(recovery_pda)?;
+    let recovery = validation::check_recovery_pda(program_id, pda.key, recovery_pda)?;
+    let proposer = next_account_info(account_info_iter)?;
+    validation::check_writable(proposer)?;
+    validation::check_key(proposer, &recovery.proposer)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    // The proposer can withdraw the recovery even if the user PDA is gone.
+    if *signer.key != recovery.proposer
+        && validation::check_any_user_pda(program_id, pda)?.owner != *signer.key
+    {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(recovery_pda.key)
+            .with_message(format!("{} can't cancel the recovery", signer.key))
+            .into());
+    }
+    close_pda_account(recovery_pda, proposer)?;
+
+    ProgramEvent::RecoveryCancelled(RecoveryChanged {
+        recovery: *recovery_pda.key,
+        user_pda: *pda.key,
+        changed_by: *signer.key,
+    })
+    .emit();
+    Ok(())
+}
+
+fn check_guardian(profile: &UserProfile, guardian: &Pubkey, pda: &AccountInfo) -> ProgramResult {
+    if !profile.is_guardian(guardian) {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message(format!("{} is not a guardian", guardian))
+            .into());
+    }
+    Ok(())
+}
+
+fn pack_recovery(recovery: &Recovery, pda: &AccountInfo) -> ProgramResult {
+    recovery.pack(&mut pda.try_borrow_mut_data()?).map_err(|_| {
+        Error::new(ErrorCause::AccountDataTooSmall)
+            .with_account(pda.key)
+            .into()
+    })
+}
@@ -0,0 +1,143 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{ProgramEvent, Referred};
+use crate::pda::{CONFIG_PDA_SIZE, REFERRAL_PDA_SEED_PREFIX, REFERRAL_PDA_SIZE};
+use crate::state::{self, ProgramConfig, Referral, UserProfile};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program::sysvar::Sysvar;
+
+use super::{create_pda_account, pack_profile};
+
+/// Accounts of the referrer passed to the Initialize instruction.
+pub struct ReferralAccounts<'a, 'b> {
+    referrer: &'a AccountInfo<'b>,
+    referrer_pda: &'a AccountInfo<'b>,
+    referrer_profile: UserProfile,
+    referral_pda: &'a AccountInfo<'b>,
+    bump_seed: u8,
+}
+
+impl ReferralAccounts<'_, '_> {
+    /// Lamports the new user pays for the referral PDA.
+    pub fn required_lamports(&self, rent: &Rent) -> u64 {
+        rent.minimum_balance(REFERRAL_PDA_SIZE as usize)
+            .saturating_sub(self.referral_pda.lamports())
+    }
+}
+
+/// Takes the referral accounts following the config PDA and checks them.
+pub fn check_referral_accounts<'a, 'b>(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    referrer_key: &Pubkey,
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+) -> Result<ReferralAccounts<'a, 'b>, ProgramError> {
+    if referrer_key == user {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(referrer_key)
+            .with_message("A user can't refer themselves".to_string())
+            .into());
+    }
+    let referrer = next_account_info(account_info_iter)?;
+    validation::check_writable(referrer)?;
+    validation::check_key(referrer, referrer_key)?;
+    let referrer_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(referrer_pda)?;
+    let referrer_profile = validation::check_user_pda(program_id, referrer.key, referrer_pda)?;
+    let referral_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(referral_pda)?;
+    let bump_seed = validation::check_new_referral_pda(program_id, user, referral_pda)?;
+    // A wallet is referred only once, also if it closes the account and registers again.
+    validation::check_uninitialized(referral_pda)?;
+
+    Ok(ReferralAccounts {
+        referrer,
+        referrer_pda,
+        referrer_profile,
+        referral_pda,
+        bump_seed,
+    })
+}
+
+/// Records the referral of the new `user` and rewards the referrer.
+/// The reward is only paid if the config PDA stays rent exempt,
+/// the registration succeeds either way.
+pub fn create_referral<'a>(
+    program_id: &Pubkey,
+    accounts: ReferralAccounts<'_, 'a>,
+    user: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    config_pda: &AccountInfo<'a>,
+    config: &ProgramConfig,
+) -> ProgramResult {
+    let ReferralAccounts {
+        referrer,
+        referrer_pda,
+        mut referrer_profile,
+        referral_pda,
+        bump_seed,
+    } = accounts;
+
+    let pda_seed = &[REFERRAL_PDA_SEED_PREFIX, user.key.as_ref(), &[bump_seed]];
+    create_pda_account(
+        program_id,
+        user,
+        referral_pda,
+        system_program,
+        REFERRAL_PDA_SIZE,
+        pda_seed,
+    )?;
+
+    let available = config_pda
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(CONFIG_PDA_SIZE as usize));
+    let reward = if config.referral_reward > 0
+        && config.referral_reward <= available
+        && config_pda.owner == program_id
+    {
+        config.referral_reward
+    } else {
+        0
+    };
+    if reward > 0 {
+        // The config PDA is owned by this program, the lamports can be moved directly.
+        validation::check_writable(config_pda)?;
+        **config_pda.lamports.borrow_mut() = config_pda
+            .lamports()
+            .checked_sub(reward)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        **referrer.lamports.borrow_mut() = referrer
+            .lamports()
+            .checked_add(reward)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    let referral = Referral::new(
+        *referrer.key,
+        *user.key,
+        bump_seed,
+        Clock::get()?.slot,
+        reward,
+    );
+    referral
+        .pack(&mut referral_pda.try_borrow_mut_data()?)
+        .map_err(|_| Error::new(ErrorCause::AccountDataTooSmall).with_account(referral_pda.key))?;
+
+    referrer_profile.version = state::USER_PROFILE_VERSION;
+    referrer_profile.invite_count = referrer_profile.invite_count.saturating_add(1);
+    pack_profile(&referrer_profile, referrer_pda)?;
+
+    ProgramEvent::Referred(Referred {
+        referrer: *referrer.key,
+        user: *user.key,
+        reward,
+    })
+    .emit();
+    Ok(())
+}
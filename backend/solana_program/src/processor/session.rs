@@ -0,0 +1,176 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{ProgramEvent, SessionCreated, SessionRevoked};
+use crate::instruction::{CreateSessionInstructionData, SESSION_ALL};
+use crate::pda::{SESSION_PDA_SEED_PREFIX, SESSION_PDA_SIZE};
+use crate::state::Session;
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::clock::Clock;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::Sysvar;
+
+use super::{close_pda_account, create_pda_account};
+
+pub fn process_create_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: CreateSessionInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    validation::check_signer(owner)?;
+    validation::check_writable(owner)?;
+    let user_pda = next_account_info(account_info_iter)?;
+    validation::check_user_pda(program_id, owner.key, user_pda)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let delegate = next_account_info(account_info_iter)?;
+    let bump_seed = validation::check_new_session_pda(program_id, owner.key, delegate.key, pda)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    if delegate.key == owner.key {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(delegate.key)
+            .with_message("The owner can't be the delegate".to_string())
+            .into());
+    }
+    if input.allowed_instructions == 0 || input.allowed_instructions & !SESSION_ALL != 0 {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message(format!(
+                "Allowed instructions must be a subset of {:#x}",
+                SESSION_ALL
+            ))
+            .into());
+    }
+    if input.expires_at_slot <= Clock::get()?.slot {
+        return Err(Error::new(ErrorCause::InvalidInstructionData)
+            .with_account(pda.key)
+            .with_message("The session would be already expired".to_string())
+            .into());
+    }
+
+    // An existing session is renewed in place.
+    if pda.owner == program_id {
+        validation::check_session_pda(program_id, pda)?;
+    } else {
+        validation::check_uninitialized(pda)?;
+        let pda_seed = &[
+            SESSION_PDA_SEED_PREFIX,
+            owner.key.as_ref(),
+            delegate.key.as_ref(),
+            &[bump_seed],
+        ];
+        create_pda_account(
+            program_id,
+            owner,
+            pda,
+            system_program,
+            SESSION_PDA_SIZE,
+            pda_seed,
+        )?;
+    }
+    let session = Session::new(
+        *owner.key,
+        *delegate.key,
+        bump_seed,
+        input.expires_at_slot,
+        input.allowed_instructions,
+    );
+    pack_session(&session, pda)?;
+
+    ProgramEvent::SessionCreated(SessionCreated {
+        owner: session.owner,
+        delegate: session.delegate,
+        expires_at_slot: session.expires_at_slot,
+        allowed_instructions: session.allowed_instructions,
+    })
+    .emit();
+    Ok(())
+}
+
+pub fn process_revoke_session(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let signer = next_account_info(account_info_iter)?;
+    validation::check_signer(signer)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let session = validation::check_session_pda(program_id, pda)?;
+    if *signer.key != session.owner && *signer.key != session.delegate {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message(format!("{} is not a party of the session", signer.key))
+            .into());
+    }
+    // The owner paid for the PDA, the rent goes back to them.
+    let owner = next_account_info(account_info_iter)?;
+    validation::check_writable(owner)?;
+    if *owner.key != session.owner {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(owner.key)
+            .with_message(format!("Expected owner {}", session.owner))
+            .into());
+    }
+    // A leaked delegate key must be revocable even while the program is paused.
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_config_pda(program_id, config_pda)?;
+
+    close_pda_account(pda, owner)?;
+
+    ProgramEvent::SessionRevoked(SessionRevoked {
+        owner: session.owner,
+        delegate: session.delegate,
+        revoked_by: *signer.key,
+    })
+    .emit();
+    Ok(())
+}
+
+/// Checks that `signer` can sign the instruction flagged `instruction` in the session
+/// stored in `session_pda` and returns the user it signs for.
+/// Without a session the signer signs for itself.
+pub(crate) fn check_authority(
+    program_id: &Pubkey,
+    signer: &AccountInfo,
+    session_pda: Option<&AccountInfo>,
+    instruction: u64,
+) -> Result<Pubkey, ProgramError> {
+    validation::check_signer(signer)?;
+    let Some(session_pda) = session_pda else {
+        return Ok(*signer.key);
+    };
+
+    let session = validation::check_session_pda(program_id, session_pda)?;
+    if session.delegate != *signer.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(session_pda.key)
+            .with_message(format!("{} is not the delegate", signer.key))
+            .into());
+    }
+    if !session.allows(instruction) {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(session_pda.key)
+            .with_message("Instruction not allowed in the session".to_string())
+            .into());
+    }
+    if session.is_expired(Clock::get()?.slot) {
+        return Err(Error::new(ErrorCause::SessionExpired)
+            .with_account(session_pda.key)
+            .into());
+    }
+    Ok(session.owner)
+}
+
+fn pack_session(session: &Session, pda: &AccountInfo) -> ProgramResult {
+    session.pack(&mut pda.try_borrow_mut_data()?).map_err(|_| {
+        Error::new(ErrorCause::AccountDataTooSmall)
+            .with_account(pda.key)
+            .into()
+    })
+}
@@ -0,0 +1,150 @@
+use crate::error::{Error, ErrorCause};
+use crate::event::{ProgramEvent, UsernameClaimed};
+use crate::instruction::ClaimUsernameInstructionData;
+use crate::pda::{USERNAME_PDA_SEED_PREFIX, USERNAME_PDA_SIZE};
+use crate::state::{self, UsernameClaim};
+use crate::validation;
+use solana_program::account_info::next_account_info;
+use solana_program::account_info::AccountInfo;
+use solana_program::entrypoint::ProgramResult;
+use solana_program::pubkey::Pubkey;
+
+use super::{close_pda_account, create_pda_account, invalid_profile_data, pack_profile};
+
+pub fn process_claim_username(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: ClaimUsernameInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner = next_account_info(account_info_iter)?;
+    validation::check_signer(owner)?;
+    validation::check_writable(owner)?;
+    let pda = next_account_info(account_info_iter)?;
+    validation::check_writable(pda)?;
+    let mut profile = validation::check_user_pda(program_id, owner.key, pda)?;
+    // The username is a seed of the username PDA, validate it first.
+    state::validate_username(&input.username).map_err(|_| invalid_profile_data(pda.key))?;
+    let username_pda = next_account_info(account_info_iter)?;
+    validation::check_writable(username_pda)?;
+    let bump_seed = validation::check_username_pda(program_id, &input.username, username_pda)?;
+    let current_username_pda = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    validation::check_system_program(system_program)?;
+    let config_pda = next_account_info(account_info_iter)?;
+    validation::check_not_paused(program_id, config_pda)?;
+
+    // Claiming the current username again only changes its case,
+    // or claims it for a profile created before the usernames were claimed.
+    match validation::check_username_claim(program_id, username_pda)? {
+        Some(claim) if claim.owner == *owner.key => {}
+        Some(_) => return Err(username_taken(username_pda.key).into()),
+        None => create_username_claim(
+            program_id,
+            owner,
+            pda.key,
+            username_pda,
+            system_program,
+            &input.username,
+            bump_seed,
+        )?,
+    }
+    if current_username_pda.key != username_pda.key {
+        validation::check_writable(current_username_pda)?;
+        release_username(program_id, owner, &profile.username, current_username_pda)?;
+    }
+
+    profile.version = state::USER_PROFILE_VERSION;
+    profile.username = input.username;
+    pack_profile(&profile, pda)?;
+
+    ProgramEvent::UsernameClaimed(UsernameClaimed {
+        owner: *owner.key,
+        username: profile.username,
+    })
+    .emit();
+    Ok(())
+}
+
+/// Creates the username PDA pointing to the user PDA of `owner`,
+/// `owner` pays for its rent.
+pub(crate) fn create_username_claim<'a>(
+    program_id: &Pubkey,
+    owner: &AccountInfo<'a>,
+    user_pda: &Pubkey,
+    username_pda: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    username: &str,
+    bump_seed: u8,
+) -> ProgramResult {
+    let normalized_username = state::normalize_username(username);
+    let pda_seed = &[
+        USERNAME_PDA_SEED_PREFIX,
+        normalized_username.as_bytes(),
+        &[bump_seed],
+    ];
+    create_pda_account(
+        program_id,
+        owner,
+        username_pda,
+        system_program,
+        USERNAME_PDA_SIZE,
+        pda_seed,
+    )?;
+
+    let claim = UsernameClaim::new(*owner.key, *user_pda, bump_seed);
+    claim
+        .pack(&mut username_pda.try_borrow_mut_data()?)
+        .map_err(|_| {
+            Error::new(ErrorCause::AccountDataTooSmall)
+                .with_account(username_pda.key)
+                .into()
+        })
+}
+
+/// Closes the username PDA of `username` if it's claimed by `owner`,
+/// the rent goes back to `owner`.
+/// Profiles created before the usernames were claimed have no claim to release.
+pub(crate) fn release_username(
+    program_id: &Pubkey,
+    owner: &AccountInfo,
+    username: &str,
+    username_pda: &AccountInfo,
+) -> ProgramResult {
+    validation::check_username_pda(program_id, username, username_pda)?;
+    match validation::check_username_claim(program_id, username_pda)? {
+        Some(claim) if claim.owner == *owner.key => close_pda_account(username_pda, owner),
+        _ => Ok(()),
+    }
+}
+
+/// Points the username PDA of `username` claimed by `owner` to the user PDA of `new_owner`.
+/// Profiles created before the usernames were claimed have no claim to transfer.
+pub(crate) fn transfer_username_claim(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    new_owner: &Pubkey,
+    new_user_pda: &Pubkey,
+    username: &str,
+    username_pda: &AccountInfo,
+) -> ProgramResult {
+    validation::check_username_pda(program_id, username, username_pda)?;
+    match validation::check_username_claim(program_id, username_pda)? {
+        Some(mut claim) if claim.owner == *owner => {
+            claim.owner = *new_owner;
+            claim.user_pda = *new_user_pda;
+            claim
+                .pack(&mut username_pda.try_borrow_mut_data()?)
+                .map_err(|_| {
+                    Error::new(ErrorCause::AccountDataTooSmall)
+                        .with_account(username_pda.key)
+                        .into()
+                })
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn username_taken(username_pda: &Pubkey) -> Error {
+    Error::new(ErrorCause::UsernameTaken).with_account(username_pda)
+}
@@ -0,0 +1,933 @@
+//! Checks of the accounts passed to the program instructions.
+//! Every failed check results in an [Error] with a specific [ErrorCause].
+
+use {
+    crate::{
+        error::{Error, ErrorCause},
+        pda,
+        state::{
+            BlockList, Connection, Endorsement, Escrow, Event, Group, Membership, Poll,
+            ProgramConfig, Recovery, Rsvp, Session, UserProfile, UsernameClaim, Vote,
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+pub fn check_signer(account: &AccountInfo) -> Result<(), Error> {
+    if !account.is_signer {
+        return Err(Error::new(ErrorCause::MissingRequiredSignature).with_account(account.key));
+    }
+    Ok(())
+}
+
+pub fn check_writable(account: &AccountInfo) -> Result<(), Error> {
+    if !account.is_writable {
+        return Err(Error::new(ErrorCause::AccountNotWritable).with_account(account.key));
+    }
+    Ok(())
+}
+
+/// Checks that the account exists and is owned by `owner`.
+pub fn check_owner(account: &AccountInfo, owner: &Pubkey) -> Result<(), Error> {
+    if account.lamports() == 0 && account.data_is_empty() {
+        return Err(Error::new(ErrorCause::AccountNotFound).with_account(account.key));
+    }
+    if account.owner != owner {
+        return Err(Error::new(ErrorCause::InvalidAccountOwner)
+            .with_account(account.key)
+            .with_message(format!("Owned by {}", account.owner)));
+    }
+    Ok(())
+}
+
+pub fn check_system_program(account: &AccountInfo) -> Result<(), Error> {
+    if !system_program::check_id(account.key) {
+        return Err(Error::new(ErrorCause::InvalidSystemProgram).with_account(account.key));
+    }
+    Ok(())
+}
+
+/// Checks that the account is the one stored in a PDA, e.g. a participant of an escrow.
+pub fn check_key(account: &AccountInfo, expected: &Pubkey) -> Result<(), Error> {
+    if account.key != expected {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(account.key)
+            .with_message(format!("Expected {}", expected)));
+    }
+    Ok(())
+}
+
+/// Checks that no program has taken over the account yet.
+pub fn check_uninitialized(account: &AccountInfo) -> Result<(), Error> {
+    if !system_program::check_id(account.owner) || !account.data_is_empty() {
+        return Err(Error::new(ErrorCause::AccountAlreadyInitialized).with_account(account.key));
+    }
+    Ok(())
+}
+
+/// Checks that `pda` is the canonical user PDA of `wallet`,
+/// derived with the given `bump_seed`.
+pub fn check_new_user_pda(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    pda: &AccountInfo,
+    bump_seed: u8,
+) -> Result<(), Error> {
+    let (expected_pda, expected_bump_seed) = pda::find_user_pda(program_id, wallet);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    if expected_bump_seed != bump_seed {
+        return Err(Error::new(ErrorCause::InvalidBumpSeed)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_bump_seed)));
+    }
+    Ok(())
+}
+
+/// Checks that `pda` is an initialized user PDA belonging to `wallet`
+/// and returns the profile stored in it.
+pub fn check_user_pda(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<UserProfile, Error> {
+    let profile = check_any_user_pda(program_id, pda)?;
+    if profile.owner != *wallet {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Not a PDA of {}", wallet)));
+    }
+    Ok(profile)
+}
+
+/// Checks that the admin hasn't flagged the user of the profile stored in `pda`.
+pub fn check_not_flagged(profile: &UserProfile, pda: &AccountInfo) -> Result<(), Error> {
+    if profile.flagged {
+        return Err(Error::new(ErrorCause::Flagged).with_account(pda.key));
+    }
+    Ok(())
+}
+
+/// Checks that `pda` is an initialized user PDA of any user
+/// and returns the profile stored in it.
+pub fn check_any_user_pda(program_id: &Pubkey, pda: &AccountInfo) -> Result<UserProfile, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let profile = UserProfile::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda = pda::create_user_pda(program_id, &profile.owner, profile.bump_seed);
+    if expected_pda != Ok(*pda.key) {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Not a PDA of {}", profile.owner)));
+    }
+    Ok(profile)
+}
+
+/// Checks that `pda` is the canonical connection PDA of the two users
+/// and returns its bump seed.
+pub fn check_new_connection_pda(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    other_user: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_connection_pda(program_id, user, other_user);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized connection PDA
+/// and returns the connection stored in it.
+pub fn check_connection_pda(program_id: &Pubkey, pda: &AccountInfo) -> Result<Connection, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let connection = Connection::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda = pda::create_connection_pda(
+        program_id,
+        &connection.user_a,
+        &connection.user_b,
+        connection.bump_seed,
+    );
+    if expected_pda != Ok(*pda.key) {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message("Not a connection PDA".to_string()));
+    }
+    Ok(connection)
+}
+
+/// Checks that `user` is one of the users of the connection stored in `pda`.
+pub fn check_connection_member(
+    connection: &Connection,
+    pda: &AccountInfo,
+    user: &Pubkey,
+) -> Result<(), Error> {
+    if !connection.is_member(user) {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(pda.key)
+            .with_message(format!("{} is not a member", user)));
+    }
+    Ok(())
+}
+
+/// Checks that `pda` is the canonical username PDA of `username`
+/// and returns its bump seed.
+pub fn check_username_pda(
+    program_id: &Pubkey,
+    username: &str,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_username_pda(program_id, username);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Returns the claim stored in the username PDA,
+/// or `None` if the username hasn't been claimed yet.
+pub fn check_username_claim(
+    program_id: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<Option<UsernameClaim>, Error> {
+    if pda.owner != program_id {
+        check_uninitialized(pda)?;
+        return Ok(None);
+    }
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let claim = UsernameClaim::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    Ok(Some(claim))
+}
+
+/// Checks that `pda` is the program config PDA and returns the config stored in it,
+/// or the default config if it hasn't been initialized yet.
+pub fn check_config_pda(program_id: &Pubkey, pda: &AccountInfo) -> Result<ProgramConfig, Error> {
+    let (expected_pda, _) = pda::find_config_pda(program_id);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    if pda.owner != program_id {
+        return Ok(ProgramConfig::default());
+    }
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    ProgramConfig::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))
+}
+
+/// Checks that the program isn't paused and returns its config.
+pub fn check_not_paused(
+    program_id: &Pubkey,
+    config_pda: &AccountInfo,
+) -> Result<ProgramConfig, Error> {
+    let config = check_config_pda(program_id, config_pda)?;
+    if config.paused {
+        return Err(Error::new(ErrorCause::ProgramPaused).with_account(config_pda.key));
+    }
+    Ok(config)
+}
+
+/// Checks that `admin` is the signing admin of the config stored in `config_pda`.
+pub fn check_admin(
+    config: &ProgramConfig,
+    config_pda: &AccountInfo,
+    admin: &AccountInfo,
+) -> Result<(), Error> {
+    check_signer(admin)?;
+    if config.admin != *admin.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(config_pda.key)
+            .with_message(format!("{} is not the admin", admin.key)));
+    }
+    Ok(())
+}
+
+/// Checks that `authority` signed and is the upgrade authority of the program,
+/// stored in the ProgramData account of the upgradeable loader.
+pub fn check_upgrade_authority(
+    program_id: &Pubkey,
+    program_data: &AccountInfo,
+    authority: &AccountInfo,
+) -> Result<(), Error> {
+    check_signer(authority)?;
+    let expected = bpf_loader_upgradeable::get_program_data_address(program_id);
+    if expected != *program_data.key {
+        return Err(Error::new(ErrorCause::InvalidPubKey)
+            .with_account(program_data.key)
+            .with_message(format!("Expected {}", expected)));
+    }
+    check_owner(program_data, &bpf_loader_upgradeable::id())?;
+
+    let data = program_data
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(program_data.key))?;
+    // Bincode encoded state: u32 variant, u64 slot, Option<Pubkey> of the upgrade authority.
+    let metadata = data
+        .get(..UpgradeableLoaderState::size_of_programdata_metadata())
+        .filter(|metadata| metadata[..4] == PROGRAM_DATA_VARIANT.to_le_bytes())
+        .ok_or_else(|| Error::new(ErrorCause::InvalidAccountData).with_account(program_data.key))?;
+    if metadata[12] != 1 || metadata[13..] != authority.key.as_ref()[..] {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(program_data.key)
+            .with_message(format!("{} is not the upgrade authority", authority.key)));
+    }
+    Ok(())
+}
+
+/// Index of [UpgradeableLoaderState::ProgramData] in the loader state enum.
+const PROGRAM_DATA_VARIANT: u32 = 3;
+
+pub fn check_attestation_authority(
+    config: &ProgramConfig,
+    config_pda: &AccountInfo,
+    authority: &AccountInfo,
+) -> Result<(), Error> {
+    check_signer(authority)?;
+    if config.attestation_authority != *authority.key {
+        return Err(Error::new(ErrorCause::Unauthorized)
+            .with_account(config_pda.key)
+            .with_message(format!(
+                "{} is not the attestation authority",
+                authority.key
+            )));
+    }
+    Ok(())
+}
+
+/// Checks that `pda` is the canonical session PDA of `owner` with `delegate`
+/// and returns its bump seed.
+pub fn check_new_session_pda(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_session_pda(program_id, owner, delegate);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized session PDA
+/// and returns the session stored in it.
+pub fn check_session_pda(program_id: &Pubkey, pda: &AccountInfo) -> Result<Session, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let session = Session::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda = pda::create_session_pda(
+        program_id,
+        &session.owner,
+        &session.delegate,
+        session.bump_seed,
+    );
+    if expected_pda != Ok(*pda.key) {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message("Not a session PDA".to_string()));
+    }
+    Ok(session)
+}
+
+/// Checks that `pda` is the canonical PDA of the endorsement of `endorsed` by `endorser`
+/// and returns its bump seed.
+pub fn check_new_endorsement_pda(
+    program_id: &Pubkey,
+    endorser: &Pubkey,
+    endorsed: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_endorsement_pda(program_id, endorser, endorsed);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized endorsement PDA
+/// and returns the endorsement stored in it.
+pub fn check_endorsement_pda(program_id: &Pubkey, pda: &AccountInfo) -> Result<Endorsement, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let endorsement = Endorsement::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda = pda::create_endorsement_pda(
+        program_id,
+        &endorsement.endorser,
+        &endorsement.endorsed,
+        endorsement.bump_seed,
+    );
+    if expected_pda != Ok(*pda.key) {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message("Not an endorsement PDA".to_string()));
+    }
+    Ok(endorsement)
+}
+
+/// Checks that `pda` is the canonical block list PDA of `owner` and returns its bump seed.
+pub fn check_new_block_list_pda(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_block_list_pda(program_id, owner);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is the initialized block list PDA of `owner`
+/// and returns the block list stored in it.
+pub fn check_block_list_pda(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<BlockList, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let block_list = BlockList::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda =
+        pda::create_block_list_pda(program_id, &block_list.owner, block_list.bump_seed);
+    if expected_pda != Ok(*pda.key) || block_list.owner != *owner {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Not the block list PDA of {}", owner)));
+    }
+    Ok(block_list)
+}
+
+/// Checks that `pda` is the canonical group PDA of `creator` and `group_id`
+/// and returns its bump seed.
+pub fn check_new_group_pda(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    group_id: u64,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_group_pda(program_id, creator, group_id);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized group PDA and returns the group stored in it.
+pub fn check_group_pda(program_id: &Pubkey, pda: &AccountInfo) -> Result<Group, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let group = Group::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda =
+        pda::create_group_pda(program_id, &group.creator, group.group_id, group.bump_seed);
+    if expected_pda != Ok(*pda.key) {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message("Not a group PDA".to_string()));
+    }
+    Ok(group)
+}
+
+/// Checks that `pda` is the canonical membership PDA of `member` in `group`
+/// and returns its bump seed.
+pub fn check_new_membership_pda(
+    program_id: &Pubkey,
+    group: &Pubkey,
+    member: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_membership_pda(program_id, group, member);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized membership PDA in `group`
+/// and returns the membership stored in it.
+pub fn check_membership_pda(
+    program_id: &Pubkey,
+    group: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<Membership, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let membership = Membership::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda = pda::create_membership_pda(
+        program_id,
+        &membership.group,
+        &membership.member,
+        membership.bump_seed,
+    );
+    if expected_pda != Ok(*pda.key) || membership.group != *group {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Not a membership PDA of group {}", group)));
+    }
+    Ok(membership)
+}
+
+/// Checks that `pda` is the canonical event PDA of `organizer` and `event_id`
+/// and returns its bump seed.
+pub fn check_new_event_pda(
+    program_id: &Pubkey,
+    organizer: &Pubkey,
+    event_id: u64,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_event_pda(program_id, organizer, event_id);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized event PDA and returns the event stored in it.
+pub fn check_event_pda(program_id: &Pubkey, pda: &AccountInfo) -> Result<Event, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let event = Event::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda = pda::create_event_pda(
+        program_id,
+        &event.organizer,
+        event.event_id,
+        event.bump_seed,
+    );
+    if expected_pda != Ok(*pda.key) {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message("Not an event PDA".to_string()));
+    }
+    Ok(event)
+}
+
+/// Checks that `pda` is the canonical RSVP PDA of `attendee` to `event`
+/// and returns its bump seed.
+pub fn check_new_rsvp_pda(
+    program_id: &Pubkey,
+    event: &Pubkey,
+    attendee: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_rsvp_pda(program_id, event, attendee);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized RSVP PDA to `event`
+/// and returns the RSVP stored in it.
+pub fn check_rsvp_pda(
+    program_id: &Pubkey,
+    event: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<Rsvp, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let rsvp = Rsvp::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda =
+        pda::create_rsvp_pda(program_id, &rsvp.event, &rsvp.attendee, rsvp.bump_seed);
+    if expected_pda != Ok(*pda.key) || rsvp.event != *event {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Not an RSVP PDA of event {}", event)));
+    }
+    Ok(rsvp)
+}
+
+/// Checks that `pda` is the canonical escrow PDA of the meeting
+/// of `proposer` and `partner` and returns its bump seed.
+pub fn check_new_escrow_pda(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    partner: &Pubkey,
+    meeting_id: u64,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_escrow_pda(program_id, proposer, partner, meeting_id);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized escrow PDA and returns the escrow stored in it.
+pub fn check_escrow_pda(program_id: &Pubkey, pda: &AccountInfo) -> Result<Escrow, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let escrow = Escrow::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda = pda::create_escrow_pda(
+        program_id,
+        &escrow.proposer,
+        &escrow.partner,
+        escrow.meeting_id,
+        escrow.bump_seed,
+    );
+    if expected_pda != Ok(*pda.key) {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message("Not an escrow PDA".to_string()));
+    }
+    Ok(escrow)
+}
+
+/// Checks that `pda` is the canonical recovery PDA of `user_pda` and returns its bump seed.
+pub fn check_new_recovery_pda(
+    program_id: &Pubkey,
+    user_pda: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_recovery_pda(program_id, user_pda);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is the recovery PDA of `user_pda` and no recovery is pending,
+/// it would apply to the next profile stored in `user_pda` otherwise.
+pub fn check_no_recovery(
+    program_id: &Pubkey,
+    user_pda: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<(), Error> {
+    check_new_recovery_pda(program_id, user_pda, pda)?;
+    if check_uninitialized(pda).is_err() {
+        return Err(Error::new(ErrorCause::InvalidState)
+            .with_account(pda.key)
+            .with_message("Cancel the pending recovery first".to_string()));
+    }
+    Ok(())
+}
+
+/// Checks that `pda` is an initialized recovery PDA of `user_pda`
+/// and returns the recovery stored in it.
+pub fn check_recovery_pda(
+    program_id: &Pubkey,
+    user_pda: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<Recovery, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let recovery = Recovery::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda = pda::create_recovery_pda(program_id, &recovery.user_pda, recovery.bump_seed);
+    if expected_pda != Ok(*pda.key) || recovery.user_pda != *user_pda {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Not a recovery PDA of {}", user_pda)));
+    }
+    Ok(recovery)
+}
+
+/// Checks that `pda` is the canonical referral PDA of the new `user` and returns its bump seed.
+pub fn check_new_referral_pda(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_referral_pda(program_id, user);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is the canonical poll PDA of `creator` and `poll_id`
+/// and returns its bump seed.
+pub fn check_new_poll_pda(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    poll_id: u64,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_poll_pda(program_id, creator, poll_id);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized poll PDA and returns the poll stored in it.
+pub fn check_poll_pda(program_id: &Pubkey, pda: &AccountInfo) -> Result<Poll, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let poll = Poll::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda =
+        pda::create_poll_pda(program_id, &poll.creator, poll.poll_id, poll.bump_seed);
+    if expected_pda != Ok(*pda.key) {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message("Not a poll PDA".to_string()));
+    }
+    Ok(poll)
+}
+
+/// Checks that `pda` is the canonical vote PDA of `voter` in `poll`
+/// and returns its bump seed.
+pub fn check_new_vote_pda(
+    program_id: &Pubkey,
+    poll: &Pubkey,
+    voter: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<u8, Error> {
+    let (expected_pda, bump_seed) = pda::find_vote_pda(program_id, poll, voter);
+    if expected_pda != *pda.key {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Expected {}", expected_pda)));
+    }
+    Ok(bump_seed)
+}
+
+/// Checks that `pda` is an initialized vote PDA in `poll`
+/// and returns the vote stored in it.
+pub fn check_vote_pda(
+    program_id: &Pubkey,
+    poll: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<Vote, Error> {
+    check_owner(pda, program_id)?;
+
+    let data = pda
+        .try_borrow_data()
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+    let vote = Vote::unpack(&data)
+        .map_err(|_| Error::new(ErrorCause::InvalidAccountData).with_account(pda.key))?;
+
+    let expected_pda = pda::create_vote_pda(program_id, &vote.poll, &vote.voter, vote.bump_seed);
+    if expected_pda != Ok(*pda.key) || vote.poll != *poll {
+        return Err(Error::new(ErrorCause::InvalidPda)
+            .with_account(pda.key)
+            .with_message(format!("Not a vote PDA of poll {}", poll)));
+    }
+    Ok(vote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_account_flags() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &system_program::ID,
+            false,
+            0,
+        );
+
+        assert_eq!(
+            &ErrorCause::MissingRequiredSignature,
+            check_signer(&account).unwrap_err().cause()
+        );
+        assert_eq!(
+            &ErrorCause::AccountNotWritable,
+            check_writable(&account).unwrap_err().cause()
+        );
+        assert_eq!(
+            &ErrorCause::InvalidSystemProgram,
+            check_system_program(&account).unwrap_err().cause()
+        );
+        assert!(check_uninitialized(&account).is_ok());
+    }
+
+    #[test]
+    fn check_account_owner() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &system_program::ID,
+            false,
+            0,
+        );
+        assert_eq!(
+            &ErrorCause::AccountNotFound,
+            check_owner(&account, &program_id).unwrap_err().cause()
+        );
+
+        let mut lamports = 100;
+        let mut data = vec![0; 8];
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &system_program::ID,
+            false,
+            0,
+        );
+        assert_eq!(
+            &ErrorCause::InvalidAccountOwner,
+            check_owner(&account, &program_id).unwrap_err().cause()
+        );
+        assert_eq!(
+            &ErrorCause::AccountAlreadyInitialized,
+            check_uninitialized(&account).unwrap_err().cause()
+        );
+    }
+
+    #[test]
+    fn check_user_pda_derivation() {
+        let program_id = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let (pda_key, bump_seed) = pda::find_user_pda(&program_id, &wallet);
+
+        let mut lamports = 100;
+        let mut data = vec![0; 1024];
+        UserProfile::new(wallet, bump_seed, "paulinka".to_string(), 0)
+            .pack(&mut data)
+            .unwrap();
+        let pda = AccountInfo::new(
+            &pda_key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+            0,
+        );
+
+        assert!(check_new_user_pda(&program_id, &wallet, &pda, bump_seed).is_ok());
+        assert_eq!(
+            &ErrorCause::InvalidBumpSeed,
+            check_new_user_pda(&program_id, &wallet, &pda, bump_seed.wrapping_sub(1))
+                .unwrap_err()
+                .cause()
+        );
+        assert_eq!(
+            &ErrorCause::InvalidPda,
+            check_new_user_pda(&program_id, &Pubkey::new_unique(), &pda, bump_seed)
+                .unwrap_err()
+                .cause()
+        );
+
+        let profile = check_user_pda(&program_id, &wallet, &pda).unwrap();
+        assert_eq!(wallet, profile.owner);
+        assert_eq!(
+            &ErrorCause::InvalidPda,
+            check_user_pda(&program_id, &Pubkey::new_unique(), &pda)
+                .unwrap_err()
+                .cause()
+        );
+    }
+}
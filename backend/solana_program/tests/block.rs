@@ -0,0 +1,255 @@
+//! Tests of the block list.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction,
+        pda::{find_block_list_pda, BLOCK_LIST_PDA_SIZE},
+        state::{BlockList, MAX_BLOCKED_USERS},
+    },
+    common::*,
+    solana_program::pubkey::Pubkey,
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+async fn block_list(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+) -> BlockList {
+    let (pda, _) = find_block_list_pda(program_id, owner);
+    let account = get_account(context, &pda).await.unwrap();
+    BlockList::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn block_and_unblock() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+    let harasser = Pubkey::new_unique();
+    let other_harasser = Pubkey::new_unique();
+
+    let instructions = [
+        instruction::block(&program_id, &wallet.pubkey(), &harasser),
+        instruction::block(&program_id, &wallet.pubkey(), &other_harasser),
+    ];
+    process(&mut context, &instructions, &[&wallet])
+        .await
+        .unwrap();
+
+    let (pda, bump_seed) = find_block_list_pda(&program_id, &wallet.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(rent_lamports_for(BLOCK_LIST_PDA_SIZE), account.lamports);
+    let blocks = BlockList::unpack(&account.data).unwrap();
+    assert_eq!(wallet.pubkey(), blocks.owner);
+    assert_eq!(bump_seed, blocks.bump_seed);
+    assert_eq!(vec![harasser, other_harasser], blocks.blocked);
+
+    let instruction = instruction::unblock(&program_id, &wallet.pubkey(), &harasser);
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    let blocks = block_list(&mut context, &program_id, &wallet.pubkey()).await;
+    assert_eq!(vec![other_harasser], blocks.blocked);
+}
+
+#[tokio::test]
+async fn block_twice() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+    let harasser = Pubkey::new_unique();
+
+    let instruction = instruction::block(&program_id, &wallet.pubkey(), &harasser);
+    process(&mut context, std::slice::from_ref(&instruction), &[&wallet])
+        .await
+        .unwrap();
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn block_yourself() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    let instruction = instruction::block(&program_id, &wallet.pubkey(), &wallet.pubkey());
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn block_list_full() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    let instructions: Vec<_> = (0..MAX_BLOCKED_USERS)
+        .map(|_| instruction::block(&program_id, &wallet.pubkey(), &Pubkey::new_unique()))
+        .collect();
+    for chunk in instructions.chunks(10) {
+        process(&mut context, chunk, &[&wallet]).await.unwrap();
+    }
+
+    let instruction = instruction::block(&program_id, &wallet.pubkey(), &Pubkey::new_unique());
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn unblock_not_blocked() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    // No block list yet
+    let instruction = instruction::unblock(&program_id, &wallet.pubkey(), &Pubkey::new_unique());
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+
+    let instruction = instruction::block(&program_id, &wallet.pubkey(), &Pubkey::new_unique());
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    let instruction = instruction::unblock(&program_id, &wallet.pubkey(), &Pubkey::new_unique());
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn blocked_user_cant_request_connection() {
+    let wallet = Keypair::new();
+    let harasser = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet, &harasser]).await;
+    initialize_users(&mut context, &program_id, &[&wallet, &harasser]).await;
+
+    let instruction = instruction::block(&program_id, &wallet.pubkey(), &harasser.pubkey());
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    let instruction =
+        instruction::request_connection(&program_id, &harasser.pubkey(), &wallet.pubkey());
+    let err = process(&mut context, &[instruction], &[&harasser])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Blocked), err);
+
+    // Blocking works both ways.
+    let instruction =
+        instruction::request_connection(&program_id, &wallet.pubkey(), &harasser.pubkey());
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Blocked), err);
+
+    let instructions = [
+        instruction::unblock(&program_id, &wallet.pubkey(), &harasser.pubkey()),
+        instruction::request_connection(&program_id, &harasser.pubkey(), &wallet.pubkey()),
+    ];
+    process(&mut context, &instructions, &[&wallet, &harasser])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn blocked_requester_cant_be_accepted() {
+    let wallet = Keypair::new();
+    let requester = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet, &requester]).await;
+    initialize_users(&mut context, &program_id, &[&wallet, &requester]).await;
+
+    let instructions = [
+        instruction::request_connection(&program_id, &requester.pubkey(), &wallet.pubkey()),
+        instruction::block(&program_id, &wallet.pubkey(), &requester.pubkey()),
+    ];
+    process(&mut context, &instructions, &[&wallet, &requester])
+        .await
+        .unwrap();
+
+    let instruction =
+        instruction::accept_connection(&program_id, &wallet.pubkey(), &requester.pubkey());
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Blocked), err);
+
+    // Rejecting a blocked user is still possible.
+    let instruction =
+        instruction::reject_connection(&program_id, &wallet.pubkey(), &requester.pubkey());
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn fake_block_list() {
+    let wallet = Keypair::new();
+    let recipient = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet, &recipient]).await;
+    initialize_users(&mut context, &program_id, &[&wallet, &recipient]).await;
+
+    // The block list of another user can't be passed in place of the recipient's.
+    let mut instruction =
+        instruction::request_connection(&program_id, &wallet.pubkey(), &recipient.pubkey());
+    instruction.accounts[6].pubkey = find_block_list_pda(&program_id, &Pubkey::new_unique()).0;
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPda), err);
+}
+
+#[tokio::test]
+async fn blocks_move_with_the_profile() {
+    let wallet = Keypair::new();
+    let new_wallet = Keypair::new();
+    let harasser = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet, &new_wallet, &harasser]).await;
+    initialize_users(&mut context, &program_id, &[&wallet, &harasser]).await;
+    let instruction = instruction::block(&program_id, &wallet.pubkey(), &harasser.pubkey());
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    let instruction = instruction::transfer_ownership(
+        &program_id,
+        &wallet.pubkey(),
+        &new_wallet.pubkey(),
+        &username(&wallet.pubkey()),
+    );
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    let (pda, _) = find_block_list_pda(&program_id, &wallet.pubkey());
+    assert!(get_account(&mut context, &pda).await.is_none());
+    let (new_pda, bump_seed) = find_block_list_pda(&program_id, &new_wallet.pubkey());
+    let account = get_account(&mut context, &new_pda).await.unwrap();
+    assert_eq!(rent_lamports_for(BLOCK_LIST_PDA_SIZE), account.lamports);
+    let blocks = BlockList::unpack(&account.data).unwrap();
+    assert_eq!(new_wallet.pubkey(), blocks.owner);
+    assert_eq!(bump_seed, blocks.bump_seed);
+    assert_eq!(vec![harasser.pubkey()], blocks.blocked);
+
+    let instruction =
+        instruction::request_connection(&program_id, &harasser.pubkey(), &new_wallet.pubkey());
+    let err = process(&mut context, &[instruction], &[&harasser])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Blocked), err);
+}
@@ -0,0 +1,164 @@
+//! Helpers shared by the program tests, running the program in-process with `solana-program-test`.
+#![allow(dead_code)]
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction,
+        pda::{find_group_pda, USERNAME_PDA_SIZE, USER_PDA_SIZE},
+        processor::process_instruction,
+    },
+    solana_program::{
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        instruction::{Instruction, InstructionError},
+        pubkey::Pubkey,
+        rent::Rent,
+        system_program,
+    },
+    solana_program_test::{processor, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+pub const WALLET_LAMPORTS: u64 = 1_000_000_000;
+
+pub async fn setup(wallets: &[&Keypair]) -> (ProgramTestContext, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "anti_loneliness_solana_program",
+        program_id,
+        processor!(process_instruction),
+    );
+    for wallet in wallets {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(WALLET_LAMPORTS, 0, &system_program::ID),
+        );
+    }
+    (program_test.start_with_context().await, program_id)
+}
+
+/// Stores the ProgramData account the upgradeable loader keeps for a deployed program,
+/// making `authority` the upgrade authority who can initialize the config.
+pub fn set_upgrade_authority(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    authority: &Pubkey,
+) {
+    let state = UpgradeableLoaderState::ProgramData {
+        slot: 0,
+        upgrade_authority_address: Some(*authority),
+    };
+    let account = Account::new_data(
+        rent_lamports_for(UpgradeableLoaderState::size_of_programdata_metadata() as u64),
+        &state,
+        &bpf_loader_upgradeable::id(),
+    )
+    .unwrap();
+    context.set_account(
+        &bpf_loader_upgradeable::get_program_data_address(program_id),
+        &AccountSharedData::from(account),
+    );
+}
+
+pub fn rent_lamports() -> u64 {
+    rent_lamports_for(USER_PDA_SIZE)
+}
+
+pub fn rent_lamports_for(size: u64) -> u64 {
+    Rent::default().minimum_balance(size as usize)
+}
+
+pub fn username_rent_lamports() -> u64 {
+    rent_lamports_for(USERNAME_PDA_SIZE)
+}
+
+/// Username unique for the wallet, usernames can't be shared.
+pub fn username(wallet: &Pubkey) -> String {
+    format!("user_{}", &wallet.to_string()[..8])
+}
+
+pub fn initialize(program_id: &Pubkey, wallet: &Pubkey) -> Instruction {
+    instruction::initialize(
+        program_id,
+        wallet,
+        rent_lamports() + username_rent_lamports(),
+        username(wallet),
+    )
+}
+
+/// Creates the user PDAs of the wallets.
+pub async fn initialize_users(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    wallets: &[&Keypair],
+) {
+    for wallet in wallets {
+        process(
+            context,
+            &[initialize(program_id, &wallet.pubkey())],
+            &[wallet],
+        )
+        .await
+        .unwrap();
+    }
+}
+
+pub async fn create_group(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    admin: &Keypair,
+    invite_only: bool,
+    max_members: u32,
+) -> Pubkey {
+    let instruction = instruction::create_group(
+        program_id,
+        &admin.pubkey(),
+        7,
+        "Hiking in Berlin".to_string(),
+        invite_only,
+        max_members,
+    );
+    process(context, &[instruction], &[admin]).await.unwrap();
+    find_group_pda(program_id, &admin.pubkey(), 7).0
+}
+
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    // A new blockhash makes repeated transactions distinct.
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+pub async fn get_account(context: &mut ProgramTestContext, pubkey: &Pubkey) -> Option<Account> {
+    context.banks_client.get_account(*pubkey).await.unwrap()
+}
+
+/// Lamports of the account, zero once it is closed.
+pub async fn balance(context: &mut ProgramTestContext, pubkey: &Pubkey) -> u64 {
+    get_account(context, pubkey)
+        .await
+        .map_or(0, |account| account.lamports)
+}
+
+pub fn program_error(cause: ErrorCause) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(cause.into()))
+}
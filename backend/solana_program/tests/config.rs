@@ -0,0 +1,535 @@
+//! Tests of the program config.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction::{self, ConfigInstructionData},
+        pda::{find_config_pda, find_user_pda, CONFIG_PDA_SIZE, MAX_USER_PDA_SIZE, USER_PDA_SIZE},
+        state::{
+            ProgramConfig, UserProfile, VERIFIED_ALL, VERIFIED_IN_PERSON, VERIFIED_MODERATOR,
+            VERIFIED_ONBOARDING,
+        },
+    },
+    common::*,
+    solana_program::pubkey::Pubkey,
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+const REGISTRATION_FEE: u64 = 5000;
+const DEPOSIT: u64 = 1_000_000;
+
+fn config_data(paused: bool) -> ConfigInstructionData {
+    ConfigInstructionData {
+        paused,
+        max_user_pda_size: 2 * USER_PDA_SIZE,
+        registration_fee: REGISTRATION_FEE,
+        deposit: 0,
+        attestation_authority: Pubkey::default(),
+        referral_reward: 0,
+    }
+}
+
+// Initializes the config with a deposit and registers the user.
+async fn initialize_with_deposit(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    admin: &Keypair,
+    wallet: &Keypair,
+) {
+    set_upgrade_authority(context, program_id, &admin.pubkey());
+    let mut config = config_data(false);
+    config.deposit = DEPOSIT;
+    let instruction = instruction::initialize_config(program_id, &admin.pubkey(), config);
+    process(context, &[instruction], &[admin]).await.unwrap();
+
+    let instruction = instruction::initialize(
+        program_id,
+        &wallet.pubkey(),
+        rent_lamports() + username_rent_lamports() + REGISTRATION_FEE + DEPOSIT,
+        username(&wallet.pubkey()),
+    );
+    process(context, &[instruction], &[wallet]).await.unwrap();
+}
+
+async fn initialize_config(context: &mut ProgramTestContext, program_id: &Pubkey, admin: &Keypair) {
+    set_upgrade_authority(context, program_id, &admin.pubkey());
+    let instruction =
+        instruction::initialize_config(program_id, &admin.pubkey(), config_data(false));
+    process(context, &[instruction], &[admin]).await.unwrap();
+}
+
+#[tokio::test]
+async fn initialize_config_once() {
+    let admin = Keypair::new();
+    let other = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &other]).await;
+    initialize_config(&mut context, &program_id, &admin).await;
+
+    let (pda, bump_seed) = find_config_pda(&program_id);
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(program_id, account.owner);
+    assert_eq!(rent_lamports_for(CONFIG_PDA_SIZE), account.lamports);
+    assert_eq!(
+        ProgramConfig::new(
+            admin.pubkey(),
+            bump_seed,
+            false,
+            2 * USER_PDA_SIZE,
+            REGISTRATION_FEE,
+            0,
+            Pubkey::default(),
+            0
+        ),
+        ProgramConfig::unpack(&account.data).unwrap()
+    );
+
+    // Nobody can take over the config afterwards.
+    let instruction =
+        instruction::initialize_config(&program_id, &other.pubkey(), config_data(false));
+    let err = process(&mut context, &[instruction], &[&other])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+}
+
+#[tokio::test]
+async fn initialize_config_upgrade_authority_only() {
+    let admin = Keypair::new();
+    let other = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &other]).await;
+
+    // A program without the ProgramData account isn't deployed by the upgradeable loader.
+    let instruction =
+        instruction::initialize_config(&program_id, &admin.pubkey(), config_data(false));
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+
+    set_upgrade_authority(&mut context, &program_id, &admin.pubkey());
+    let instruction =
+        instruction::initialize_config(&program_id, &other.pubkey(), config_data(false));
+    let err = process(&mut context, &[instruction], &[&other])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    // Another account passed as the ProgramData.
+    let mut instruction =
+        instruction::initialize_config(&program_id, &admin.pubkey(), config_data(false));
+    instruction.accounts[3].pubkey = Pubkey::new_unique();
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPubKey), err);
+
+    initialize_config(&mut context, &program_id, &admin).await;
+}
+
+#[tokio::test]
+async fn initialize_config_invalid_size() {
+    let admin = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin]).await;
+    set_upgrade_authority(&mut context, &program_id, &admin.pubkey());
+
+    let mut config = config_data(false);
+    config.max_user_pda_size = MAX_USER_PDA_SIZE + 1;
+    let instruction = instruction::initialize_config(&program_id, &admin.pubkey(), config);
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidAccountSize), err);
+}
+
+#[tokio::test]
+async fn registration_fee() {
+    let admin = Keypair::new();
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &wallet]).await;
+    initialize_config(&mut context, &program_id, &admin).await;
+
+    // The offered lamports don't cover the fee.
+    let err = process(
+        &mut context,
+        &[initialize(&program_id, &wallet.pubkey())],
+        &[&wallet],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InsufficientRent), err);
+
+    let instruction = instruction::initialize(
+        &program_id,
+        &wallet.pubkey(),
+        rent_lamports() + username_rent_lamports() + REGISTRATION_FEE,
+        username(&wallet.pubkey()),
+    );
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    let (pda, _) = find_config_pda(&program_id);
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(
+        rent_lamports_for(CONFIG_PDA_SIZE) + REGISTRATION_FEE,
+        account.lamports
+    );
+    let wallet_lamports = context
+        .banks_client
+        .get_balance(wallet.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports() - REGISTRATION_FEE,
+        wallet_lamports
+    );
+}
+
+#[tokio::test]
+async fn registration_deposit() {
+    let admin = Keypair::new();
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &wallet]).await;
+    set_upgrade_authority(&mut context, &program_id, &admin.pubkey());
+    let mut config = config_data(false);
+    config.deposit = DEPOSIT;
+    let instruction = instruction::initialize_config(&program_id, &admin.pubkey(), config);
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+
+    // The offered lamports don't cover the deposit.
+    let instruction = instruction::initialize(
+        &program_id,
+        &wallet.pubkey(),
+        rent_lamports() + username_rent_lamports() + REGISTRATION_FEE,
+        username(&wallet.pubkey()),
+    );
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InsufficientRent), err);
+
+    let instruction = instruction::initialize(
+        &program_id,
+        &wallet.pubkey(),
+        rent_lamports() + username_rent_lamports() + REGISTRATION_FEE + DEPOSIT,
+        username(&wallet.pubkey()),
+    );
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    let (pda, _) = find_user_pda(&program_id, &wallet.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(rent_lamports() + DEPOSIT, account.lamports);
+    assert_eq!(DEPOSIT, UserProfile::unpack(&account.data).unwrap().deposit);
+
+    // Resizing keeps the deposit in the PDA.
+    let instruction = instruction::resize(&program_id, &wallet.pubkey(), 2 * USER_PDA_SIZE);
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    assert_eq!(
+        rent_lamports_for(2 * USER_PDA_SIZE) + DEPOSIT,
+        balance(&mut context, &pda).await
+    );
+
+    // Closing the account returns the deposit.
+    let instruction =
+        instruction::close_account(&program_id, &wallet.pubkey(), &username(&wallet.pubkey()));
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - REGISTRATION_FEE,
+        balance(&mut context, &wallet.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn slash_deposit() {
+    let admin = Keypair::new();
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &wallet]).await;
+    initialize_with_deposit(&mut context, &program_id, &admin, &wallet).await;
+
+    // Only the admin can slash.
+    let instruction = instruction::slash_deposit(&program_id, &wallet.pubkey(), &wallet.pubkey());
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    let instruction = instruction::slash_deposit(&program_id, &admin.pubkey(), &wallet.pubkey());
+    process(&mut context, std::slice::from_ref(&instruction), &[&admin])
+        .await
+        .unwrap();
+    let (config_pda, _) = find_config_pda(&program_id);
+    assert_eq!(
+        rent_lamports_for(CONFIG_PDA_SIZE) + REGISTRATION_FEE + DEPOSIT,
+        balance(&mut context, &config_pda).await
+    );
+    let (pda, _) = find_user_pda(&program_id, &wallet.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(rent_lamports(), account.lamports);
+    assert_eq!(0, UserProfile::unpack(&account.data).unwrap().deposit);
+
+    // Nothing left to slash.
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    // Closing the account returns the rent only.
+    let instruction =
+        instruction::close_account(&program_id, &wallet.pubkey(), &username(&wallet.pubkey()));
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - REGISTRATION_FEE - DEPOSIT,
+        balance(&mut context, &wallet.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn withdraw() {
+    let admin = Keypair::new();
+    let wallet = Keypair::new();
+    let recipient = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &wallet, &recipient]).await;
+    initialize_with_deposit(&mut context, &program_id, &admin, &wallet).await;
+    let instruction = instruction::slash_deposit(&program_id, &admin.pubkey(), &wallet.pubkey());
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+    let collected = REGISTRATION_FEE + DEPOSIT;
+
+    // Only the admin can withdraw.
+    let instruction =
+        instruction::withdraw(&program_id, &wallet.pubkey(), &wallet.pubkey(), collected);
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    // The config PDA stays rent-exempt.
+    let instruction = instruction::withdraw(
+        &program_id,
+        &admin.pubkey(),
+        &recipient.pubkey(),
+        collected + 1,
+    );
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InsufficientRent), err);
+    let instruction = instruction::withdraw(&program_id, &admin.pubkey(), &recipient.pubkey(), 0);
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+
+    let instruction =
+        instruction::withdraw(&program_id, &admin.pubkey(), &recipient.pubkey(), collected);
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+    let (config_pda, _) = find_config_pda(&program_id);
+    assert_eq!(
+        rent_lamports_for(CONFIG_PDA_SIZE),
+        balance(&mut context, &config_pda).await
+    );
+    assert_eq!(
+        WALLET_LAMPORTS + collected,
+        balance(&mut context, &recipient.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn max_user_pda_size() {
+    let admin = Keypair::new();
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+    initialize_config(&mut context, &program_id, &admin).await;
+
+    let instruction = instruction::resize(&program_id, &wallet.pubkey(), 3 * USER_PDA_SIZE);
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidAccountSize), err);
+
+    let instruction = instruction::resize(&program_id, &wallet.pubkey(), 2 * USER_PDA_SIZE);
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn pause_and_resume() {
+    let admin = Keypair::new();
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+    initialize_config(&mut context, &program_id, &admin).await;
+
+    let instruction = instruction::update_config(&program_id, &admin.pubkey(), config_data(true));
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+
+    let instruction =
+        instruction::close_account(&program_id, &wallet.pubkey(), &username(&wallet.pubkey()));
+    let err = process(&mut context, std::slice::from_ref(&instruction), &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::ProgramPaused), err);
+
+    // The admin can resume the program while it's paused.
+    let update = instruction::update_config(&program_id, &admin.pubkey(), config_data(false));
+    process(&mut context, &[update], &[&admin]).await.unwrap();
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    let (pda, _) = find_user_pda(&program_id, &wallet.pubkey());
+    assert!(get_account(&mut context, &pda).await.is_none());
+}
+
+#[tokio::test]
+async fn update_config_not_admin() {
+    let admin = Keypair::new();
+    let attacker = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &attacker]).await;
+    initialize_config(&mut context, &program_id, &admin).await;
+
+    let instruction =
+        instruction::update_config(&program_id, &attacker.pubkey(), config_data(true));
+    let err = process(&mut context, &[instruction], &[&attacker])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+}
+
+#[tokio::test]
+async fn transfer_authority() {
+    let admin = Keypair::new();
+    let new_admin = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &new_admin]).await;
+    initialize_config(&mut context, &program_id, &admin).await;
+
+    let instruction =
+        instruction::transfer_authority(&program_id, &admin.pubkey(), &new_admin.pubkey());
+    process(&mut context, &[instruction], &[&admin, &new_admin])
+        .await
+        .unwrap();
+
+    let (pda, _) = find_config_pda(&program_id);
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(
+        new_admin.pubkey(),
+        ProgramConfig::unpack(&account.data).unwrap().admin
+    );
+
+    // The previous admin has no authority anymore.
+    let instruction = instruction::update_config(&program_id, &admin.pubkey(), config_data(true));
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+    let instruction =
+        instruction::update_config(&program_id, &new_admin.pubkey(), config_data(true));
+    process(&mut context, &[instruction], &[&new_admin])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn attest_verifications() {
+    let admin = Keypair::new();
+    let authority = Keypair::new();
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &authority, &wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    // Nobody can attest until the authority is configured.
+    let instruction = instruction::attest(
+        &program_id,
+        &authority.pubkey(),
+        &wallet.pubkey(),
+        VERIFIED_ONBOARDING,
+        true,
+    );
+    let err = process(&mut context, &[instruction], &[&authority])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+
+    set_upgrade_authority(&mut context, &program_id, &admin.pubkey());
+    let mut config = config_data(false);
+    config.attestation_authority = authority.pubkey();
+    let instruction = instruction::initialize_config(&program_id, &admin.pubkey(), config);
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+
+    // Neither the user nor the admin can attest.
+    for signer in [&wallet, &admin] {
+        let instruction = instruction::attest(
+            &program_id,
+            &signer.pubkey(),
+            &wallet.pubkey(),
+            VERIFIED_MODERATOR,
+            true,
+        );
+        let err = process(&mut context, &[instruction], &[signer])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::Unauthorized), err);
+    }
+
+    let (pda, _) = find_user_pda(&program_id, &wallet.pubkey());
+    for (flags, verified, expected) in [
+        (
+            VERIFIED_ONBOARDING | VERIFIED_IN_PERSON,
+            true,
+            VERIFIED_ONBOARDING | VERIFIED_IN_PERSON,
+        ),
+        (VERIFIED_MODERATOR, true, VERIFIED_ALL),
+        (
+            VERIFIED_IN_PERSON | VERIFIED_MODERATOR,
+            false,
+            VERIFIED_ONBOARDING,
+        ),
+    ] {
+        let instruction = instruction::attest(
+            &program_id,
+            &authority.pubkey(),
+            &wallet.pubkey(),
+            flags,
+            verified,
+        );
+        process(&mut context, &[instruction], &[&authority])
+            .await
+            .unwrap();
+        let account = get_account(&mut context, &pda).await.unwrap();
+        let profile = UserProfile::unpack(&account.data).unwrap();
+        assert_eq!(expected, profile.verifications);
+    }
+
+    // Flags unknown to the program are rejected.
+    let instruction = instruction::attest(
+        &program_id,
+        &authority.pubkey(),
+        &wallet.pubkey(),
+        1 << 10,
+        true,
+    );
+    let err = process(&mut context, &[instruction], &[&authority])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+}
@@ -0,0 +1,243 @@
+//! Tests of the connection instructions.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction,
+        pda::{find_connection_pda, CONNECTION_PDA_SIZE},
+        state::{Connection, ConnectionStatus},
+    },
+    common::*,
+    solana_program::pubkey::Pubkey,
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+async fn get_connection(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    user: &Pubkey,
+    other_user: &Pubkey,
+) -> Option<Connection> {
+    let (pda, _) = find_connection_pda(program_id, user, other_user);
+    get_account(context, &pda)
+        .await
+        .map(|account| Connection::unpack(&account.data).unwrap())
+}
+
+#[tokio::test]
+async fn request_and_accept() {
+    let requester = Keypair::new();
+    let recipient = Keypair::new();
+    let (mut context, program_id) = setup(&[&requester, &recipient]).await;
+    initialize_users(&mut context, &program_id, &[&requester, &recipient]).await;
+
+    let instruction =
+        instruction::request_connection(&program_id, &requester.pubkey(), &recipient.pubkey());
+    process(&mut context, &[instruction], &[&requester])
+        .await
+        .unwrap();
+
+    // The same PDA is found whatever the order of the users.
+    let connection = get_connection(
+        &mut context,
+        &program_id,
+        &recipient.pubkey(),
+        &requester.pubkey(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(ConnectionStatus::Pending, connection.status);
+    assert_eq!(requester.pubkey(), connection.requester);
+    assert!(connection.is_member(&recipient.pubkey()));
+    let requester_lamports = context
+        .banks_client
+        .get_balance(requester.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS
+            - rent_lamports()
+            - username_rent_lamports()
+            - rent_lamports_for(CONNECTION_PDA_SIZE),
+        requester_lamports
+    );
+
+    let instruction =
+        instruction::accept_connection(&program_id, &recipient.pubkey(), &requester.pubkey());
+    process(&mut context, &[instruction], &[&recipient])
+        .await
+        .unwrap();
+
+    let connection = get_connection(
+        &mut context,
+        &program_id,
+        &requester.pubkey(),
+        &recipient.pubkey(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(ConnectionStatus::Accepted, connection.status);
+
+    // Can't be accepted or rejected again.
+    let instruction =
+        instruction::reject_connection(&program_id, &recipient.pubkey(), &requester.pubkey());
+    let err = process(&mut context, &[instruction], &[&recipient])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn request_twice() {
+    let requester = Keypair::new();
+    let recipient = Keypair::new();
+    let (mut context, program_id) = setup(&[&requester, &recipient]).await;
+    initialize_users(&mut context, &program_id, &[&requester, &recipient]).await;
+
+    let instruction =
+        instruction::request_connection(&program_id, &requester.pubkey(), &recipient.pubkey());
+    process(&mut context, &[instruction], &[&requester])
+        .await
+        .unwrap();
+
+    // Neither the requester nor the recipient can request it again.
+    let instruction =
+        instruction::request_connection(&program_id, &recipient.pubkey(), &requester.pubkey());
+    let err = process(&mut context, &[instruction], &[&recipient])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+}
+
+#[tokio::test]
+async fn request_unregistered_user() {
+    let requester = Keypair::new();
+    let recipient = Keypair::new();
+    let (mut context, program_id) = setup(&[&requester, &recipient]).await;
+    initialize_users(&mut context, &program_id, &[&requester]).await;
+
+    let instruction =
+        instruction::request_connection(&program_id, &requester.pubkey(), &recipient.pubkey());
+    let err = process(&mut context, &[instruction], &[&requester])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+}
+
+#[tokio::test]
+async fn request_self() {
+    let requester = Keypair::new();
+    let (mut context, program_id) = setup(&[&requester]).await;
+    initialize_users(&mut context, &program_id, &[&requester]).await;
+
+    let instruction =
+        instruction::request_connection(&program_id, &requester.pubkey(), &requester.pubkey());
+    let err = process(&mut context, &[instruction], &[&requester])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn respond_unauthorized() {
+    let requester = Keypair::new();
+    let recipient = Keypair::new();
+    let stranger = Keypair::new();
+    let (mut context, program_id) = setup(&[&requester, &recipient, &stranger]).await;
+    initialize_users(&mut context, &program_id, &[&requester, &recipient]).await;
+
+    let instruction =
+        instruction::request_connection(&program_id, &requester.pubkey(), &recipient.pubkey());
+    process(&mut context, &[instruction], &[&requester])
+        .await
+        .unwrap();
+
+    // The requester can't accept their own request.
+    let instruction =
+        instruction::accept_connection(&program_id, &requester.pubkey(), &recipient.pubkey());
+    let err = process(&mut context, &[instruction], &[&requester])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    // Neither can someone else.
+    let mut instruction =
+        instruction::accept_connection(&program_id, &stranger.pubkey(), &requester.pubkey());
+    instruction.accounts[1].pubkey =
+        find_connection_pda(&program_id, &requester.pubkey(), &recipient.pubkey()).0;
+    let err = process(&mut context, &[instruction], &[&stranger])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+}
+
+#[tokio::test]
+async fn reject_and_remove() {
+    let requester = Keypair::new();
+    let recipient = Keypair::new();
+    let (mut context, program_id) = setup(&[&requester, &recipient]).await;
+    initialize_users(&mut context, &program_id, &[&requester, &recipient]).await;
+
+    let instruction =
+        instruction::request_connection(&program_id, &requester.pubkey(), &recipient.pubkey());
+    process(&mut context, &[instruction], &[&requester])
+        .await
+        .unwrap();
+    let instruction =
+        instruction::reject_connection(&program_id, &recipient.pubkey(), &requester.pubkey());
+    process(&mut context, &[instruction], &[&recipient])
+        .await
+        .unwrap();
+    let connection = get_connection(
+        &mut context,
+        &program_id,
+        &requester.pubkey(),
+        &recipient.pubkey(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(ConnectionStatus::Rejected, connection.status);
+
+    // The rent must go back to the requester.
+    let instruction = instruction::remove_connection(
+        &program_id,
+        &recipient.pubkey(),
+        &requester.pubkey(),
+        &recipient.pubkey(),
+    );
+    let err = process(&mut context, &[instruction], &[&recipient])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPubKey), err);
+
+    let instruction = instruction::remove_connection(
+        &program_id,
+        &recipient.pubkey(),
+        &requester.pubkey(),
+        &requester.pubkey(),
+    );
+    process(&mut context, &[instruction], &[&recipient])
+        .await
+        .unwrap();
+
+    assert!(get_connection(
+        &mut context,
+        &program_id,
+        &requester.pubkey(),
+        &recipient.pubkey()
+    )
+    .await
+    .is_none());
+    let requester_lamports = context
+        .banks_client
+        .get_balance(requester.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        requester_lamports
+    );
+}
@@ -0,0 +1,220 @@
+//! Tests of the endorsements.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction,
+        pda::{find_endorsement_pda, find_user_pda, ENDORSEMENT_PDA_SIZE},
+        state::{Endorsement, UserProfile},
+    },
+    common::*,
+    solana_program::pubkey::Pubkey,
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+async fn endorsement_count(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+) -> u64 {
+    let (pda, _) = find_user_pda(program_id, wallet);
+    let account = get_account(context, &pda).await.unwrap();
+    UserProfile::unpack(&account.data)
+        .unwrap()
+        .endorsement_count
+}
+
+#[tokio::test]
+async fn endorse_increments_count() {
+    let endorser = Keypair::new();
+    let other_endorser = Keypair::new();
+    let endorsed = Keypair::new();
+    let (mut context, program_id) = setup(&[&endorser, &other_endorser, &endorsed]).await;
+    initialize_users(
+        &mut context,
+        &program_id,
+        &[&endorser, &other_endorser, &endorsed],
+    )
+    .await;
+
+    let instruction = instruction::endorse(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    process(&mut context, &[instruction], &[&endorser])
+        .await
+        .unwrap();
+
+    let (pda, bump_seed) =
+        find_endorsement_pda(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(rent_lamports_for(ENDORSEMENT_PDA_SIZE), account.lamports);
+    let endorsement = Endorsement::unpack(&account.data).unwrap();
+    assert_eq!(endorser.pubkey(), endorsement.endorser);
+    assert_eq!(endorsed.pubkey(), endorsement.endorsed);
+    assert_eq!(bump_seed, endorsement.bump_seed);
+    assert_eq!(
+        1,
+        endorsement_count(&mut context, &program_id, &endorsed.pubkey()).await
+    );
+
+    let instruction =
+        instruction::endorse(&program_id, &other_endorser.pubkey(), &endorsed.pubkey());
+    process(&mut context, &[instruction], &[&other_endorser])
+        .await
+        .unwrap();
+    assert_eq!(
+        2,
+        endorsement_count(&mut context, &program_id, &endorsed.pubkey()).await
+    );
+    // Endorsing is one-way.
+    assert_eq!(
+        0,
+        endorsement_count(&mut context, &program_id, &endorser.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn endorse_twice() {
+    let endorser = Keypair::new();
+    let endorsed = Keypair::new();
+    let (mut context, program_id) = setup(&[&endorser, &endorsed]).await;
+    initialize_users(&mut context, &program_id, &[&endorser, &endorsed]).await;
+
+    let instruction = instruction::endorse(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    process(
+        &mut context,
+        std::slice::from_ref(&instruction),
+        &[&endorser],
+    )
+    .await
+    .unwrap();
+    let err = process(&mut context, &[instruction], &[&endorser])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+    assert_eq!(
+        1,
+        endorsement_count(&mut context, &program_id, &endorsed.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn endorse_yourself() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    let instruction = instruction::endorse(&program_id, &wallet.pubkey(), &wallet.pubkey());
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn endorse_by_unregistered_user() {
+    let endorser = Keypair::new();
+    let endorsed = Keypair::new();
+    let (mut context, program_id) = setup(&[&endorser, &endorsed]).await;
+    initialize_users(&mut context, &program_id, &[&endorsed]).await;
+
+    let instruction = instruction::endorse(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    let err = process(&mut context, &[instruction], &[&endorser])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+}
+
+#[tokio::test]
+async fn revoke_endorsement() {
+    let endorser = Keypair::new();
+    let endorsed = Keypair::new();
+    let (mut context, program_id) = setup(&[&endorser, &endorsed]).await;
+    initialize_users(&mut context, &program_id, &[&endorser, &endorsed]).await;
+    let endorse = instruction::endorse(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    process(&mut context, std::slice::from_ref(&endorse), &[&endorser])
+        .await
+        .unwrap();
+
+    let instruction =
+        instruction::revoke_endorsement(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    process(&mut context, &[instruction], &[&endorser])
+        .await
+        .unwrap();
+
+    let (pda, _) = find_endorsement_pda(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    assert!(get_account(&mut context, &pda).await.is_none());
+    assert_eq!(
+        0,
+        endorsement_count(&mut context, &program_id, &endorsed.pubkey()).await
+    );
+    let endorser_lamports = context
+        .banks_client
+        .get_balance(endorser.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        endorser_lamports
+    );
+
+    // The endorsement can be given again.
+    process(&mut context, &[endorse], &[&endorser])
+        .await
+        .unwrap();
+    assert_eq!(
+        1,
+        endorsement_count(&mut context, &program_id, &endorsed.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn revoke_endorsement_not_endorser() {
+    let endorser = Keypair::new();
+    let endorsed = Keypair::new();
+    let attacker = Keypair::new();
+    let (mut context, program_id) = setup(&[&endorser, &endorsed, &attacker]).await;
+    initialize_users(&mut context, &program_id, &[&endorser, &endorsed]).await;
+    let instruction = instruction::endorse(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    process(&mut context, &[instruction], &[&endorser])
+        .await
+        .unwrap();
+
+    let mut instruction =
+        instruction::revoke_endorsement(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    instruction.accounts[0].pubkey = attacker.pubkey();
+    let err = process(&mut context, &[instruction], &[&attacker])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+}
+
+#[tokio::test]
+async fn revoke_endorsement_of_closed_account() {
+    let endorser = Keypair::new();
+    let endorsed = Keypair::new();
+    let (mut context, program_id) = setup(&[&endorser, &endorsed]).await;
+    initialize_users(&mut context, &program_id, &[&endorser, &endorsed]).await;
+    let instruction = instruction::endorse(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    process(&mut context, &[instruction], &[&endorser])
+        .await
+        .unwrap();
+    let instruction = instruction::close_account(
+        &program_id,
+        &endorsed.pubkey(),
+        &username(&endorsed.pubkey()),
+    );
+    process(&mut context, &[instruction], &[&endorsed])
+        .await
+        .unwrap();
+
+    // The endorser can still get the rent back.
+    let instruction =
+        instruction::revoke_endorsement(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    process(&mut context, &[instruction], &[&endorser])
+        .await
+        .unwrap();
+    let (pda, _) = find_endorsement_pda(&program_id, &endorser.pubkey(), &endorsed.pubkey());
+    assert!(get_account(&mut context, &pda).await.is_none());
+}
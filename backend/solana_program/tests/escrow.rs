@@ -0,0 +1,537 @@
+//! Tests of the meetup escrow, its check-ins and the settlement of the stakes.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction::{self, ConfigInstructionData},
+        pda::{find_escrow_pda, find_user_pda, ESCROW_PDA_SIZE, USER_PDA_SIZE},
+        state::Escrow,
+    },
+    common::*,
+    solana_program::{pubkey::Pubkey, system_instruction},
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+const STAKE: u64 = 1_000_000;
+const DEADLINE_SLOT: u64 = 1000;
+
+// Balance of a registered user who didn't stake anything.
+fn registered_balance() -> u64 {
+    WALLET_LAMPORTS - rent_lamports() - username_rent_lamports()
+}
+
+async fn create_escrow(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    proposer: &Keypair,
+    partner: &Keypair,
+    charity: Option<&Pubkey>,
+) -> Pubkey {
+    let instruction = instruction::create_escrow(
+        program_id,
+        &proposer.pubkey(),
+        &partner.pubkey(),
+        7,
+        STAKE,
+        DEADLINE_SLOT,
+        charity,
+    );
+    process(context, &[instruction], &[proposer]).await.unwrap();
+    find_escrow_pda(program_id, &proposer.pubkey(), &partner.pubkey(), 7).0
+}
+
+// Creates the escrow which the partner joins.
+async fn create_joined_escrow(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    proposer: &Keypair,
+    partner: &Keypair,
+    charity: Option<&Pubkey>,
+) -> Pubkey {
+    let escrow = create_escrow(context, program_id, proposer, partner, charity).await;
+    let instruction = instruction::join_escrow(program_id, &partner.pubkey(), &escrow);
+    process(context, &[instruction], &[partner]).await.unwrap();
+    escrow
+}
+
+async fn check_in(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    participant: &Keypair,
+    escrow: &Pubkey,
+) {
+    let instruction = instruction::check_in(program_id, &participant.pubkey(), escrow);
+    process(context, &[instruction], &[participant])
+        .await
+        .unwrap();
+}
+
+async fn settle(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    escrow: &Pubkey,
+    proposer: &Keypair,
+    partner: &Keypair,
+    charity: Option<&Pubkey>,
+) {
+    let instruction = instruction::settle_escrow(
+        program_id,
+        escrow,
+        &proposer.pubkey(),
+        &partner.pubkey(),
+        charity,
+    );
+    process(context, &[instruction], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn create_and_join_escrow() {
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let (mut context, program_id) = setup(&[&proposer, &partner]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+
+    let escrow = create_escrow(&mut context, &program_id, &proposer, &partner, None).await;
+
+    let account = get_account(&mut context, &escrow).await.unwrap();
+    assert_eq!(rent_lamports_for(ESCROW_PDA_SIZE) + STAKE, account.lamports);
+    let state = Escrow::unpack(&account.data).unwrap();
+    assert_eq!(proposer.pubkey(), state.proposer);
+    assert_eq!(partner.pubkey(), state.partner);
+    assert_eq!(STAKE, state.stake);
+    assert_eq!(DEADLINE_SLOT, state.deadline_slot);
+    assert!(!state.partner_staked);
+
+    let instruction = instruction::join_escrow(&program_id, &partner.pubkey(), &escrow);
+    process(
+        &mut context,
+        std::slice::from_ref(&instruction),
+        &[&partner],
+    )
+    .await
+    .unwrap();
+    let account = get_account(&mut context, &escrow).await.unwrap();
+    assert_eq!(
+        rent_lamports_for(ESCROW_PDA_SIZE) + 2 * STAKE,
+        account.lamports
+    );
+    assert!(Escrow::unpack(&account.data).unwrap().partner_staked);
+    assert_eq!(
+        registered_balance() - STAKE,
+        balance(&mut context, &partner.pubkey()).await
+    );
+
+    // The stake is locked once.
+    let err = process(&mut context, &[instruction], &[&partner])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn create_escrow_invalid() {
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let (mut context, program_id) = setup(&[&proposer, &partner]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+
+    for (stake, deadline_slot) in [(0, DEADLINE_SLOT), (STAKE, 0)] {
+        let instruction = instruction::create_escrow(
+            &program_id,
+            &proposer.pubkey(),
+            &partner.pubkey(),
+            1,
+            stake,
+            deadline_slot,
+            None,
+        );
+        let err = process(&mut context, &[instruction], &[&proposer])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+    }
+
+    // No meetings with oneself.
+    let instruction = instruction::create_escrow(
+        &program_id,
+        &proposer.pubkey(),
+        &proposer.pubkey(),
+        1,
+        STAKE,
+        DEADLINE_SLOT,
+        None,
+    );
+    let err = process(&mut context, &[instruction], &[&proposer])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPubKey), err);
+}
+
+#[tokio::test]
+async fn create_escrow_invalid_charity() {
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let (mut context, program_id) = setup(&[&proposer, &partner]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+
+    // A no-show can't get the stake back as the charity.
+    // An account without the rent exemption couldn't receive the stake,
+    // a program or a PDA couldn't spend it.
+    let unfunded = Pubkey::new_unique();
+    for (charity, cause) in [
+        (proposer.pubkey(), ErrorCause::InvalidPubKey),
+        (partner.pubkey(), ErrorCause::InvalidPubKey),
+        (unfunded, ErrorCause::InsufficientRent),
+        (program_id, ErrorCause::InvalidAccountOwner),
+        (
+            find_user_pda(&program_id, &partner.pubkey()).0,
+            ErrorCause::InvalidAccountOwner,
+        ),
+    ] {
+        let instruction = instruction::create_escrow(
+            &program_id,
+            &proposer.pubkey(),
+            &partner.pubkey(),
+            1,
+            STAKE,
+            DEADLINE_SLOT,
+            Some(&charity),
+        );
+        let err = process(&mut context, &[instruction], &[&proposer])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(cause), err);
+    }
+
+    // The charity account has to match the instruction data.
+    let mut instruction = instruction::create_escrow(
+        &program_id,
+        &proposer.pubkey(),
+        &partner.pubkey(),
+        1,
+        STAKE,
+        DEADLINE_SLOT,
+        Some(&unfunded),
+    );
+    instruction.accounts[7].pubkey = Pubkey::new_unique();
+    let err = process(&mut context, &[instruction], &[&proposer])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPubKey), err);
+}
+
+#[tokio::test]
+async fn only_participants_take_part() {
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let stranger = Keypair::new();
+    let (mut context, program_id) = setup(&[&proposer, &partner, &stranger]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+    let escrow = create_escrow(&mut context, &program_id, &proposer, &partner, None).await;
+
+    let instruction = instruction::join_escrow(&program_id, &stranger.pubkey(), &escrow);
+    let err = process(&mut context, &[instruction], &[&stranger])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    // Checking in needs both stakes.
+    let instruction = instruction::check_in(&program_id, &proposer.pubkey(), &escrow);
+    let err = process(&mut context, &[instruction], &[&proposer])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    let instruction = instruction::join_escrow(&program_id, &partner.pubkey(), &escrow);
+    process(&mut context, &[instruction], &[&partner])
+        .await
+        .unwrap();
+    let instruction = instruction::check_in(&program_id, &stranger.pubkey(), &escrow);
+    let err = process(&mut context, &[instruction], &[&stranger])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+}
+
+#[tokio::test]
+async fn settle_met_escrow() {
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let (mut context, program_id) = setup(&[&proposer, &partner]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+    let escrow = create_joined_escrow(&mut context, &program_id, &proposer, &partner, None).await;
+
+    check_in(&mut context, &program_id, &proposer, &escrow).await;
+    // Settling waits for the other check-in or the deadline.
+    let instruction = instruction::settle_escrow(
+        &program_id,
+        &escrow,
+        &proposer.pubkey(),
+        &partner.pubkey(),
+        None,
+    );
+    let err = process(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    check_in(&mut context, &program_id, &partner, &escrow).await;
+    let instruction = instruction::check_in(&program_id, &partner.pubkey(), &escrow);
+    let err = process(&mut context, &[instruction], &[&partner])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    settle(
+        &mut context,
+        &program_id,
+        &escrow,
+        &proposer,
+        &partner,
+        None,
+    )
+    .await;
+
+    // Both stakes and the rent are back.
+    assert!(get_account(&mut context, &escrow).await.is_none());
+    assert_eq!(
+        registered_balance(),
+        balance(&mut context, &proposer.pubkey()).await
+    );
+    assert_eq!(
+        registered_balance(),
+        balance(&mut context, &partner.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn forfeit_to_partner() {
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let (mut context, program_id) = setup(&[&proposer, &partner]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+    let escrow = create_joined_escrow(&mut context, &program_id, &proposer, &partner, None).await;
+    check_in(&mut context, &program_id, &partner, &escrow).await;
+
+    context.warp_to_slot(DEADLINE_SLOT + 1).unwrap();
+    let instruction = instruction::check_in(&program_id, &proposer.pubkey(), &escrow);
+    let err = process(&mut context, &[instruction], &[&proposer])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    settle(
+        &mut context,
+        &program_id,
+        &escrow,
+        &proposer,
+        &partner,
+        None,
+    )
+    .await;
+
+    assert!(get_account(&mut context, &escrow).await.is_none());
+    assert_eq!(
+        registered_balance() - STAKE,
+        balance(&mut context, &proposer.pubkey()).await
+    );
+    assert_eq!(
+        registered_balance() + STAKE,
+        balance(&mut context, &partner.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn forfeit_to_charity() {
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let charity = Keypair::new();
+    let (mut context, program_id) = setup(&[&proposer, &partner, &charity]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+    let escrow = create_joined_escrow(
+        &mut context,
+        &program_id,
+        &proposer,
+        &partner,
+        Some(&charity.pubkey()),
+    )
+    .await;
+    check_in(&mut context, &program_id, &proposer, &escrow).await;
+    context.warp_to_slot(DEADLINE_SLOT + 1).unwrap();
+
+    // The charity has to match the escrow.
+    let instruction = instruction::settle_escrow(
+        &program_id,
+        &escrow,
+        &proposer.pubkey(),
+        &partner.pubkey(),
+        Some(&Pubkey::new_unique()),
+    );
+    let err = process(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPubKey), err);
+
+    settle(
+        &mut context,
+        &program_id,
+        &escrow,
+        &proposer,
+        &partner,
+        Some(&charity.pubkey()),
+    )
+    .await;
+
+    assert_eq!(
+        WALLET_LAMPORTS + STAKE,
+        balance(&mut context, &charity.pubkey()).await
+    );
+    assert_eq!(
+        registered_balance(),
+        balance(&mut context, &proposer.pubkey()).await
+    );
+    assert_eq!(
+        registered_balance() - STAKE,
+        balance(&mut context, &partner.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn forfeit_to_partner_when_charity_drained() {
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let charity = Keypair::new();
+    let (mut context, program_id) = setup(&[&proposer, &partner, &charity]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+    // Too small to make the drained charity rent exempt.
+    let stake = rent_lamports_for(0) / 2;
+    let instruction = instruction::create_escrow(
+        &program_id,
+        &proposer.pubkey(),
+        &partner.pubkey(),
+        7,
+        stake,
+        DEADLINE_SLOT,
+        Some(&charity.pubkey()),
+    );
+    process(&mut context, &[instruction], &[&proposer])
+        .await
+        .unwrap();
+    let (escrow, _) = find_escrow_pda(&program_id, &proposer.pubkey(), &partner.pubkey(), 7);
+    let instruction = instruction::join_escrow(&program_id, &partner.pubkey(), &escrow);
+    process(&mut context, &[instruction], &[&partner])
+        .await
+        .unwrap();
+    check_in(&mut context, &program_id, &proposer, &escrow).await;
+
+    let instruction =
+        system_instruction::transfer(&charity.pubkey(), &context.payer.pubkey(), WALLET_LAMPORTS);
+    process(&mut context, &[instruction], &[&charity])
+        .await
+        .unwrap();
+    context.warp_to_slot(DEADLINE_SLOT + 1).unwrap();
+    settle(
+        &mut context,
+        &program_id,
+        &escrow,
+        &proposer,
+        &partner,
+        Some(&charity.pubkey()),
+    )
+    .await;
+
+    assert!(get_account(&mut context, &escrow).await.is_none());
+    assert_eq!(0, balance(&mut context, &charity.pubkey()).await);
+    assert_eq!(
+        registered_balance() + stake,
+        balance(&mut context, &proposer.pubkey()).await
+    );
+    assert_eq!(
+        registered_balance() - stake,
+        balance(&mut context, &partner.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn refund_when_partner_never_joined() {
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let (mut context, program_id) = setup(&[&proposer, &partner]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+    let escrow = create_escrow(&mut context, &program_id, &proposer, &partner, None).await;
+
+    context.warp_to_slot(DEADLINE_SLOT + 1).unwrap();
+    let instruction = instruction::join_escrow(&program_id, &partner.pubkey(), &escrow);
+    let err = process(&mut context, &[instruction], &[&partner])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    settle(
+        &mut context,
+        &program_id,
+        &escrow,
+        &proposer,
+        &partner,
+        None,
+    )
+    .await;
+
+    assert!(get_account(&mut context, &escrow).await.is_none());
+    assert_eq!(
+        registered_balance(),
+        balance(&mut context, &proposer.pubkey()).await
+    );
+    assert_eq!(
+        registered_balance(),
+        balance(&mut context, &partner.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn settle_while_paused() {
+    let admin = Keypair::new();
+    let proposer = Keypair::new();
+    let partner = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &proposer, &partner]).await;
+    initialize_users(&mut context, &program_id, &[&proposer, &partner]).await;
+    let escrow = create_joined_escrow(&mut context, &program_id, &proposer, &partner, None).await;
+    check_in(&mut context, &program_id, &proposer, &escrow).await;
+    check_in(&mut context, &program_id, &partner, &escrow).await;
+
+    set_upgrade_authority(&mut context, &program_id, &admin.pubkey());
+    let instruction = instruction::initialize_config(
+        &program_id,
+        &admin.pubkey(),
+        ConfigInstructionData {
+            paused: true,
+            max_user_pda_size: USER_PDA_SIZE,
+            registration_fee: 0,
+            deposit: 0,
+            attestation_authority: admin.pubkey(),
+            referral_reward: 0,
+        },
+    );
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+
+    // The stakes don't stay locked while the program is paused.
+    settle(
+        &mut context,
+        &program_id,
+        &escrow,
+        &proposer,
+        &partner,
+        None,
+    )
+    .await;
+    assert!(get_account(&mut context, &escrow).await.is_none());
+    assert_eq!(
+        registered_balance(),
+        balance(&mut context, &partner.pubkey()).await
+    );
+}
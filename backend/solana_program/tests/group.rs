@@ -0,0 +1,210 @@
+//! Tests of the groups and their members.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction,
+        pda::{find_membership_pda, GROUP_PDA_SIZE, MEMBERSHIP_PDA_SIZE},
+        state::{Group, Membership},
+    },
+    common::*,
+    solana_program::pubkey::Pubkey,
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+async fn get_group(context: &mut ProgramTestContext, group: &Pubkey) -> Group {
+    let account = get_account(context, group).await.unwrap();
+    Group::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn create_group_with_admin() {
+    let admin = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin]).await;
+    initialize_users(&mut context, &program_id, &[&admin]).await;
+
+    let group = create_group(&mut context, &program_id, &admin, false, 10).await;
+
+    let account = get_account(&mut context, &group).await.unwrap();
+    assert_eq!(rent_lamports_for(GROUP_PDA_SIZE), account.lamports);
+    let state = Group::unpack(&account.data).unwrap();
+    assert_eq!(admin.pubkey(), state.admin);
+    assert_eq!(7, state.group_id);
+    assert_eq!("Hiking in Berlin", state.name);
+    assert_eq!(10, state.max_members);
+    assert_eq!(1, state.member_count);
+
+    // The admin is the first member.
+    let (pda, bump_seed) = find_membership_pda(&program_id, &group, &admin.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(rent_lamports_for(MEMBERSHIP_PDA_SIZE), account.lamports);
+    let membership = Membership::unpack(&account.data).unwrap();
+    assert_eq!(group, membership.group);
+    assert_eq!(admin.pubkey(), membership.member);
+    assert_eq!(bump_seed, membership.bump_seed);
+}
+
+#[tokio::test]
+async fn create_group_invalid() {
+    let admin = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin]).await;
+    initialize_users(&mut context, &program_id, &[&admin]).await;
+
+    for (name, max_members) in [
+        (" ".to_string(), 10),
+        ("x".repeat(33), 10),
+        ("Hiking".to_string(), 0),
+    ] {
+        let instruction =
+            instruction::create_group(&program_id, &admin.pubkey(), 1, name, false, max_members);
+        let err = process(&mut context, &[instruction], &[&admin])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+    }
+}
+
+#[tokio::test]
+async fn join_and_leave_open_group() {
+    let admin = Keypair::new();
+    let member = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &member]).await;
+    initialize_users(&mut context, &program_id, &[&admin, &member]).await;
+    let group = create_group(&mut context, &program_id, &admin, false, 10).await;
+
+    let instruction = instruction::join_group(&program_id, &member.pubkey(), &group, None);
+    process(&mut context, &[instruction], &[&member])
+        .await
+        .unwrap();
+    assert_eq!(2, get_group(&mut context, &group).await.member_count);
+    let (membership, _) = find_membership_pda(&program_id, &group, &member.pubkey());
+    assert!(get_account(&mut context, &membership).await.is_some());
+
+    let instruction = instruction::leave_group(&program_id, &member.pubkey(), &group);
+    process(&mut context, &[instruction], &[&member])
+        .await
+        .unwrap();
+    assert_eq!(1, get_group(&mut context, &group).await.member_count);
+    assert!(get_account(&mut context, &membership).await.is_none());
+    // The rent of the membership went back to the member.
+    let account = get_account(&mut context, &member.pubkey()).await.unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        account.lamports
+    );
+}
+
+#[tokio::test]
+async fn join_invite_only_group() {
+    let admin = Keypair::new();
+    let member = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &member]).await;
+    initialize_users(&mut context, &program_id, &[&admin, &member]).await;
+    let group = create_group(&mut context, &program_id, &admin, true, 10).await;
+
+    let instruction = instruction::join_group(&program_id, &member.pubkey(), &group, None);
+    let err = process(&mut context, &[instruction], &[&member])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    let instruction =
+        instruction::join_group(&program_id, &member.pubkey(), &group, Some(&admin.pubkey()));
+    process(&mut context, &[instruction], &[&member, &admin])
+        .await
+        .unwrap();
+    assert_eq!(2, get_group(&mut context, &group).await.member_count);
+}
+
+#[tokio::test]
+async fn join_full_group() {
+    let admin = Keypair::new();
+    let member = Keypair::new();
+    let other_member = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &member, &other_member]).await;
+    initialize_users(&mut context, &program_id, &[&admin, &member, &other_member]).await;
+    let group = create_group(&mut context, &program_id, &admin, false, 2).await;
+
+    let instruction = instruction::join_group(&program_id, &member.pubkey(), &group, None);
+    process(&mut context, &[instruction], &[&member])
+        .await
+        .unwrap();
+    let instruction = instruction::join_group(&program_id, &other_member.pubkey(), &group, None);
+    let err = process(&mut context, &[instruction], &[&other_member])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn join_twice() {
+    let admin = Keypair::new();
+    let member = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &member]).await;
+    initialize_users(&mut context, &program_id, &[&admin, &member]).await;
+    let group = create_group(&mut context, &program_id, &admin, false, 10).await;
+
+    let instruction = instruction::join_group(&program_id, &member.pubkey(), &group, None);
+    process(&mut context, std::slice::from_ref(&instruction), &[&member])
+        .await
+        .unwrap();
+    let err = process(&mut context, &[instruction], &[&member])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+    assert_eq!(2, get_group(&mut context, &group).await.member_count);
+}
+
+#[tokio::test]
+async fn kick_member() {
+    let admin = Keypair::new();
+    let member = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &member]).await;
+    initialize_users(&mut context, &program_id, &[&admin, &member]).await;
+    let group = create_group(&mut context, &program_id, &admin, false, 10).await;
+    let instruction = instruction::join_group(&program_id, &member.pubkey(), &group, None);
+    process(&mut context, &[instruction], &[&member])
+        .await
+        .unwrap();
+
+    // Only the admin can kick.
+    let instruction =
+        instruction::kick_member(&program_id, &member.pubkey(), &group, &member.pubkey());
+    let err = process(&mut context, &[instruction], &[&member])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    let instruction =
+        instruction::kick_member(&program_id, &admin.pubkey(), &group, &member.pubkey());
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+    assert_eq!(1, get_group(&mut context, &group).await.member_count);
+    let (membership, _) = find_membership_pda(&program_id, &group, &member.pubkey());
+    assert!(get_account(&mut context, &membership).await.is_none());
+}
+
+#[tokio::test]
+async fn admin_cant_leave() {
+    let admin = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin]).await;
+    initialize_users(&mut context, &program_id, &[&admin]).await;
+    let group = create_group(&mut context, &program_id, &admin, false, 10).await;
+
+    let instruction = instruction::leave_group(&program_id, &admin.pubkey(), &group);
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    let instruction =
+        instruction::kick_member(&program_id, &admin.pubkey(), &group, &admin.pubkey());
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
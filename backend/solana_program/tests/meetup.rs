@@ -0,0 +1,251 @@
+//! Tests of the meetup events and their RSVPs.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction,
+        pda::{find_event_pda, find_rsvp_pda, EVENT_PDA_SIZE, RSVP_PDA_SIZE},
+        state::{Event, Rsvp},
+    },
+    common::*,
+    solana_program::{clock::Clock, pubkey::Pubkey},
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+const HOUR: i64 = 3600;
+
+async fn now(context: &mut ProgramTestContext) -> i64 {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp
+}
+
+async fn create_event(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    organizer: &Keypair,
+    capacity: u32,
+) -> Pubkey {
+    let starts_at = now(context).await + HOUR;
+    let instruction = instruction::create_event(
+        program_id,
+        &organizer.pubkey(),
+        7,
+        "Picnic".to_string(),
+        starts_at,
+        "Tempelhofer Feld".to_string(),
+        capacity,
+    );
+    process(context, &[instruction], &[organizer])
+        .await
+        .unwrap();
+    find_event_pda(program_id, &organizer.pubkey(), 7).0
+}
+
+async fn get_event(context: &mut ProgramTestContext, event: &Pubkey) -> Event {
+    let account = get_account(context, event).await.unwrap();
+    Event::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn create_event_by_organizer() {
+    let organizer = Keypair::new();
+    let (mut context, program_id) = setup(&[&organizer]).await;
+    initialize_users(&mut context, &program_id, &[&organizer]).await;
+
+    let starts_at = now(&mut context).await + HOUR;
+    let event = create_event(&mut context, &program_id, &organizer, 12).await;
+
+    let (_, bump_seed) = find_event_pda(&program_id, &organizer.pubkey(), 7);
+    let account = get_account(&mut context, &event).await.unwrap();
+    assert_eq!(rent_lamports_for(EVENT_PDA_SIZE), account.lamports);
+    let state = Event::unpack(&account.data).unwrap();
+    assert_eq!(organizer.pubkey(), state.organizer);
+    assert_eq!(bump_seed, state.bump_seed);
+    assert_eq!("Picnic", state.title);
+    assert_eq!(starts_at, state.starts_at);
+    assert_eq!("Tempelhofer Feld", state.location_hint);
+    assert_eq!(12, state.capacity);
+    assert_eq!(0, state.attendee_count);
+    assert!(!state.closed);
+}
+
+#[tokio::test]
+async fn create_event_invalid() {
+    let organizer = Keypair::new();
+    let (mut context, program_id) = setup(&[&organizer]).await;
+    initialize_users(&mut context, &program_id, &[&organizer]).await;
+
+    let now = now(&mut context).await;
+    for (title, starts_at, capacity) in [
+        (" ".to_string(), now + HOUR, 10),
+        ("Picnic".to_string(), now + HOUR, 0),
+        // Already started
+        ("Picnic".to_string(), now, 10),
+    ] {
+        let instruction = instruction::create_event(
+            &program_id,
+            &organizer.pubkey(),
+            1,
+            title,
+            starts_at,
+            String::new(),
+            capacity,
+        );
+        let err = process(&mut context, &[instruction], &[&organizer])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+    }
+}
+
+#[tokio::test]
+async fn rsvp_and_cancel() {
+    let organizer = Keypair::new();
+    let attendee = Keypair::new();
+    let (mut context, program_id) = setup(&[&organizer, &attendee]).await;
+    initialize_users(&mut context, &program_id, &[&organizer, &attendee]).await;
+    let event = create_event(&mut context, &program_id, &organizer, 12).await;
+
+    let instruction = instruction::rsvp(&program_id, &attendee.pubkey(), &event);
+    process(&mut context, &[instruction], &[&attendee])
+        .await
+        .unwrap();
+    assert_eq!(1, get_event(&mut context, &event).await.attendee_count);
+    let (pda, bump_seed) = find_rsvp_pda(&program_id, &event, &attendee.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(rent_lamports_for(RSVP_PDA_SIZE), account.lamports);
+    let rsvp = Rsvp::unpack(&account.data).unwrap();
+    assert_eq!(event, rsvp.event);
+    assert_eq!(attendee.pubkey(), rsvp.attendee);
+    assert_eq!(bump_seed, rsvp.bump_seed);
+
+    let instruction = instruction::cancel_rsvp(&program_id, &attendee.pubkey(), &event);
+    process(&mut context, &[instruction], &[&attendee])
+        .await
+        .unwrap();
+    assert_eq!(0, get_event(&mut context, &event).await.attendee_count);
+    assert!(get_account(&mut context, &pda).await.is_none());
+    // The rent of the RSVP went back to the attendee.
+    let account = get_account(&mut context, &attendee.pubkey()).await.unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        account.lamports
+    );
+}
+
+#[tokio::test]
+async fn rsvp_full_event() {
+    let organizer = Keypair::new();
+    let attendee = Keypair::new();
+    let other_attendee = Keypair::new();
+    let (mut context, program_id) = setup(&[&organizer, &attendee, &other_attendee]).await;
+    initialize_users(
+        &mut context,
+        &program_id,
+        &[&organizer, &attendee, &other_attendee],
+    )
+    .await;
+    let event = create_event(&mut context, &program_id, &organizer, 1).await;
+
+    let instruction = instruction::rsvp(&program_id, &attendee.pubkey(), &event);
+    process(&mut context, &[instruction], &[&attendee])
+        .await
+        .unwrap();
+    let instruction = instruction::rsvp(&program_id, &other_attendee.pubkey(), &event);
+    let err = process(&mut context, &[instruction], &[&other_attendee])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+    assert_eq!(1, get_event(&mut context, &event).await.attendee_count);
+}
+
+#[tokio::test]
+async fn rsvp_twice() {
+    let organizer = Keypair::new();
+    let attendee = Keypair::new();
+    let (mut context, program_id) = setup(&[&organizer, &attendee]).await;
+    initialize_users(&mut context, &program_id, &[&organizer, &attendee]).await;
+    let event = create_event(&mut context, &program_id, &organizer, 12).await;
+
+    let instruction = instruction::rsvp(&program_id, &attendee.pubkey(), &event);
+    process(
+        &mut context,
+        std::slice::from_ref(&instruction),
+        &[&attendee],
+    )
+    .await
+    .unwrap();
+    let err = process(&mut context, &[instruction], &[&attendee])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+    assert_eq!(1, get_event(&mut context, &event).await.attendee_count);
+}
+
+#[tokio::test]
+async fn rsvp_started_event() {
+    let organizer = Keypair::new();
+    let attendee = Keypair::new();
+    let (mut context, program_id) = setup(&[&organizer, &attendee]).await;
+    initialize_users(&mut context, &program_id, &[&organizer, &attendee]).await;
+    let event = create_event(&mut context, &program_id, &organizer, 12).await;
+
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += 2 * HOUR;
+    context.set_sysvar(&clock);
+
+    let instruction = instruction::rsvp(&program_id, &attendee.pubkey(), &event);
+    let err = process(&mut context, &[instruction], &[&attendee])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn close_event() {
+    let organizer = Keypair::new();
+    let attendee = Keypair::new();
+    let (mut context, program_id) = setup(&[&organizer, &attendee]).await;
+    initialize_users(&mut context, &program_id, &[&organizer, &attendee]).await;
+    let event = create_event(&mut context, &program_id, &organizer, 12).await;
+    let instruction = instruction::rsvp(&program_id, &attendee.pubkey(), &event);
+    process(&mut context, &[instruction], &[&attendee])
+        .await
+        .unwrap();
+
+    // Only the organizer can close the event.
+    let instruction = instruction::close_event(&program_id, &attendee.pubkey(), &event);
+    let err = process(&mut context, &[instruction], &[&attendee])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    let instruction = instruction::close_event(&program_id, &organizer.pubkey(), &event);
+    process(
+        &mut context,
+        std::slice::from_ref(&instruction),
+        &[&organizer],
+    )
+    .await
+    .unwrap();
+    assert!(get_event(&mut context, &event).await.closed);
+    let err = process(&mut context, &[instruction], &[&organizer])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    // No new RSVPs, but the existing ones can be cancelled.
+    let instruction = instruction::rsvp(&program_id, &organizer.pubkey(), &event);
+    let err = process(&mut context, &[instruction], &[&organizer])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+    let instruction = instruction::cancel_rsvp(&program_id, &attendee.pubkey(), &event);
+    process(&mut context, &[instruction], &[&attendee])
+        .await
+        .unwrap();
+    assert_eq!(0, get_event(&mut context, &event).await.attendee_count);
+}
@@ -0,0 +1,256 @@
+//! Tests of the membership verification, directly and via CPI from a partner program.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        cpi,
+        error::ErrorCause,
+        instruction::{self, ConfigInstructionData},
+        pda::{find_user_pda, USER_PDA_SIZE},
+        processor::process_instruction,
+        state::{VERIFIED_IN_PERSON, VERIFIED_ONBOARDING},
+    },
+    common::*,
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction, InstructionError},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+    solana_program_test::{processor, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::Account,
+        signature::{Keypair, Signer},
+        transaction::TransactionError,
+    },
+};
+
+/// A partner program letting in members only.
+/// The id of the anti-loneliness program comes in the instruction data.
+fn process_partner_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let program_id = Pubkey::try_from(input).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let [program, user, user_pda] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    cpi::verify_member(&program_id, program, user, user_pda, 0)
+}
+
+async fn setup_with_partner(wallets: &[&Keypair]) -> (ProgramTestContext, Pubkey, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let partner_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "anti_loneliness_solana_program",
+        program_id,
+        processor!(process_instruction),
+    );
+    program_test.add_program(
+        "partner_program",
+        partner_id,
+        processor!(process_partner_instruction),
+    );
+    for wallet in wallets {
+        program_test.add_account(
+            wallet.pubkey(),
+            Account::new(WALLET_LAMPORTS, 0, &system_program::ID),
+        );
+    }
+    (
+        program_test.start_with_context().await,
+        program_id,
+        partner_id,
+    )
+}
+
+fn partner_instruction(partner_id: &Pubkey, program_id: &Pubkey, user: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *partner_id,
+        program_id.as_ref(),
+        vec![
+            AccountMeta::new_readonly(*program_id, false),
+            AccountMeta::new_readonly(*user, false),
+            AccountMeta::new_readonly(find_user_pda(program_id, user).0, false),
+        ],
+    )
+}
+
+#[tokio::test]
+async fn verify_member() {
+    let wallet = Keypair::new();
+    let stranger = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet, &stranger]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    // No signature of the user is needed.
+    let instruction = instruction::verify_member(&program_id, &wallet.pubkey(), 0);
+    process(&mut context, &[instruction], &[]).await.unwrap();
+
+    let instruction = instruction::verify_member(&program_id, &stranger.pubkey(), 0);
+    let err = process(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+
+    // The PDA of another user.
+    let mut instruction = instruction::verify_member(&program_id, &stranger.pubkey(), 0);
+    instruction.accounts[1].pubkey = find_user_pda(&program_id, &wallet.pubkey()).0;
+    let err = process(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPda), err);
+}
+
+#[tokio::test]
+async fn verify_closed_member() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    let instruction =
+        instruction::close_account(&program_id, &wallet.pubkey(), &username(&wallet.pubkey()));
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    let instruction = instruction::verify_member(&program_id, &wallet.pubkey(), 0);
+    let err = process(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+}
+
+#[tokio::test]
+async fn verify_flagged_member() {
+    let admin = Keypair::new();
+    let wallet = Keypair::new();
+    let new_wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &wallet, &new_wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+    set_upgrade_authority(&mut context, &program_id, &admin.pubkey());
+    let instruction = instruction::initialize_config(
+        &program_id,
+        &admin.pubkey(),
+        ConfigInstructionData {
+            paused: false,
+            max_user_pda_size: 2 * USER_PDA_SIZE,
+            registration_fee: 0,
+            deposit: 0,
+            attestation_authority: admin.pubkey(),
+            referral_reward: 0,
+        },
+    );
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+
+    // Verifications are checked only when required.
+    let instruction = instruction::verify_member(&program_id, &wallet.pubkey(), VERIFIED_IN_PERSON);
+    let err = process(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+    let instruction = instruction::attest(
+        &program_id,
+        &admin.pubkey(),
+        &wallet.pubkey(),
+        VERIFIED_IN_PERSON | VERIFIED_ONBOARDING,
+        true,
+    );
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+    let instruction = instruction::verify_member(&program_id, &wallet.pubkey(), VERIFIED_IN_PERSON);
+    process(&mut context, &[instruction], &[]).await.unwrap();
+
+    // Only the admin can flag.
+    let instruction = instruction::flag(&program_id, &wallet.pubkey(), &wallet.pubkey());
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+    let instruction = instruction::flag(&program_id, &admin.pubkey(), &wallet.pubkey());
+    process(&mut context, std::slice::from_ref(&instruction), &[&admin])
+        .await
+        .unwrap();
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    let verify = instruction::verify_member(&program_id, &wallet.pubkey(), 0);
+    let err = process(&mut context, std::slice::from_ref(&verify), &[])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Flagged), err);
+
+    // Registering again or moving the profile to another wallet would drop the flag.
+    let username = username(&wallet.pubkey());
+    for instruction in [
+        instruction::close_account(&program_id, &wallet.pubkey(), &username),
+        instruction::transfer_ownership(
+            &program_id,
+            &wallet.pubkey(),
+            &new_wallet.pubkey(),
+            &username,
+        ),
+    ] {
+        let err = process(&mut context, &[instruction], &[&wallet])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::Flagged), err);
+    }
+    let err = process(
+        &mut context,
+        &[initialize(&program_id, &wallet.pubkey())],
+        &[&wallet],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+
+    // Unflagging restores the membership.
+    let instruction = instruction::unflag(&program_id, &admin.pubkey(), &wallet.pubkey());
+    process(&mut context, std::slice::from_ref(&instruction), &[&admin])
+        .await
+        .unwrap();
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+    process(&mut context, &[verify], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn verify_member_via_cpi() {
+    let wallet = Keypair::new();
+    let stranger = Keypair::new();
+    let (mut context, program_id, partner_id) = setup_with_partner(&[&wallet, &stranger]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    let instruction = partner_instruction(&partner_id, &program_id, &wallet.pubkey());
+    process(&mut context, &[instruction], &[]).await.unwrap();
+
+    // The error of the program fails the partner instruction.
+    let instruction = partner_instruction(&partner_id, &program_id, &stranger.pubkey());
+    let err = process(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+
+    // Another program posing as the anti-loneliness one is rejected by the helper.
+    let mut instruction = partner_instruction(&partner_id, &program_id, &wallet.pubkey());
+    instruction.accounts[0].pubkey = system_program::ID;
+    let err = process(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId),
+        err
+    );
+}
@@ -0,0 +1,273 @@
+//! Tests of the community polls and their votes.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction,
+        pda::{find_membership_pda, find_poll_pda, find_vote_pda, POLL_PDA_SIZE, VOTE_PDA_SIZE},
+        state::{Poll, Vote},
+    },
+    common::*,
+    solana_program::{instruction::AccountMeta, pubkey::Pubkey},
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+fn options() -> Vec<String> {
+    vec![
+        "Tempelhofer Feld".to_string(),
+        "Volkspark Friedrichshain".to_string(),
+        "Treptower Park".to_string(),
+    ]
+}
+
+async fn create_poll(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    creator: &Keypair,
+    group: Option<&Pubkey>,
+) -> Pubkey {
+    let instruction = instruction::create_poll(
+        program_id,
+        &creator.pubkey(),
+        7,
+        "Where do we meet next?".to_string(),
+        options(),
+        group,
+    );
+    process(context, &[instruction], &[creator]).await.unwrap();
+    find_poll_pda(program_id, &creator.pubkey(), 7).0
+}
+
+async fn get_poll(context: &mut ProgramTestContext, poll: &Pubkey) -> Poll {
+    let account = get_account(context, poll).await.unwrap();
+    Poll::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn create_open_poll() {
+    let creator = Keypair::new();
+    let (mut context, program_id) = setup(&[&creator]).await;
+    initialize_users(&mut context, &program_id, &[&creator]).await;
+
+    let poll = create_poll(&mut context, &program_id, &creator, None).await;
+
+    let (_, bump_seed) = find_poll_pda(&program_id, &creator.pubkey(), 7);
+    let account = get_account(&mut context, &poll).await.unwrap();
+    assert_eq!(rent_lamports_for(POLL_PDA_SIZE), account.lamports);
+    let state = Poll::unpack(&account.data).unwrap();
+    assert_eq!(creator.pubkey(), state.creator);
+    assert_eq!(bump_seed, state.bump_seed);
+    assert_eq!(None, state.group);
+    assert_eq!("Where do we meet next?", state.question);
+    assert_eq!(options(), state.options);
+    assert_eq!(vec![0, 0, 0], state.tallies);
+    assert!(!state.closed);
+}
+
+#[tokio::test]
+async fn create_poll_invalid() {
+    let creator = Keypair::new();
+    let (mut context, program_id) = setup(&[&creator]).await;
+    initialize_users(&mut context, &program_id, &[&creator]).await;
+
+    for (question, options) in [
+        (" ".to_string(), options()),
+        ("Where?".to_string(), vec!["Park".to_string()]),
+        (
+            "Where?".to_string(),
+            vec!["Park".to_string(), "Park".to_string()],
+        ),
+    ] {
+        let instruction =
+            instruction::create_poll(&program_id, &creator.pubkey(), 1, question, options, None);
+        let err = process(&mut context, &[instruction], &[&creator])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+    }
+}
+
+#[tokio::test]
+async fn vote_and_close() {
+    let creator = Keypair::new();
+    let voter = Keypair::new();
+    let other_voter = Keypair::new();
+    let late_voter = Keypair::new();
+    let users = [&creator, &voter, &other_voter, &late_voter];
+    let (mut context, program_id) = setup(&users).await;
+    initialize_users(&mut context, &program_id, &users).await;
+    let poll = create_poll(&mut context, &program_id, &creator, None).await;
+
+    for (user, option) in [(&creator, 2), (&voter, 2), (&other_voter, 0)] {
+        let instruction = instruction::vote(&program_id, &user.pubkey(), &poll, None, option);
+        process(&mut context, &[instruction], &[user])
+            .await
+            .unwrap();
+    }
+    assert_eq!(vec![1, 0, 2], get_poll(&mut context, &poll).await.tallies);
+    let (pda, bump_seed) = find_vote_pda(&program_id, &poll, &voter.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(rent_lamports_for(VOTE_PDA_SIZE), account.lamports);
+    let vote = Vote::unpack(&account.data).unwrap();
+    assert_eq!(poll, vote.poll);
+    assert_eq!(voter.pubkey(), vote.voter);
+    assert_eq!(bump_seed, vote.bump_seed);
+    assert_eq!(2, vote.option);
+
+    // Only the creator can close the poll.
+    let instruction = instruction::close_poll(&program_id, &voter.pubkey(), &poll);
+    let err = process(&mut context, &[instruction], &[&voter])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+    let instruction = instruction::close_poll(&program_id, &creator.pubkey(), &poll);
+    process(&mut context, &[instruction], &[&creator])
+        .await
+        .unwrap();
+    let state = get_poll(&mut context, &poll).await;
+    assert!(state.closed);
+    assert_eq!(vec![1, 0, 2], state.tallies);
+
+    let instruction = instruction::vote(&program_id, &late_voter.pubkey(), &poll, None, 1);
+    let err = process(&mut context, &[instruction], &[&late_voter])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn close_vote() {
+    let creator = Keypair::new();
+    let voter = Keypair::new();
+    let (mut context, program_id) = setup(&[&creator, &voter]).await;
+    initialize_users(&mut context, &program_id, &[&creator, &voter]).await;
+    let poll = create_poll(&mut context, &program_id, &creator, None).await;
+    let instruction = instruction::vote(&program_id, &voter.pubkey(), &poll, None, 1);
+    process(&mut context, &[instruction], &[&voter])
+        .await
+        .unwrap();
+
+    // Not while the poll takes votes.
+    let instruction = instruction::close_vote(&program_id, &voter.pubkey(), &poll);
+    let err = process(&mut context, &[instruction], &[&voter])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+    let instruction = instruction::close_poll(&program_id, &creator.pubkey(), &poll);
+    process(&mut context, &[instruction], &[&creator])
+        .await
+        .unwrap();
+
+    // Only the voter can close the vote.
+    let (pda, _) = find_vote_pda(&program_id, &poll, &voter.pubkey());
+    let mut instruction = instruction::close_vote(&program_id, &creator.pubkey(), &poll);
+    instruction.accounts[2] = AccountMeta::new(pda, false);
+    let err = process(&mut context, &[instruction], &[&creator])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    let instruction = instruction::close_vote(&program_id, &voter.pubkey(), &poll);
+    process(&mut context, &[instruction], &[&voter])
+        .await
+        .unwrap();
+    assert!(get_account(&mut context, &pda).await.is_none());
+    assert_eq!(vec![0, 1, 0], get_poll(&mut context, &poll).await.tallies);
+    // The rent of the vote went back to the voter.
+    let account = get_account(&mut context, &voter.pubkey()).await.unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        account.lamports
+    );
+}
+
+#[tokio::test]
+async fn vote_twice() {
+    let creator = Keypair::new();
+    let voter = Keypair::new();
+    let (mut context, program_id) = setup(&[&creator, &voter]).await;
+    initialize_users(&mut context, &program_id, &[&creator, &voter]).await;
+    let poll = create_poll(&mut context, &program_id, &creator, None).await;
+
+    let instruction = instruction::vote(&program_id, &voter.pubkey(), &poll, None, 0);
+    process(&mut context, &[instruction], &[&voter])
+        .await
+        .unwrap();
+    // Neither for the same option nor for another one.
+    for option in [0, 1] {
+        let instruction = instruction::vote(&program_id, &voter.pubkey(), &poll, None, option);
+        let err = process(&mut context, &[instruction], &[&voter])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+    }
+    assert_eq!(vec![1, 0, 0], get_poll(&mut context, &poll).await.tallies);
+}
+
+#[tokio::test]
+async fn vote_unknown_option() {
+    let creator = Keypair::new();
+    let (mut context, program_id) = setup(&[&creator]).await;
+    initialize_users(&mut context, &program_id, &[&creator]).await;
+    let poll = create_poll(&mut context, &program_id, &creator, None).await;
+
+    let instruction = instruction::vote(&program_id, &creator.pubkey(), &poll, None, 3);
+    let err = process(&mut context, &[instruction], &[&creator])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+}
+
+#[tokio::test]
+async fn group_poll_members_only() {
+    let admin = Keypair::new();
+    let member = Keypair::new();
+    let outsider = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &member, &outsider]).await;
+    initialize_users(&mut context, &program_id, &[&admin, &member, &outsider]).await;
+    let group = create_group(&mut context, &program_id, &admin, false, 10).await;
+    let instruction = instruction::join_group(&program_id, &member.pubkey(), &group, None);
+    process(&mut context, &[instruction], &[&member])
+        .await
+        .unwrap();
+
+    // A user outside of the group can't create a poll for it.
+    let instruction = instruction::create_poll(
+        &program_id,
+        &outsider.pubkey(),
+        7,
+        "Where do we meet next?".to_string(),
+        options(),
+        Some(&group),
+    );
+    let err = process(&mut context, &[instruction], &[&outsider])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+
+    let poll = create_poll(&mut context, &program_id, &member, Some(&group)).await;
+    assert_eq!(Some(group), get_poll(&mut context, &poll).await.group);
+    let instruction = instruction::vote(&program_id, &admin.pubkey(), &poll, Some(&group), 1);
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+
+    let instruction = instruction::vote(&program_id, &outsider.pubkey(), &poll, Some(&group), 1);
+    let err = process(&mut context, &[instruction], &[&outsider])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+    // Nor with the membership of someone else.
+    let mut instruction =
+        instruction::vote(&program_id, &outsider.pubkey(), &poll, Some(&group), 1);
+    let (admin_membership, _) = find_membership_pda(&program_id, &group, &admin.pubkey());
+    instruction.accounts[6] = AccountMeta::new_readonly(admin_membership, false);
+    let err = process(&mut context, &[instruction], &[&outsider])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+    assert_eq!(vec![0, 1, 0], get_poll(&mut context, &poll).await.tallies);
+}
@@ -0,0 +1,448 @@
+//! Tests of the user PDA instructions.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction::{self, InitializeInstructionData, ProgramInstruction},
+        pda::{find_user_pda, find_username_pda, MAX_USER_PDA_SIZE, USER_PDA_SIZE},
+        state::{UserProfile, UsernameClaim},
+    },
+    common::*,
+    solana_program::{pubkey::Pubkey, system_instruction},
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn initialize_and_close() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    let (pda, bump_seed) = find_user_pda(&program_id, &wallet.pubkey());
+
+    let instruction = initialize(&program_id, &wallet.pubkey());
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(program_id, account.owner);
+    assert_eq!(USER_PDA_SIZE as usize, account.data.len());
+    assert_eq!(rent_lamports(), account.lamports);
+    let profile = UserProfile::unpack(&account.data).unwrap();
+    assert_eq!(wallet.pubkey(), profile.owner);
+    assert_eq!(bump_seed, profile.bump_seed);
+    assert_eq!(username(&wallet.pubkey()), profile.username);
+
+    let instruction = instruction::close_account(&program_id, &wallet.pubkey(), &profile.username);
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(pda)
+        .await
+        .unwrap()
+        .is_none());
+    // The username is released and the rent is returned to the owner.
+    let (username_pda, _) = find_username_pda(&program_id, &profile.username);
+    assert!(get_account(&mut context, &username_pda).await.is_none());
+    let wallet_lamports = context
+        .banks_client
+        .get_balance(wallet.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(WALLET_LAMPORTS, wallet_lamports);
+}
+
+#[tokio::test]
+async fn initialize_wrong_signer() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+
+    // The wallet doesn't sign the transaction.
+    let mut instruction = initialize(&program_id, &wallet.pubkey());
+    instruction.accounts[0].is_signer = false;
+    let err = process(&mut context, &[instruction], &[])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::MissingRequiredSignature), err);
+}
+
+#[tokio::test]
+async fn initialize_wrong_pda() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+
+    // PDA of a different wallet
+    let mut instruction = initialize(&program_id, &wallet.pubkey());
+    instruction.accounts[1].pubkey = find_user_pda(&program_id, &Pubkey::new_unique()).0;
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPda), err);
+
+    // Correct PDA, but not the canonical bump seed
+    let (_, bump_seed) = find_user_pda(&program_id, &wallet.pubkey());
+    let mut instruction = initialize(&program_id, &wallet.pubkey());
+    instruction.data = ProgramInstruction::Initialize(InitializeInstructionData {
+        lamports: rent_lamports(),
+        pda_bump_seed: bump_seed.wrapping_sub(1),
+        username: "paulinka".to_string(),
+        referrer: None,
+    })
+    .pack()
+    .unwrap();
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidBumpSeed), err);
+}
+
+#[tokio::test]
+async fn initialize_twice() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+
+    let instruction = initialize(&program_id, &wallet.pubkey());
+    process(&mut context, std::slice::from_ref(&instruction), &[&wallet])
+        .await
+        .unwrap();
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+}
+
+#[tokio::test]
+async fn initialize_insufficient_rent() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    let (pda, _) = find_user_pda(&program_id, &wallet.pubkey());
+
+    let instruction = instruction::initialize(
+        &program_id,
+        &wallet.pubkey(),
+        rent_lamports() - 1,
+        "paulinka".to_string(),
+    );
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InsufficientRent), err);
+    assert!(context
+        .banks_client
+        .get_account(pda)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn initialize_pays_only_rent() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    let (pda, _) = find_user_pda(&program_id, &wallet.pubkey());
+
+    let instruction = instruction::initialize(
+        &program_id,
+        &wallet.pubkey(),
+        2 * rent_lamports(),
+        "paulinka".to_string(),
+    );
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(rent_lamports(), account.lamports);
+    let wallet_lamports = context
+        .banks_client
+        .get_balance(wallet.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        wallet_lamports
+    );
+}
+
+#[tokio::test]
+async fn initialize_prefunded_pda() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    let (pda, _) = find_user_pda(&program_id, &wallet.pubkey());
+
+    // Someone transfers lamports to the PDA address before it's created.
+    let prefunded_lamports = rent_lamports() / 2;
+    let transfer = system_instruction::transfer(&context.payer.pubkey(), &pda, prefunded_lamports);
+    process(&mut context, &[transfer], &[]).await.unwrap();
+
+    let instruction = initialize(&program_id, &wallet.pubkey());
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(program_id, account.owner);
+    assert_eq!(USER_PDA_SIZE as usize, account.data.len());
+    assert_eq!(rent_lamports(), account.lamports);
+    let profile = UserProfile::unpack(&account.data).unwrap();
+    assert_eq!(wallet.pubkey(), profile.owner);
+    // The wallet only pays the missing part of the rent.
+    let wallet_lamports = context
+        .banks_client
+        .get_balance(wallet.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - (rent_lamports() - prefunded_lamports) - username_rent_lamports(),
+        wallet_lamports
+    );
+}
+
+#[tokio::test]
+async fn close_someone_elses_account() {
+    let owner = Keypair::new();
+    let attacker = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &attacker]).await;
+    let (pda, _) = find_user_pda(&program_id, &owner.pubkey());
+
+    let instruction = initialize(&program_id, &owner.pubkey());
+    process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap();
+
+    let mut instruction =
+        instruction::close_account(&program_id, &attacker.pubkey(), &username(&owner.pubkey()));
+    instruction.accounts[1].pubkey = pda;
+    let err = process(&mut context, &[instruction], &[&attacker])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPda), err);
+
+    // The PDA is untouched.
+    let account = context
+        .banks_client
+        .get_account(pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(rent_lamports(), account.lamports);
+}
+
+#[tokio::test]
+async fn close_uninitialized_account() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+
+    let instruction = instruction::close_account(&program_id, &wallet.pubkey(), "paulinka");
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+}
+
+#[tokio::test]
+async fn resize_grow_and_shrink() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    let (pda, _) = find_user_pda(&program_id, &wallet.pubkey());
+
+    let instruction = initialize(&program_id, &wallet.pubkey());
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    for new_size in [3 * USER_PDA_SIZE, USER_PDA_SIZE] {
+        let instruction = instruction::resize(&program_id, &wallet.pubkey(), new_size);
+        process(&mut context, &[instruction], &[&wallet])
+            .await
+            .unwrap();
+
+        let account = context
+            .banks_client
+            .get_account(pda)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(new_size as usize, account.data.len());
+        assert_eq!(rent_lamports_for(new_size), account.lamports);
+        let profile = UserProfile::unpack(&account.data).unwrap();
+        assert_eq!(username(&wallet.pubkey()), profile.username);
+        // The wallet has always paid exactly the rent of the current size.
+        let wallet_lamports = context
+            .banks_client
+            .get_balance(wallet.pubkey())
+            .await
+            .unwrap();
+        assert_eq!(
+            WALLET_LAMPORTS - rent_lamports_for(new_size) - username_rent_lamports(),
+            wallet_lamports
+        );
+    }
+}
+
+#[tokio::test]
+async fn resize_invalid_size() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+
+    let instruction = initialize(&program_id, &wallet.pubkey());
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    // Too small to hold the profile, below the initial size, and above the limit
+    for new_size in [8, USER_PDA_SIZE - 1, MAX_USER_PDA_SIZE + 1] {
+        let instruction = instruction::resize(&program_id, &wallet.pubkey(), new_size);
+        let err = process(&mut context, &[instruction], &[&wallet])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::InvalidAccountSize), err);
+    }
+}
+
+#[tokio::test]
+async fn resize_someone_elses_account() {
+    let owner = Keypair::new();
+    let attacker = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &attacker]).await;
+    let (pda, _) = find_user_pda(&program_id, &owner.pubkey());
+
+    let instruction = initialize(&program_id, &owner.pubkey());
+    process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap();
+
+    // Shrinking would refund the rent to the attacker.
+    let mut instruction = instruction::resize(&program_id, &attacker.pubkey(), USER_PDA_SIZE / 2);
+    instruction.accounts[1].pubkey = pda;
+    let err = process(&mut context, &[instruction], &[&attacker])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPda), err);
+}
+
+#[tokio::test]
+async fn transfer_ownership() {
+    let owner = Keypair::new();
+    let new_owner = Keypair::new();
+    let endorser = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &new_owner, &endorser]).await;
+    initialize_users(&mut context, &program_id, &[&owner, &endorser]).await;
+    let (pda, _) = find_user_pda(&program_id, &owner.pubkey());
+    let (new_pda, bump_seed) = find_user_pda(&program_id, &new_owner.pubkey());
+    let instruction = instruction::endorse(&program_id, &endorser.pubkey(), &owner.pubkey());
+    process(&mut context, &[instruction], &[&endorser])
+        .await
+        .unwrap();
+
+    let instruction = instruction::transfer_ownership(
+        &program_id,
+        &owner.pubkey(),
+        &new_owner.pubkey(),
+        &username(&owner.pubkey()),
+    );
+    process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap();
+
+    assert!(get_account(&mut context, &pda).await.is_none());
+    let account = get_account(&mut context, &new_pda).await.unwrap();
+    assert_eq!(USER_PDA_SIZE as usize, account.data.len());
+    assert_eq!(rent_lamports(), account.lamports);
+    let profile = UserProfile::unpack(&account.data).unwrap();
+    assert_eq!(new_owner.pubkey(), profile.owner);
+    assert_eq!(bump_seed, profile.bump_seed);
+    assert_eq!(username(&owner.pubkey()), profile.username);
+    // The endorsement is still keyed by the old wallet.
+    assert_eq!(0, profile.endorsement_count);
+
+    // The username stays claimed, now by the new owner.
+    let (username_pda, _) = find_username_pda(&program_id, &username(&owner.pubkey()));
+    let account = get_account(&mut context, &username_pda).await.unwrap();
+    let claim = UsernameClaim::unpack(&account.data).unwrap();
+    assert_eq!(new_owner.pubkey(), claim.owner);
+    assert_eq!(new_pda, claim.user_pda);
+
+    // The current owner paid for the new PDA and got the rent of the old one back.
+    let account = get_account(&mut context, &owner.pubkey()).await.unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        account.lamports
+    );
+}
+
+#[tokio::test]
+async fn transfer_ownership_paid_by_new_owner() {
+    let owner = Keypair::new();
+    let new_owner = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &new_owner]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+
+    let mut instruction = instruction::transfer_ownership(
+        &program_id,
+        &owner.pubkey(),
+        &new_owner.pubkey(),
+        &username(&owner.pubkey()),
+    );
+    instruction.accounts[1].is_signer = true;
+    process(&mut context, &[instruction], &[&owner, &new_owner])
+        .await
+        .unwrap();
+
+    let account = get_account(&mut context, &owner.pubkey()).await.unwrap();
+    assert_eq!(WALLET_LAMPORTS - username_rent_lamports(), account.lamports);
+    let account = get_account(&mut context, &new_owner.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(WALLET_LAMPORTS - rent_lamports(), account.lamports);
+}
+
+#[tokio::test]
+async fn transfer_ownership_invalid() {
+    let owner = Keypair::new();
+    let other_user = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &other_user]).await;
+    initialize_users(&mut context, &program_id, &[&owner, &other_user]).await;
+
+    // A wallet can't hold two profiles.
+    let instruction = instruction::transfer_ownership(
+        &program_id,
+        &owner.pubkey(),
+        &other_user.pubkey(),
+        &username(&owner.pubkey()),
+    );
+    let err = process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+
+    let instruction = instruction::transfer_ownership(
+        &program_id,
+        &owner.pubkey(),
+        &owner.pubkey(),
+        &username(&owner.pubkey()),
+    );
+    let err = process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPubKey), err);
+}
@@ -0,0 +1,404 @@
+//! Tests of the guardians and the social recovery of a user PDA.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction,
+        pda::{find_recovery_pda, find_user_pda, find_username_pda, RECOVERY_PDA_SIZE},
+        state::{Recovery, UserProfile, UsernameClaim, RECOVERY_TIMELOCK_SLOTS},
+    },
+    common::*,
+    solana_program::pubkey::Pubkey,
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+async fn set_guardians(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    owner: &Keypair,
+    guardians: &[&Keypair],
+    threshold: u8,
+) {
+    let guardians = guardians.iter().map(|guardian| guardian.pubkey()).collect();
+    let instruction = instruction::set_guardians(program_id, &owner.pubkey(), guardians, threshold);
+    process(context, &[instruction], &[owner]).await.unwrap();
+}
+
+async fn propose_recovery(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    guardian: &Keypair,
+    owner: &Keypair,
+    new_owner: &Keypair,
+) -> Pubkey {
+    let instruction = instruction::propose_recovery(
+        program_id,
+        &guardian.pubkey(),
+        &owner.pubkey(),
+        &new_owner.pubkey(),
+    );
+    process(context, &[instruction], &[guardian]).await.unwrap();
+    let (pda, _) = find_user_pda(program_id, &owner.pubkey());
+    find_recovery_pda(program_id, &pda).0
+}
+
+async fn approve_recovery(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    guardian: &Keypair,
+    owner: &Keypair,
+) {
+    let instruction =
+        instruction::approve_recovery(program_id, &guardian.pubkey(), &owner.pubkey());
+    process(context, &[instruction], &[guardian]).await.unwrap();
+}
+
+fn execute_recovery(
+    program_id: &Pubkey,
+    new_owner: &Keypair,
+    owner: &Keypair,
+    proposer: &Keypair,
+) -> solana_program::instruction::Instruction {
+    instruction::execute_recovery(
+        program_id,
+        &new_owner.pubkey(),
+        &owner.pubkey(),
+        &username(&owner.pubkey()),
+        &proposer.pubkey(),
+    )
+}
+
+#[tokio::test]
+async fn set_and_clear_guardians() {
+    let owner = Keypair::new();
+    let guardians = [Keypair::new(), Keypair::new()];
+    let (mut context, program_id) = setup(&[&owner]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    let (pda, _) = find_user_pda(&program_id, &owner.pubkey());
+
+    set_guardians(
+        &mut context,
+        &program_id,
+        &owner,
+        &[&guardians[0], &guardians[1]],
+        2,
+    )
+    .await;
+    let account = get_account(&mut context, &pda).await.unwrap();
+    let profile = UserProfile::unpack(&account.data).unwrap();
+    assert_eq!(
+        vec![guardians[0].pubkey(), guardians[1].pubkey()],
+        profile.guardians
+    );
+    assert_eq!(2, profile.guardian_threshold);
+
+    // More approvals than guardians could never be collected.
+    let instruction =
+        instruction::set_guardians(&program_id, &owner.pubkey(), vec![guardians[0].pubkey()], 2);
+    let err = process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+
+    set_guardians(&mut context, &program_id, &owner, &[], 0).await;
+    let account = get_account(&mut context, &pda).await.unwrap();
+    let profile = UserProfile::unpack(&account.data).unwrap();
+    assert!(profile.guardians.is_empty());
+    assert_eq!(0, profile.guardian_threshold);
+}
+
+#[tokio::test]
+async fn recover_with_guardians() {
+    let owner = Keypair::new();
+    let new_owner = Keypair::new();
+    let guardians = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let (mut context, program_id) =
+        setup(&[&owner, &new_owner, &guardians[0], &guardians[1]]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    set_guardians(
+        &mut context,
+        &program_id,
+        &owner,
+        &[&guardians[0], &guardians[1], &guardians[2]],
+        2,
+    )
+    .await;
+    let (pda, _) = find_user_pda(&program_id, &owner.pubkey());
+    let (new_pda, _) = find_user_pda(&program_id, &new_owner.pubkey());
+
+    let recovery =
+        propose_recovery(&mut context, &program_id, &guardians[0], &owner, &new_owner).await;
+    let account = get_account(&mut context, &recovery).await.unwrap();
+    let state = Recovery::unpack(&account.data).unwrap();
+    assert_eq!(new_owner.pubkey(), state.new_owner);
+    assert_eq!(vec![guardians[0].pubkey()], state.approvals);
+
+    // The proposal alone doesn't reach the threshold.
+    context.warp_to_slot(RECOVERY_TIMELOCK_SLOTS + 1).unwrap();
+    let instruction = execute_recovery(&program_id, &new_owner, &owner, &guardians[0]);
+    let err = process(&mut context, &[instruction], &[&new_owner])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    approve_recovery(&mut context, &program_id, &guardians[1], &owner).await;
+    let instruction =
+        instruction::approve_recovery(&program_id, &guardians[1].pubkey(), &owner.pubkey());
+    let err = process(&mut context, &[instruction], &[&guardians[1]])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    let instruction = execute_recovery(&program_id, &new_owner, &owner, &guardians[0]);
+    process(&mut context, &[instruction], &[&new_owner])
+        .await
+        .unwrap();
+
+    assert!(get_account(&mut context, &pda).await.is_none());
+    assert!(get_account(&mut context, &recovery).await.is_none());
+    let account = get_account(&mut context, &new_pda).await.unwrap();
+    let profile = UserProfile::unpack(&account.data).unwrap();
+    assert_eq!(new_owner.pubkey(), profile.owner);
+    assert_eq!(username(&owner.pubkey()), profile.username);
+    assert_eq!(3, profile.guardians.len());
+    let (username_pda, _) = find_username_pda(&program_id, &username(&owner.pubkey()));
+    let account = get_account(&mut context, &username_pda).await.unwrap();
+    let claim = UsernameClaim::unpack(&account.data).unwrap();
+    assert_eq!(new_owner.pubkey(), claim.owner);
+
+    // The new owner paid for its PDA and got the rent of the old one,
+    // the proposer got the rent of the recovery PDA back.
+    assert_eq!(
+        WALLET_LAMPORTS,
+        balance(&mut context, &new_owner.pubkey()).await
+    );
+    assert_eq!(
+        WALLET_LAMPORTS,
+        balance(&mut context, &guardians[0].pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn recovery_waits_for_timelock() {
+    let owner = Keypair::new();
+    let new_owner = Keypair::new();
+    let guardian = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &new_owner, &guardian]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    set_guardians(&mut context, &program_id, &owner, &[&guardian], 1).await;
+    propose_recovery(&mut context, &program_id, &guardian, &owner, &new_owner).await;
+
+    let instruction = execute_recovery(&program_id, &new_owner, &owner, &guardian);
+    let err = process(&mut context, &[instruction], &[&new_owner])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+
+    // Only the wallet chosen by the guardians can take the profile.
+    context.warp_to_slot(RECOVERY_TIMELOCK_SLOTS + 1).unwrap();
+    let stranger = Keypair::new();
+    let instruction = execute_recovery(&program_id, &stranger, &owner, &guardian);
+    let err = process(&mut context, &[instruction], &[&stranger])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPubKey), err);
+}
+
+#[tokio::test]
+async fn only_guardians_recover() {
+    let owner = Keypair::new();
+    let new_owner = Keypair::new();
+    let guardian = Keypair::new();
+    let stranger = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &guardian, &stranger]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+
+    // No recovery without guardians.
+    let instruction = instruction::propose_recovery(
+        &program_id,
+        &guardian.pubkey(),
+        &owner.pubkey(),
+        &new_owner.pubkey(),
+    );
+    let err = process(&mut context, &[instruction], &[&guardian])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    set_guardians(&mut context, &program_id, &owner, &[&guardian], 1).await;
+    let instruction = instruction::propose_recovery(
+        &program_id,
+        &stranger.pubkey(),
+        &owner.pubkey(),
+        &new_owner.pubkey(),
+    );
+    let err = process(&mut context, &[instruction], &[&stranger])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    let instruction = instruction::propose_recovery(
+        &program_id,
+        &guardian.pubkey(),
+        &owner.pubkey(),
+        &owner.pubkey(),
+    );
+    let err = process(&mut context, &[instruction], &[&guardian])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPubKey), err);
+
+    propose_recovery(&mut context, &program_id, &guardian, &owner, &new_owner).await;
+    let instruction =
+        instruction::approve_recovery(&program_id, &stranger.pubkey(), &owner.pubkey());
+    let err = process(&mut context, &[instruction], &[&stranger])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+}
+
+#[tokio::test]
+async fn removed_guardian_approval_dropped() {
+    let owner = Keypair::new();
+    let new_owner = Keypair::new();
+    let guardians = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let (mut context, program_id) = setup(&[&owner, &new_owner, &guardians[0]]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    set_guardians(
+        &mut context,
+        &program_id,
+        &owner,
+        &[&guardians[0], &guardians[1]],
+        2,
+    )
+    .await;
+    propose_recovery(&mut context, &program_id, &guardians[0], &owner, &new_owner).await;
+    approve_recovery(&mut context, &program_id, &guardians[1], &owner).await;
+
+    set_guardians(
+        &mut context,
+        &program_id,
+        &owner,
+        &[&guardians[0], &guardians[2]],
+        2,
+    )
+    .await;
+    context.warp_to_slot(RECOVERY_TIMELOCK_SLOTS + 1).unwrap();
+    let instruction = execute_recovery(&program_id, &new_owner, &owner, &guardians[0]);
+    let err = process(&mut context, &[instruction], &[&new_owner])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidState), err);
+}
+
+#[tokio::test]
+async fn cancel_recovery() {
+    let owner = Keypair::new();
+    let new_owner = Keypair::new();
+    let guardian = Keypair::new();
+    let stranger = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &guardian, &stranger]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    set_guardians(&mut context, &program_id, &owner, &[&guardian], 1).await;
+
+    let recovery = propose_recovery(&mut context, &program_id, &guardian, &owner, &new_owner).await;
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports_for(RECOVERY_PDA_SIZE),
+        balance(&mut context, &guardian.pubkey()).await
+    );
+    // A second recovery waits for the pending one.
+    let instruction = instruction::propose_recovery(
+        &program_id,
+        &guardian.pubkey(),
+        &owner.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let err = process(&mut context, &[instruction], &[&guardian])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+
+    let instruction = instruction::cancel_recovery(
+        &program_id,
+        &stranger.pubkey(),
+        &owner.pubkey(),
+        &guardian.pubkey(),
+    );
+    let err = process(&mut context, &[instruction], &[&stranger])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+
+    let instruction = instruction::cancel_recovery(
+        &program_id,
+        &owner.pubkey(),
+        &owner.pubkey(),
+        &guardian.pubkey(),
+    );
+    process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap();
+    assert!(get_account(&mut context, &recovery).await.is_none());
+    assert_eq!(
+        WALLET_LAMPORTS,
+        balance(&mut context, &guardian.pubkey()).await
+    );
+
+    // The proposer can withdraw the recovery too.
+    propose_recovery(&mut context, &program_id, &guardian, &owner, &new_owner).await;
+    let instruction = instruction::cancel_recovery(
+        &program_id,
+        &guardian.pubkey(),
+        &owner.pubkey(),
+        &guardian.pubkey(),
+    );
+    process(&mut context, &[instruction], &[&guardian])
+        .await
+        .unwrap();
+    assert!(get_account(&mut context, &recovery).await.is_none());
+}
+
+#[tokio::test]
+async fn pending_recovery_keeps_profile() {
+    let owner = Keypair::new();
+    let new_owner = Keypair::new();
+    let guardian = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &guardian]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    set_guardians(&mut context, &program_id, &owner, &[&guardian], 1).await;
+    propose_recovery(&mut context, &program_id, &guardian, &owner, &new_owner).await;
+
+    // The recovery would apply to the next profile stored in the user PDA.
+    let username = username(&owner.pubkey());
+    for instruction in [
+        instruction::close_account(&program_id, &owner.pubkey(), &username),
+        instruction::transfer_ownership(
+            &program_id,
+            &owner.pubkey(),
+            &new_owner.pubkey(),
+            &username,
+        ),
+    ] {
+        let err = process(&mut context, &[instruction], &[&owner])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::InvalidState), err);
+    }
+
+    let instruction = instruction::cancel_recovery(
+        &program_id,
+        &owner.pubkey(),
+        &owner.pubkey(),
+        &guardian.pubkey(),
+    );
+    process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap();
+    let instruction = instruction::close_account(&program_id, &owner.pubkey(), &username);
+    process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap();
+}
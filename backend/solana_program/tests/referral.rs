@@ -0,0 +1,243 @@
+//! Tests of the referrals of new users.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction::{self, ConfigInstructionData},
+        pda::{
+            find_config_pda, find_referral_pda, find_user_pda, CONFIG_PDA_SIZE, REFERRAL_PDA_SIZE,
+            USER_PDA_SIZE,
+        },
+        state::{Referral, UserProfile},
+    },
+    common::*,
+    solana_program::{instruction::Instruction, pubkey::Pubkey},
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+const REGISTRATION_FEE: u64 = 5000;
+const REFERRAL_REWARD: u64 = 2000;
+
+fn config_data(referral_reward: u64) -> ConfigInstructionData {
+    ConfigInstructionData {
+        paused: false,
+        max_user_pda_size: USER_PDA_SIZE,
+        registration_fee: REGISTRATION_FEE,
+        deposit: 0,
+        attestation_authority: Pubkey::default(),
+        referral_reward,
+    }
+}
+
+fn initialize_referred(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    referrer: &Pubkey,
+    registration_fee: u64,
+) -> Instruction {
+    instruction::initialize_with_referrer(
+        program_id,
+        wallet,
+        rent_lamports()
+            + username_rent_lamports()
+            + rent_lamports_for(REFERRAL_PDA_SIZE)
+            + registration_fee,
+        username(wallet),
+        Some(referrer),
+    )
+}
+
+async fn invite_count(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+) -> u64 {
+    let (pda, _) = find_user_pda(program_id, wallet);
+    let account = get_account(context, &pda).await.unwrap();
+    UserProfile::unpack(&account.data).unwrap().invite_count
+}
+
+async fn referral(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+) -> Referral {
+    let (pda, _) = find_referral_pda(program_id, wallet);
+    let account = get_account(context, &pda).await.unwrap();
+    Referral::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn refer_new_user() {
+    let referrer = Keypair::new();
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&referrer, &wallet]).await;
+    initialize_users(&mut context, &program_id, &[&referrer]).await;
+
+    let instruction = initialize_referred(&program_id, &wallet.pubkey(), &referrer.pubkey(), 0);
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        1,
+        invite_count(&mut context, &program_id, &referrer.pubkey()).await
+    );
+    let referral = referral(&mut context, &program_id, &wallet.pubkey()).await;
+    assert_eq!(referrer.pubkey(), referral.referrer);
+    assert_eq!(wallet.pubkey(), referral.user);
+    // No reward without a config.
+    assert_eq!(0, referral.reward);
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        balance(&mut context, &referrer.pubkey()).await
+    );
+    // The new user pays for the referral PDA.
+    assert_eq!(
+        WALLET_LAMPORTS
+            - rent_lamports()
+            - username_rent_lamports()
+            - rent_lamports_for(REFERRAL_PDA_SIZE),
+        balance(&mut context, &wallet.pubkey()).await
+    );
+}
+
+#[tokio::test]
+async fn referral_reward() {
+    let admin = Keypair::new();
+    let referrer = Keypair::new();
+    let wallet = Keypair::new();
+    let other_wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&admin, &referrer, &wallet, &other_wallet]).await;
+    set_upgrade_authority(&mut context, &program_id, &admin.pubkey());
+    let instruction =
+        instruction::initialize_config(&program_id, &admin.pubkey(), config_data(REFERRAL_REWARD));
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+    let instruction = instruction::initialize(
+        &program_id,
+        &referrer.pubkey(),
+        rent_lamports() + username_rent_lamports() + REGISTRATION_FEE,
+        username(&referrer.pubkey()),
+    );
+    process(&mut context, &[instruction], &[&referrer])
+        .await
+        .unwrap();
+
+    // The reward is paid from the registration fees collected by the config PDA.
+    let instruction = initialize_referred(
+        &program_id,
+        &wallet.pubkey(),
+        &referrer.pubkey(),
+        REGISTRATION_FEE,
+    );
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    assert_eq!(
+        REFERRAL_REWARD,
+        referral(&mut context, &program_id, &wallet.pubkey())
+            .await
+            .reward
+    );
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports() - REGISTRATION_FEE
+            + REFERRAL_REWARD,
+        balance(&mut context, &referrer.pubkey()).await
+    );
+    let (config_pda, _) = find_config_pda(&program_id);
+    assert_eq!(
+        rent_lamports_for(CONFIG_PDA_SIZE) + 2 * REGISTRATION_FEE - REFERRAL_REWARD,
+        balance(&mut context, &config_pda).await
+    );
+
+    // Referring one's own wallets can't pay more than the registration fee.
+    let instruction = instruction::update_config(
+        &program_id,
+        &admin.pubkey(),
+        config_data(REGISTRATION_FEE + 1),
+    );
+    let err = process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+    let instruction =
+        instruction::update_config(&program_id, &admin.pubkey(), config_data(REGISTRATION_FEE));
+    process(&mut context, &[instruction], &[&admin])
+        .await
+        .unwrap();
+    let instruction = initialize_referred(
+        &program_id,
+        &other_wallet.pubkey(),
+        &referrer.pubkey(),
+        REGISTRATION_FEE,
+    );
+    process(&mut context, &[instruction], &[&other_wallet])
+        .await
+        .unwrap();
+    assert_eq!(
+        REGISTRATION_FEE,
+        referral(&mut context, &program_id, &other_wallet.pubkey())
+            .await
+            .reward
+    );
+    assert_eq!(
+        2,
+        invite_count(&mut context, &program_id, &referrer.pubkey()).await
+    );
+    assert_eq!(
+        rent_lamports_for(CONFIG_PDA_SIZE) + 2 * REGISTRATION_FEE - REFERRAL_REWARD,
+        balance(&mut context, &config_pda).await
+    );
+}
+
+#[tokio::test]
+async fn refer_once() {
+    let referrer = Keypair::new();
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&referrer, &wallet]).await;
+
+    // Users can't refer themselves.
+    let instruction = initialize_referred(&program_id, &wallet.pubkey(), &wallet.pubkey(), 0);
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidPubKey), err);
+
+    // The referrer needs to be registered.
+    let instruction = initialize_referred(&program_id, &wallet.pubkey(), &referrer.pubkey(), 0);
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+
+    initialize_users(&mut context, &program_id, &[&referrer]).await;
+    let instruction = initialize_referred(&program_id, &wallet.pubkey(), &referrer.pubkey(), 0);
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    let instruction =
+        instruction::close_account(&program_id, &wallet.pubkey(), &username(&wallet.pubkey()));
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    // Registering again doesn't count as another invite.
+    let instruction = initialize_referred(&program_id, &wallet.pubkey(), &referrer.pubkey(), 0);
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountAlreadyInitialized), err);
+    let instruction = initialize(&program_id, &wallet.pubkey());
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    assert_eq!(
+        1,
+        invite_count(&mut context, &program_id, &referrer.pubkey()).await
+    );
+}
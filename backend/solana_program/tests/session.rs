@@ -0,0 +1,321 @@
+//! Tests of the session keys.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction::{self, SESSION_ACCEPT_CONNECTION, SESSION_ALL, SESSION_UPDATE_PROFILE},
+        pda::{find_connection_pda, find_session_pda, find_user_pda, SESSION_PDA_SIZE},
+        state::{Connection, ConnectionStatus, Session, UserProfile},
+    },
+    common::*,
+    solana_program::{instruction::Instruction, pubkey::Pubkey},
+    solana_program_test::ProgramTestContext,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+const EXPIRES_AT_SLOT: u64 = 1000;
+
+async fn create_session(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+    owner: &Keypair,
+    delegate: &Pubkey,
+    allowed_instructions: u64,
+) {
+    let instruction = instruction::create_session(
+        program_id,
+        &owner.pubkey(),
+        delegate,
+        EXPIRES_AT_SLOT,
+        allowed_instructions,
+    );
+    process(context, &[instruction], &[owner]).await.unwrap();
+}
+
+fn update_bio(program_id: &Pubkey, owner: &Pubkey, delegate: &Pubkey, bio: &str) -> Instruction {
+    let instruction =
+        instruction::update_profile(program_id, owner, username(owner), bio.to_string(), vec![]);
+    instruction::with_session(instruction, owner, delegate)
+}
+
+#[tokio::test]
+async fn delegate_updates_profile() {
+    let owner = Keypair::new();
+    let delegate = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    create_session(
+        &mut context,
+        &program_id,
+        &owner,
+        &delegate.pubkey(),
+        SESSION_UPDATE_PROFILE,
+    )
+    .await;
+
+    let (session_pda, bump_seed) =
+        find_session_pda(&program_id, &owner.pubkey(), &delegate.pubkey());
+    let account = get_account(&mut context, &session_pda).await.unwrap();
+    assert_eq!(rent_lamports_for(SESSION_PDA_SIZE), account.lamports);
+    assert_eq!(
+        Session::new(
+            owner.pubkey(),
+            delegate.pubkey(),
+            bump_seed,
+            EXPIRES_AT_SLOT,
+            SESSION_UPDATE_PROFILE
+        ),
+        Session::unpack(&account.data).unwrap()
+    );
+
+    // Only the delegate signs, it doesn't even need an account.
+    let instruction = update_bio(
+        &program_id,
+        &owner.pubkey(),
+        &delegate.pubkey(),
+        "Looking for hiking buddies",
+    );
+    process(&mut context, &[instruction], &[&delegate])
+        .await
+        .unwrap();
+
+    let (pda, _) = find_user_pda(&program_id, &owner.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    let profile = UserProfile::unpack(&account.data).unwrap();
+    assert_eq!("Looking for hiking buddies", profile.bio);
+}
+
+#[tokio::test]
+async fn delegate_accepts_connection() {
+    let requester = Keypair::new();
+    let recipient = Keypair::new();
+    let delegate = Keypair::new();
+    let (mut context, program_id) = setup(&[&requester, &recipient]).await;
+    initialize_users(&mut context, &program_id, &[&requester, &recipient]).await;
+    create_session(
+        &mut context,
+        &program_id,
+        &recipient,
+        &delegate.pubkey(),
+        SESSION_ACCEPT_CONNECTION,
+    )
+    .await;
+    let instruction =
+        instruction::request_connection(&program_id, &requester.pubkey(), &recipient.pubkey());
+    process(&mut context, &[instruction], &[&requester])
+        .await
+        .unwrap();
+
+    let instruction = instruction::with_session(
+        instruction::accept_connection(&program_id, &recipient.pubkey(), &requester.pubkey()),
+        &recipient.pubkey(),
+        &delegate.pubkey(),
+    );
+    process(&mut context, &[instruction], &[&delegate])
+        .await
+        .unwrap();
+
+    let (pda, _) = find_connection_pda(&program_id, &requester.pubkey(), &recipient.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    assert_eq!(
+        ConnectionStatus::Accepted,
+        Connection::unpack(&account.data).unwrap().status
+    );
+}
+
+#[tokio::test]
+async fn delegate_limited_to_allowed_instructions() {
+    let requester = Keypair::new();
+    let recipient = Keypair::new();
+    let delegate = Keypair::new();
+    let (mut context, program_id) = setup(&[&requester, &recipient]).await;
+    initialize_users(&mut context, &program_id, &[&requester, &recipient]).await;
+    create_session(
+        &mut context,
+        &program_id,
+        &recipient,
+        &delegate.pubkey(),
+        SESSION_UPDATE_PROFILE,
+    )
+    .await;
+    let instruction =
+        instruction::request_connection(&program_id, &requester.pubkey(), &recipient.pubkey());
+    process(&mut context, &[instruction], &[&requester])
+        .await
+        .unwrap();
+
+    let instruction = instruction::with_session(
+        instruction::accept_connection(&program_id, &recipient.pubkey(), &requester.pubkey()),
+        &recipient.pubkey(),
+        &delegate.pubkey(),
+    );
+    let err = process(&mut context, &[instruction], &[&delegate])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+}
+
+#[tokio::test]
+async fn session_of_another_delegate() {
+    let owner = Keypair::new();
+    let delegate = Keypair::new();
+    let attacker = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    create_session(
+        &mut context,
+        &program_id,
+        &owner,
+        &delegate.pubkey(),
+        SESSION_ALL,
+    )
+    .await;
+
+    // The attacker passes the session of the real delegate.
+    let mut instruction = update_bio(&program_id, &owner.pubkey(), &delegate.pubkey(), "Hacked");
+    instruction.accounts[0].pubkey = attacker.pubkey();
+    let err = process(&mut context, &[instruction], &[&attacker])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+}
+
+#[tokio::test]
+async fn session_expires() {
+    let owner = Keypair::new();
+    let delegate = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    create_session(
+        &mut context,
+        &program_id,
+        &owner,
+        &delegate.pubkey(),
+        SESSION_ALL,
+    )
+    .await;
+
+    context.warp_to_slot(EXPIRES_AT_SLOT).unwrap();
+    let instruction = update_bio(&program_id, &owner.pubkey(), &delegate.pubkey(), "Too late");
+    let err = process(&mut context, &[instruction], &[&delegate])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::SessionExpired), err);
+
+    // Creating the session again renews it.
+    let instruction = instruction::create_session(
+        &program_id,
+        &owner.pubkey(),
+        &delegate.pubkey(),
+        2 * EXPIRES_AT_SLOT,
+        SESSION_ALL,
+    );
+    process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap();
+    let instruction = update_bio(&program_id, &owner.pubkey(), &delegate.pubkey(), "Renewed");
+    process(&mut context, &[instruction], &[&delegate])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn create_invalid_session() {
+    let owner = Keypair::new();
+    let delegate = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+
+    // Resize moves the owner's lamports, it can't be delegated.
+    let resize_flag = 1 << 4;
+    for (expires_at_slot, allowed_instructions) in [
+        (EXPIRES_AT_SLOT, SESSION_UPDATE_PROFILE | resize_flag),
+        (EXPIRES_AT_SLOT, 0),
+        (0, SESSION_ALL),
+    ] {
+        let instruction = instruction::create_session(
+            &program_id,
+            &owner.pubkey(),
+            &delegate.pubkey(),
+            expires_at_slot,
+            allowed_instructions,
+        );
+        let err = process(&mut context, &[instruction], &[&owner])
+            .await
+            .unwrap_err();
+        assert_eq!(program_error(ErrorCause::InvalidInstructionData), err);
+    }
+}
+
+#[tokio::test]
+async fn delegate_revokes_session() {
+    let owner = Keypair::new();
+    let delegate = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    create_session(
+        &mut context,
+        &program_id,
+        &owner,
+        &delegate.pubkey(),
+        SESSION_ALL,
+    )
+    .await;
+
+    let instruction = instruction::revoke_session(
+        &program_id,
+        &delegate.pubkey(),
+        &owner.pubkey(),
+        &delegate.pubkey(),
+    );
+    process(&mut context, &[instruction], &[&delegate])
+        .await
+        .unwrap();
+
+    // The owner got the rent back.
+    let wallet_lamports = context
+        .banks_client
+        .get_balance(owner.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        wallet_lamports
+    );
+    let instruction = update_bio(&program_id, &owner.pubkey(), &delegate.pubkey(), "Revoked");
+    let err = process(&mut context, &[instruction], &[&delegate])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::AccountNotFound), err);
+}
+
+#[tokio::test]
+async fn revoke_session_not_a_party() {
+    let owner = Keypair::new();
+    let delegate = Keypair::new();
+    let attacker = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+    create_session(
+        &mut context,
+        &program_id,
+        &owner,
+        &delegate.pubkey(),
+        SESSION_ALL,
+    )
+    .await;
+
+    let mut instruction = instruction::revoke_session(
+        &program_id,
+        &owner.pubkey(),
+        &owner.pubkey(),
+        &delegate.pubkey(),
+    );
+    instruction.accounts[0].pubkey = attacker.pubkey();
+    let err = process(&mut context, &[instruction], &[&attacker])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::Unauthorized), err);
+}
@@ -0,0 +1,186 @@
+//! Tests of the username claims.
+
+mod common;
+
+use {
+    anti_loneliness_solana_program::{
+        error::ErrorCause,
+        instruction,
+        pda::{find_user_pda, find_username_pda},
+        state::{UserProfile, UsernameClaim},
+    },
+    common::*,
+    solana_sdk::signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn initialize_claims_username() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    let (username_pda, bump_seed) = find_username_pda(&program_id, &username(&wallet.pubkey()));
+    let account = get_account(&mut context, &username_pda).await.unwrap();
+    assert_eq!(program_id, account.owner);
+    assert_eq!(username_rent_lamports(), account.lamports);
+    let claim = UsernameClaim::unpack(&account.data).unwrap();
+    assert_eq!(wallet.pubkey(), claim.owner);
+    assert_eq!(
+        find_user_pda(&program_id, &wallet.pubkey()).0,
+        claim.user_pda
+    );
+    assert_eq!(bump_seed, claim.bump_seed);
+}
+
+#[tokio::test]
+async fn initialize_taken_username() {
+    let owner = Keypair::new();
+    let impostor = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &impostor]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+
+    // The username differs only in case.
+    let instruction = instruction::initialize(
+        &program_id,
+        &impostor.pubkey(),
+        rent_lamports() + username_rent_lamports(),
+        username(&owner.pubkey()).to_uppercase(),
+    );
+    let err = process(&mut context, &[instruction], &[&impostor])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::UsernameTaken), err);
+    let (pda, _) = find_user_pda(&program_id, &impostor.pubkey());
+    assert!(get_account(&mut context, &pda).await.is_none());
+}
+
+#[tokio::test]
+async fn claim_new_username() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+    let old_username = username(&wallet.pubkey());
+
+    let instruction = instruction::claim_username(
+        &program_id,
+        &wallet.pubkey(),
+        &old_username,
+        "Paulinka".to_string(),
+    );
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+
+    let (pda, _) = find_user_pda(&program_id, &wallet.pubkey());
+    let account = get_account(&mut context, &pda).await.unwrap();
+    let profile = UserProfile::unpack(&account.data).unwrap();
+    assert_eq!("Paulinka", profile.username);
+    let (username_pda, _) = find_username_pda(&program_id, "paulinka");
+    let account = get_account(&mut context, &username_pda).await.unwrap();
+    assert_eq!(
+        wallet.pubkey(),
+        UsernameClaim::unpack(&account.data).unwrap().owner
+    );
+    // The old username is released, the wallet paid for a single claim.
+    let (old_username_pda, _) = find_username_pda(&program_id, &old_username);
+    assert!(get_account(&mut context, &old_username_pda).await.is_none());
+    let wallet_lamports = context
+        .banks_client
+        .get_balance(wallet.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        WALLET_LAMPORTS - rent_lamports() - username_rent_lamports(),
+        wallet_lamports
+    );
+
+    // Changing only the case keeps the claim.
+    let instruction = instruction::claim_username(
+        &program_id,
+        &wallet.pubkey(),
+        "Paulinka",
+        "paulinka".to_string(),
+    );
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+    let account = get_account(&mut context, &pda).await.unwrap();
+    let profile = UserProfile::unpack(&account.data).unwrap();
+    assert_eq!("paulinka", profile.username);
+    assert!(get_account(&mut context, &username_pda).await.is_some());
+}
+
+#[tokio::test]
+async fn claim_taken_username() {
+    let owner = Keypair::new();
+    let impostor = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &impostor]).await;
+    initialize_users(&mut context, &program_id, &[&owner, &impostor]).await;
+
+    let instruction = instruction::claim_username(
+        &program_id,
+        &impostor.pubkey(),
+        &username(&impostor.pubkey()),
+        username(&owner.pubkey()),
+    );
+    let err = process(&mut context, &[instruction], &[&impostor])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::UsernameTaken), err);
+}
+
+#[tokio::test]
+async fn update_profile_keeps_username() {
+    let wallet = Keypair::new();
+    let (mut context, program_id) = setup(&[&wallet]).await;
+    initialize_users(&mut context, &program_id, &[&wallet]).await;
+
+    // A different username must be claimed.
+    let instruction = instruction::update_profile(
+        &program_id,
+        &wallet.pubkey(),
+        "paulinka".to_string(),
+        String::new(),
+        vec![],
+    );
+    let err = process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(program_error(ErrorCause::InvalidProfileData), err);
+
+    let instruction = instruction::update_profile(
+        &program_id,
+        &wallet.pubkey(),
+        username(&wallet.pubkey()).to_uppercase(),
+        "Looking for hiking buddies".to_string(),
+        vec![],
+    );
+    process(&mut context, &[instruction], &[&wallet])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn close_account_releases_username() {
+    let owner = Keypair::new();
+    let newcomer = Keypair::new();
+    let (mut context, program_id) = setup(&[&owner, &newcomer]).await;
+    initialize_users(&mut context, &program_id, &[&owner]).await;
+
+    let instruction =
+        instruction::close_account(&program_id, &owner.pubkey(), &username(&owner.pubkey()));
+    process(&mut context, &[instruction], &[&owner])
+        .await
+        .unwrap();
+
+    // Someone else can take the username now.
+    let instruction = instruction::initialize(
+        &program_id,
+        &newcomer.pubkey(),
+        rent_lamports() + username_rent_lamports(),
+        username(&owner.pubkey()),
+    );
+    process(&mut context, &[instruction], &[&newcomer])
+        .await
+        .unwrap();
+}
@@ -29,10 +29,10 @@ impl Config {
     }
 }
 
-#[derive(Clone)]
 pub struct AppStateBuiler {
     user_repo: Option<repo::user::Repo>,
     solana_repo: Option<repo::solana::Repo>,
+    authority_keypair: Option<Keypair>,
 }
 
 impl AppStateBuiler {
@@ -40,6 +40,7 @@ impl AppStateBuiler {
         AppStateBuiler {
             solana_repo: None,
             user_repo: None,
+            authority_keypair: None,
         }
     }
 
@@ -56,6 +57,12 @@ impl AppStateBuiler {
         return self;
     }
 
+    // Signer of the attestations, a new keypair if not given.
+    pub fn with_authority_keypair(&mut self, authority_keypair: Keypair) -> &mut AppStateBuiler {
+        self.authority_keypair = Some(authority_keypair);
+        self
+    }
+
     pub fn build(
         &mut self,
         cfg: Config,
@@ -67,6 +74,7 @@ impl AppStateBuiler {
         let solana_service = solana_service::SolanaService::new(
             cfg.solana_service_config.clone(),
             program_keypair,
+            self.authority_keypair.take().unwrap_or(Keypair::new()),
             self.solana_repo.take().unwrap_or(repo::solana::Repo::new()),
         );
         let user_service = user_service::UserService::new(
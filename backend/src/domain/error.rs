@@ -1,7 +1,16 @@
+use solana_sdk::pubkey::Pubkey;
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum Error {
     GeneralError(String),
     InvalidPubKey(String),
+    InvalidUsername(String),
+    // another user has already claimed this username
+    UsernameTaken,
+    InvalidProfile(String),
+    InvalidEvent(String),
+    // no user has claimed the username used as the invite code
+    InvalidInviteCode(String),
     UserNotFound,
     UserNotConfirmed,
     InvalidAuthToken,
@@ -13,10 +22,26 @@ pub enum Error {
     // transaction data is invalid or a signature is missing
     InvalidTransaction(String),
     TransactionExpired,
+    // the program has been paused by its admin
+    ProgramPaused,
+    // the session of the delegate signing for the user has expired
+    SessionExpired,
+    // one of the users has blocked the other one
+    Blocked,
     // Solana account with this pubkey doesn't exist
     WalletNotFound,
     // User's solana account has insufficient founds for this operation
     WalletInsufficientFounds,
+    // Errors returned by the program, with the account that caused them
+    AccountNotFound(Option<Pubkey>),
+    AccountAlreadyInitialized(Option<Pubkey>),
+    AccountDataTooSmall(Option<Pubkey>),
+    // Account passed to the program didn't pass validation
+    InvalidAccount(String, Option<Pubkey>),
+    // Account is in a state that doesn't allow this operation
+    InvalidAccountState(Option<Pubkey>),
+    // Signer is not allowed to modify the account
+    Unauthorized(Option<Pubkey>),
 }
 
 impl From<solana_sdk::pubkey::ParsePubkeyError> for Error {
@@ -54,21 +79,49 @@ impl std::fmt::Display for Error {
         match self {
             Error::GeneralError(msg) => write!(f, "GeneralError: {}", msg),
             Error::InvalidPubKey(msg) => write!(f, "InvalidPubkey: {}", msg),
+            Error::InvalidUsername(username) => write!(f, "InvalidUsername: {}", username),
+            Error::UsernameTaken => write!(f, "UsernameTaken"),
+            Error::InvalidProfile(msg) => write!(f, "InvalidProfile: {}", msg),
+            Error::InvalidEvent(msg) => write!(f, "InvalidEvent: {}", msg),
+            Error::InvalidInviteCode(code) => write!(f, "InvalidInviteCode: {}", code),
             Error::UserNotFound => write!(f, "UserNotFound"),
             Error::UserAlreadyInitialized => write!(f, "UserAlreadyInitialized"),
             Error::TransactionNotFound => write!(f, "TransactionNotFound"),
             Error::InvalidTransaction(msg) => write!(f, "InvalidTransaction: {}", msg),
             Error::TransactionExpired => write!(f, "TransactionExpired"),
+            Error::ProgramPaused => write!(f, "ProgramPaused"),
+            Error::SessionExpired => write!(f, "SessionExpired"),
+            Error::Blocked => write!(f, "Blocked"),
             Error::WalletNotFound => write!(f, "WalletNotFound"),
             Error::WalletInsufficientFounds => write!(f, "WalletInsufficientFounds"),
             Error::UserNotConfirmed => write!(f, "UserNotConfirmed"),
             Error::InvalidAuthToken => write!(f, "InvalidAuthToken"),
             Error::AuthTokenExpired => write!(f, "AuthTokenExpired"),
             Error::InvalidSignature => write!(f, "InvalidSignature"),
+            Error::AccountNotFound(account) => {
+                write!(f, "AccountNotFound{}", fmt_account(account))
+            }
+            Error::AccountAlreadyInitialized(account) => {
+                write!(f, "AccountAlreadyInitialized{}", fmt_account(account))
+            }
+            Error::AccountDataTooSmall(account) => {
+                write!(f, "AccountDataTooSmall{}", fmt_account(account))
+            }
+            Error::InvalidAccount(cause, account) => {
+                write!(f, "InvalidAccount{}: {}", fmt_account(account), cause)
+            }
+            Error::InvalidAccountState(account) => {
+                write!(f, "InvalidAccountState{}", fmt_account(account))
+            }
+            Error::Unauthorized(account) => write!(f, "Unauthorized{}", fmt_account(account)),
         }
     }
 }
 
+fn fmt_account(account: &Option<Pubkey>) -> String {
+    account.map(|a| format!(" {}", a)).unwrap_or_default()
+}
+
 impl std::error::Error for Error {
     fn cause(&self) -> Option<&dyn std::error::Error> {
         None
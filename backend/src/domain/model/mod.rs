@@ -12,6 +12,101 @@ pub struct User {
 
     pub pda_pubkey: Option<Pubkey>, // completed the registration process
     pub refresh_token: Option<RefreshToken>,
+    pub pending_transfer: Option<PendingTransfer>,
+}
+
+// Transfer of the profile to a new wallet prepared for the user to sign,
+// only the transaction prepared for it completes the transfer.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PendingTransfer {
+    pub new_pubkey: Pubkey,
+    pub transaction_id: Uuid,
+    pub created_at_slot: u64, // of the profile, kept when it moves
+}
+
+// Profile fields to be changed, `None` keeps the current value.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ProfileUpdate {
+    pub username: Option<String>,
+    pub bio: Option<String>,
+    pub interests: Option<Vec<String>>,
+}
+
+// Endorsements of a user, as stored on-chain.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Endorsements {
+    pub count: u64, // kept in the user's profile
+    pub received: Vec<Pubkey>,
+    pub given: Vec<Pubkey>,
+}
+
+// Group stored on-chain in the group PDA.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Group {
+    pub pubkey: Pubkey, // group PDA
+    pub admin: Pubkey,
+    pub name: String,
+    pub invite_only: bool,
+    pub max_members: u32,
+    pub member_count: u32,
+}
+
+// Meetup event stored on-chain in the event PDA.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Event {
+    pub pubkey: Pubkey, // event PDA
+    pub organizer: Pubkey,
+    pub title: String,
+    pub starts_at: i64, // unix timestamp
+    pub location_hint: String,
+    pub capacity: u32,
+    pub attendee_count: u32,
+    pub closed: bool, // takes no more RSVPs
+}
+
+// Poll stored on-chain in the poll PDA.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Poll {
+    pub pubkey: Pubkey, // poll PDA
+    pub creator: Pubkey,
+    pub group: Option<Pubkey>, // only its members can vote
+    pub question: String,
+    pub options: Vec<PollOption>,
+    pub closed: bool, // takes no more votes
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PollOption {
+    pub text: String,
+    pub votes: u32,
+}
+
+// Guardians of a user and the recovery pending for the user PDA, as stored on-chain.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct RecoveryStatus {
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8, // approvals needed to execute a recovery
+    pub pending: Option<PendingRecovery>,
+}
+
+// Recovery proposed by a guardian, stored on-chain in the recovery PDA.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PendingRecovery {
+    pub pubkey: Pubkey, // recovery PDA
+    pub new_owner: Pubkey,
+    pub proposer: Pubkey,
+    pub approvals: Vec<Pubkey>, // only the ones of current guardians
+    pub approval_count: usize,  // counted as by the program executing the recovery
+    pub executable_at_slot: u64,
+}
+
+// Event to be organized by the user.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct NewEvent {
+    pub title: String,
+    pub starts_at: i64, // unix timestamp
+    pub location_hint: String,
+    pub capacity: u32,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug)]
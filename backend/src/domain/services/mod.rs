@@ -1,2 +1,3 @@
+pub mod program_events;
 pub mod solana_service;
 pub mod user_service;
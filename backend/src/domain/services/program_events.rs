@@ -0,0 +1,103 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_sdk::pubkey::Pubkey;
+
+pub use anti_loneliness_program_interface::event::*;
+
+/// Parses the events logged by the program out of the transaction log messages.
+/// Data logged by other programs, including the ones invoked by our program, is skipped.
+pub fn decode_program_events(program_id: &Pubkey, logs: &[String]) -> Vec<ProgramEvent> {
+    let program_id = program_id.to_string();
+    // Programs currently executing, the last one is logging.
+    let mut invoked: Vec<&str> = vec![];
+    let mut events = vec![];
+
+    for log in logs {
+        let Some(log) = log.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = log.strip_prefix("data: ") {
+            if invoked.last() == Some(&program_id.as_str()) {
+                match decode_event(data) {
+                    Some(event) => events.push(event),
+                    None => println!("Failed to decode program event: {}", data),
+                }
+            }
+            continue;
+        }
+
+        let mut words = log.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some(program), Some("invoke")) => invoked.push(program),
+            (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                invoked.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+// The event is logged as its discriminator and body, both base64 encoded.
+fn decode_event(data: &str) -> Option<ProgramEvent> {
+    let fields = data
+        .split_whitespace()
+        .map(|field| STANDARD.decode(field).ok())
+        .collect::<Option<Vec<Vec<u8>>>>()?;
+    match fields.as_slice() {
+        [discriminator, body] => ProgramEvent::unpack(discriminator, body).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_log(event: &ProgramEvent) -> String {
+        format!(
+            "Program data: {} {}",
+            STANDARD.encode(event.discriminator()),
+            STANDARD.encode(event.pack().unwrap())
+        )
+    }
+
+    #[test]
+    fn test_decode_program_events() {
+        let program_id = Pubkey::new_unique();
+        let system_program = solana_sdk::system_program::ID;
+        let owner = Pubkey::new_unique();
+        let initialized = ProgramEvent::UserInitialized(UserInitialized {
+            owner,
+            user_pda: Pubkey::new_unique(),
+            username: "paulinka".to_string(),
+        });
+        let resized = ProgramEvent::UserPdaResized(UserPdaResized {
+            owner,
+            new_size: 2048,
+        });
+
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            format!("Program {} invoke [2]", system_program),
+            // Logged by an invoked program, not ours.
+            data_log(&resized),
+            format!("Program {} success", system_program),
+            data_log(&initialized),
+            format!(
+                "Program {} consumed 1000 of 200000 compute units",
+                program_id
+            ),
+            format!("Program {} success", program_id),
+            format!("Program {} invoke [1]", program_id),
+            "Program data: dW5rbm93biE= AA==".to_string(),
+            data_log(&resized),
+            format!("Program {} success", program_id),
+        ];
+
+        assert_eq!(
+            vec![initialized, resized],
+            decode_program_events(&program_id, &logs)
+        );
+        assert!(decode_program_events(&Pubkey::new_unique(), &logs).is_empty());
+    }
+}
@@ -1,13 +1,23 @@
-mod instruction;
-use std::{sync::Arc, time::SystemTime};
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant, SystemTime},
+};
 
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
 use solana_sdk::{
+    account::Account,
     commitment_config::CommitmentConfig,
-    instruction::{AccountMeta, Instruction},
+    instruction::{Instruction, InstructionError},
     message::Message,
     pubkey::Pubkey,
     signature::Keypair,
     signer::Signer,
+    system_instruction::SystemError,
     sysvar::recent_blockhashes,
     transaction::Transaction,
     transaction::TransactionError,
@@ -15,33 +25,54 @@ use solana_sdk::{
 
 use uuid::Uuid;
 
+pub use anti_loneliness_program_interface::state;
+use anti_loneliness_program_interface::{error as program_error, instruction, pda};
+
 use crate::{
     domain::{
         error::Error,
-        model::{TransactionRecord, TransactionToSign},
+        model::{
+            Endorsements, Event, Group, NewEvent, PendingRecovery, Poll, PollOption,
+            RecoveryStatus, TransactionRecord, TransactionToSign,
+        },
     },
     repo::solana::Repo,
 };
 
-const USER_PDA_PREFIX: &[u8] = b"user";
+use super::program_events;
+
+// Offsets of the users in the endorsement account data,
+// right after the discriminator and the version.
+const ENDORSER_OFFSET: usize = 9;
+const ENDORSED_OFFSET: usize = ENDORSER_OFFSET + 32;
+// Offsets of the group and the member in the membership account data.
+const MEMBERSHIP_GROUP_OFFSET: usize = 9;
+const MEMBERSHIP_MEMBER_OFFSET: usize = MEMBERSHIP_GROUP_OFFSET + 32;
+// Offsets of the event and the attendee in the RSVP account data.
+const RSVP_EVENT_OFFSET: usize = 9;
+const RSVP_ATTENDEE_OFFSET: usize = RSVP_EVENT_OFFSET + 32;
+// Offset of the group in the poll account data, after the creator, the poll id,
+// the bump seed and the tag of the optional group.
+const POLL_GROUP_OFFSET: usize = 9 + 32 + 8 + 1 + 1;
 
 #[derive(Clone)]
 pub struct Config {
-    pub user_pda_size: usize,
     pub rpc_client_url: String,
     pub commitment_config: CommitmentConfig,
     pub timeout_sec: u64,
     pub transaction_validity_sec: u32,
+    // The program config is read again once it's older than this
+    pub program_config_ttl_sec: u64,
 }
 
 impl Config {
     pub fn default() -> Config {
         Config {
-            user_pda_size: 1024,
             rpc_client_url: "http://localhost:8899".to_string(),
             commitment_config: CommitmentConfig::confirmed(),
             timeout_sec: 5,
             transaction_validity_sec: 3600,
+            program_config_ttl_sec: 10,
         }
     }
 }
@@ -50,12 +81,16 @@ impl Config {
 pub struct SolanaService {
     cfg: Config,
     program: Arc<Keypair>,
+    // Signs the attestations, set as the attestation authority in the program config
+    authority: Arc<Keypair>,
     client: Arc<solana_client::rpc_client::RpcClient>,
     repo: Arc<Repo>,
+    // Config of the program and when it was read, cached for the TTL
+    program_config: Arc<RwLock<Option<(state::ProgramConfig, Instant)>>>,
 }
 
 impl SolanaService {
-    pub fn new(cfg: Config, program: Keypair, repo: Repo) -> SolanaService {
+    pub fn new(cfg: Config, program: Keypair, authority: Keypair, repo: Repo) -> SolanaService {
         let timeout = std::time::Duration::from_secs(cfg.timeout_sec);
         let solana_client = solana_client::rpc_client::RpcClient::new_with_timeout_and_commitment(
             &cfg.rpc_client_url,
@@ -65,14 +100,55 @@ impl SolanaService {
         SolanaService {
             cfg: cfg,
             program: Arc::new(program),
+            authority: Arc::new(authority),
             client: Arc::new(solana_client),
             repo: Arc::new(repo),
+            program_config: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Reads the program config stored on-chain and caches it for building the transactions.
+    /// The default config applies until the config PDA is initialized.
+    pub fn load_program_config(&self) -> Result<state::ProgramConfig, Error> {
+        let (config_pda, _) = pda::find_config_pda(&self.program.pubkey());
+        let account = self
+            .client
+            .get_account_with_commitment(&config_pda, self.cfg.commitment_config)?
+            .value;
+        let config = match account {
+            Some(account) if account.owner.eq(&self.program.pubkey()) => {
+                state::ProgramConfig::unpack(&account.data).map_err(|err| {
+                    Error::GeneralError(format!("Failed to decode program config: {}", err))
+                })?
+            }
+            _ => state::ProgramConfig::default(),
+        };
+        *self.program_config.write().unwrap() = Some((config.clone(), Instant::now()));
+        Ok(config)
+    }
+
+    // The admin can pause the program or change the fees at any time,
+    // the cached config is only used while it's fresh.
+    fn program_config(&self) -> Result<state::ProgramConfig, Error> {
+        if let Some((config, loaded_at)) = &*self.program_config.read().unwrap() {
+            if loaded_at.elapsed() < Duration::from_secs(self.cfg.program_config_ttl_sec) {
+                return Ok(config.clone());
+            }
+        }
+        self.load_program_config()
+    }
+
+    pub fn authority_pubkey(&self) -> Pubkey {
+        self.authority.pubkey()
+    }
+
+    /// Parses the events logged by the program out of the transaction log messages.
+    pub fn decode_events(&self, logs: &[String]) -> Vec<program_events::ProgramEvent> {
+        program_events::decode_program_events(&self.program.pubkey(), logs)
+    }
+
     pub fn get_user_pda(&self, wallet_pubkey: &Pubkey) -> Pubkey {
-        let seeds = &[USER_PDA_PREFIX, wallet_pubkey.as_ref()];
-        let (pda_pubkey, _) = Pubkey::find_program_address(seeds, &self.program.pubkey());
+        let (pda_pubkey, _) = pda::find_user_pda(&self.program.pubkey(), wallet_pubkey);
         pda_pubkey
     }
 
@@ -94,40 +170,731 @@ impl SolanaService {
         return Ok(transaction_record);
     }
 
-    pub fn create_user_pda(&self, wallet_pubkey: &Pubkey) -> Result<TransactionToSign, Error> {
-        let account_size = self.cfg.user_pda_size;
+    /// Reads the user profile stored on-chain in the user's PDA.
+    pub fn get_user_profile(&self, wallet_pubkey: &Pubkey) -> Result<state::UserProfile, Error> {
+        let account = self.get_user_account(wallet_pubkey)?;
+        let profile = state::UserProfile::unpack(&account.data).map_err(|err| {
+            Error::GeneralError(format!("Failed to decode user profile: {}", err))
+        })?;
+        if profile.owner.ne(wallet_pubkey) {
+            return Err(Error::GeneralError(
+                "User profile is owned by a different wallet".to_string(),
+            ));
+        }
+        Ok(profile)
+    }
 
-        let lamports = self
+    fn get_user_account(&self, wallet_pubkey: &Pubkey) -> Result<Account, Error> {
+        let pda_pubkey = self.get_user_pda(wallet_pubkey);
+        let account = self
             .client
-            .get_minimum_balance_for_rent_exemption(account_size)?;
-
-        // Derive the PDA from the payer account, a string representing the unique
-        // purpose of the account, and the address of our on-chain program.
-        let seeds = &[USER_PDA_PREFIX, wallet_pubkey.as_ref()];
-        let (pda_pubkey, pda_bump_seed) =
-            Pubkey::find_program_address(seeds, &self.program.pubkey());
-        // println!("PDA pubkey: {}", pda_pubkey);
-
-        let instr_data =
-            instruction::ProgramInstruction::Initialize(instruction::InitializeInstructionData {
-                lamports,
-                pda_bump_seed,
+            .get_account_with_commitment(&pda_pubkey, self.cfg.commitment_config)?
+            .value
+            .ok_or(Error::UserNotFound)?;
+        if account.owner.ne(&self.program.pubkey()) {
+            return Err(Error::UserNotFound);
+        }
+        Ok(account)
+    }
+
+    /// Reads the endorsements of the user: the count kept in the profile
+    /// and the endorsement PDAs received and given by the user.
+    pub fn get_endorsements(&self, wallet_pubkey: &Pubkey) -> Result<Endorsements, Error> {
+        let profile = self.get_user_profile(wallet_pubkey)?;
+        let received = self
+            .find_endorsements(ENDORSED_OFFSET, wallet_pubkey)?
+            .into_iter()
+            .map(|endorsement| endorsement.endorser)
+            .collect();
+        let given = self
+            .find_endorsements(ENDORSER_OFFSET, wallet_pubkey)?
+            .into_iter()
+            .map(|endorsement| endorsement.endorsed)
+            .collect();
+        Ok(Endorsements {
+            count: profile.endorsement_count,
+            received,
+            given,
+        })
+    }
+
+    // Finds the endorsement PDAs with `pubkey` stored at `offset`.
+    fn find_endorsements(
+        &self,
+        offset: usize,
+        pubkey: &Pubkey,
+    ) -> Result<Vec<state::Endorsement>, Error> {
+        self.find_program_accounts(
+            pda::ENDORSEMENT_PDA_SIZE,
+            &state::ENDORSEMENT_DISCRIMINATOR,
+            Some((offset, pubkey)),
+        )?
+        .into_iter()
+        .map(|(_, account)| {
+            state::Endorsement::unpack(&account.data).map_err(|err| {
+                Error::GeneralError(format!("Failed to decode endorsement: {}", err))
             })
-            .pack()?;
-
-        // The accounts required by both our on-chain program and the system program's
-        // `create_account` instruction, including the vault's address.
-        let accounts = vec![
-            AccountMeta::new(*wallet_pubkey, true /* is_signer */),
-            AccountMeta::new(pda_pubkey, false),
-            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+        })
+        .collect()
+    }
+
+    /// Reads the groups the user is a member of.
+    pub fn get_user_groups(&self, wallet_pubkey: &Pubkey) -> Result<Vec<Group>, Error> {
+        let group_pubkeys: Vec<Pubkey> = self
+            .find_memberships(MEMBERSHIP_MEMBER_OFFSET, wallet_pubkey)?
+            .into_iter()
+            .map(|membership| membership.group)
+            .collect();
+        if group_pubkeys.is_empty() {
+            return Ok(vec![]);
+        }
+        let accounts = self
+            .client
+            .get_multiple_accounts_with_commitment(&group_pubkeys, self.cfg.commitment_config)?
+            .value;
+        group_pubkeys
+            .iter()
+            .zip(accounts)
+            // Skip a group that disappeared between the two reads.
+            .filter_map(|(pubkey, account)| account.map(|account| (pubkey, account)))
+            .map(|(pubkey, account)| decode_group(pubkey, &account))
+            .collect()
+    }
+
+    /// Reads the group stored in the group PDA.
+    pub fn get_group(&self, group_pubkey: &Pubkey) -> Result<Group, Error> {
+        let account = self
+            .client
+            .get_account_with_commitment(group_pubkey, self.cfg.commitment_config)?
+            .value
+            .filter(|account| account.owner.eq(&self.program.pubkey()))
+            .ok_or(Error::AccountNotFound(Some(*group_pubkey)))?;
+        decode_group(group_pubkey, &account)
+    }
+
+    /// Reads the wallets of the group members.
+    pub fn get_group_members(&self, group_pubkey: &Pubkey) -> Result<Vec<Pubkey>, Error> {
+        Ok(self
+            .find_memberships(MEMBERSHIP_GROUP_OFFSET, group_pubkey)?
+            .into_iter()
+            .map(|membership| membership.member)
+            .collect())
+    }
+
+    // Finds the membership PDAs with `pubkey` stored at `offset`.
+    fn find_memberships(
+        &self,
+        offset: usize,
+        pubkey: &Pubkey,
+    ) -> Result<Vec<state::Membership>, Error> {
+        self.find_program_accounts(
+            pda::MEMBERSHIP_PDA_SIZE,
+            &state::MEMBERSHIP_DISCRIMINATOR,
+            Some((offset, pubkey)),
+        )?
+        .into_iter()
+        .map(|(_, account)| {
+            state::Membership::unpack(&account.data)
+                .map_err(|err| Error::GeneralError(format!("Failed to decode membership: {}", err)))
+        })
+        .collect()
+    }
+
+    /// Reads the open events which haven't started yet, the earliest first.
+    pub fn get_upcoming_events(&self) -> Result<Vec<Event>, Error> {
+        let now = unix_timestamp()?;
+        let mut events = self
+            .find_program_accounts(pda::EVENT_PDA_SIZE, &state::EVENT_DISCRIMINATOR, None)?
+            .iter()
+            .map(|(pubkey, account)| decode_event(pubkey, account))
+            .collect::<Result<Vec<_>, _>>()?;
+        events.retain(|event| !event.closed && event.starts_at > now);
+        events.sort_by_key(|event| event.starts_at);
+        Ok(events)
+    }
+
+    /// Reads the events the user RSVPed to.
+    pub fn get_user_events(&self, wallet_pubkey: &Pubkey) -> Result<Vec<Event>, Error> {
+        let event_pubkeys: Vec<Pubkey> = self
+            .find_rsvps(RSVP_ATTENDEE_OFFSET, wallet_pubkey)?
+            .into_iter()
+            .map(|rsvp| rsvp.event)
+            .collect();
+        if event_pubkeys.is_empty() {
+            return Ok(vec![]);
+        }
+        let accounts = self
+            .client
+            .get_multiple_accounts_with_commitment(&event_pubkeys, self.cfg.commitment_config)?
+            .value;
+        event_pubkeys
+            .iter()
+            .zip(accounts)
+            .filter_map(|(pubkey, account)| account.map(|account| (pubkey, account)))
+            .map(|(pubkey, account)| decode_event(pubkey, &account))
+            .collect()
+    }
+
+    /// Reads the event stored in the event PDA.
+    pub fn get_event(&self, event_pubkey: &Pubkey) -> Result<Event, Error> {
+        let account = self
+            .client
+            .get_account_with_commitment(event_pubkey, self.cfg.commitment_config)?
+            .value
+            .filter(|account| account.owner.eq(&self.program.pubkey()))
+            .ok_or(Error::AccountNotFound(Some(*event_pubkey)))?;
+        decode_event(event_pubkey, &account)
+    }
+
+    /// Reads the wallets of the users who RSVPed to the event.
+    pub fn get_event_attendees(&self, event_pubkey: &Pubkey) -> Result<Vec<Pubkey>, Error> {
+        Ok(self
+            .find_rsvps(RSVP_EVENT_OFFSET, event_pubkey)?
+            .into_iter()
+            .map(|rsvp| rsvp.attendee)
+            .collect())
+    }
+
+    // Finds the RSVP PDAs with `pubkey` stored at `offset`.
+    fn find_rsvps(&self, offset: usize, pubkey: &Pubkey) -> Result<Vec<state::Rsvp>, Error> {
+        self.find_program_accounts(
+            pda::RSVP_PDA_SIZE,
+            &state::RSVP_DISCRIMINATOR,
+            Some((offset, pubkey)),
+        )?
+        .into_iter()
+        .map(|(_, account)| {
+            state::Rsvp::unpack(&account.data)
+                .map_err(|err| Error::GeneralError(format!("Failed to decode RSVP: {}", err)))
+        })
+        .collect()
+    }
+
+    /// Reads the poll stored in the poll PDA, the votes included.
+    pub fn get_poll(&self, poll_pubkey: &Pubkey) -> Result<Poll, Error> {
+        let account = self
+            .client
+            .get_account_with_commitment(poll_pubkey, self.cfg.commitment_config)?
+            .value
+            .filter(|account| account.owner.eq(&self.program.pubkey()))
+            .ok_or(Error::AccountNotFound(Some(*poll_pubkey)))?;
+        decode_poll(poll_pubkey, &account)
+    }
+
+    /// Reads the polls restricted to the members of the group.
+    pub fn get_group_polls(&self, group_pubkey: &Pubkey) -> Result<Vec<Poll>, Error> {
+        self.find_program_accounts(
+            pda::POLL_PDA_SIZE,
+            &state::POLL_DISCRIMINATOR,
+            Some((POLL_GROUP_OFFSET, group_pubkey)),
+        )?
+        .iter()
+        .map(|(pubkey, account)| decode_poll(pubkey, account))
+        .collect()
+    }
+
+    // Finds the program accounts of the given size and discriminator,
+    // optionally only the ones with the pubkey stored at the offset of the account data.
+    fn find_program_accounts(
+        &self,
+        data_size: u64,
+        discriminator: &[u8],
+        memcmp: Option<(usize, &Pubkey)>,
+    ) -> Result<Vec<(Pubkey, Account)>, Error> {
+        let mut filters = vec![
+            RpcFilterType::DataSize(data_size),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, discriminator)),
         ];
+        if let Some((offset, pubkey)) = memcmp {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                offset,
+                pubkey.as_ref(),
+            )));
+        }
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                commitment: Some(self.cfg.commitment_config),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Ok(self
+            .client
+            .get_program_accounts_with_config(&self.program.pubkey(), config)?)
+    }
+
+    /// Reads the wallets blocked by the user.
+    /// A user who has never blocked anyone has no block list PDA.
+    pub fn get_blocked_users(&self, wallet_pubkey: &Pubkey) -> Result<Vec<Pubkey>, Error> {
+        let (block_list_pda, _) = pda::find_block_list_pda(&self.program.pubkey(), wallet_pubkey);
+        let account = self
+            .client
+            .get_account_with_commitment(&block_list_pda, self.cfg.commitment_config)?
+            .value;
+        let Some(account) = account.filter(|a| a.owner.eq(&self.program.pubkey())) else {
+            return Ok(vec![]);
+        };
+        let block_list = state::BlockList::unpack(&account.data)
+            .map_err(|err| Error::GeneralError(format!("Failed to decode block list: {}", err)))?;
+        Ok(block_list.blocked)
+    }
+
+    /// Reads the guardians of the user and the recovery pending for the user PDA.
+    pub fn get_recovery_status(&self, wallet_pubkey: &Pubkey) -> Result<RecoveryStatus, Error> {
+        let profile = self.get_user_profile(wallet_pubkey)?;
+        let pending = self
+            .get_recovery(wallet_pubkey)?
+            .map(|(pubkey, recovery)| PendingRecovery {
+                pubkey,
+                new_owner: recovery.new_owner,
+                proposer: recovery.proposer,
+                executable_at_slot: recovery.executable_at_slot(),
+                approval_count: recovery.approval_count(&profile.guardians),
+                approvals: recovery
+                    .approvals
+                    .into_iter()
+                    .filter(|approval| profile.is_guardian(approval))
+                    .collect(),
+            });
+        Ok(RecoveryStatus {
+            guardians: profile.guardians,
+            threshold: profile.guardian_threshold,
+            pending,
+        })
+    }
+
+    // A user PDA without a pending recovery has no recovery PDA.
+    fn get_recovery(
+        &self,
+        wallet_pubkey: &Pubkey,
+    ) -> Result<Option<(Pubkey, state::Recovery)>, Error> {
+        let (recovery_pda, _) =
+            pda::find_recovery_pda(&self.program.pubkey(), &self.get_user_pda(wallet_pubkey));
+        let account = self
+            .client
+            .get_account_with_commitment(&recovery_pda, self.cfg.commitment_config)?
+            .value;
+        let Some(account) = account.filter(|a| a.owner.eq(&self.program.pubkey())) else {
+            return Ok(None);
+        };
+        let recovery = state::Recovery::unpack(&account.data)
+            .map_err(|err| Error::GeneralError(format!("Failed to decode recovery: {}", err)))?;
+        Ok(Some((recovery_pda, recovery)))
+    }
+
+    pub fn get_slot(&self) -> Result<u64, Error> {
+        Ok(self
+            .client
+            .get_slot_with_commitment(self.cfg.commitment_config)?)
+    }
+
+    /// Checks that no user has claimed the username yet.
+    /// Usernames differing only in case are the same username.
+    pub fn is_username_available(&self, username: &str) -> Result<bool, Error> {
+        state::validate_username(username)
+            .map_err(|_| Error::InvalidUsername(username.to_string()))?;
+        let (username_pda, _) = pda::find_username_pda(&self.program.pubkey(), username);
+        let account = self
+            .client
+            .get_account_with_commitment(&username_pda, self.cfg.commitment_config)?
+            .value;
+        Ok(account.is_none_or(|account| account.owner.ne(&self.program.pubkey())))
+    }
+
+    /// Finds the wallet of the user who claimed the username, `None` if nobody did.
+    pub fn get_username_owner(&self, username: &str) -> Result<Option<Pubkey>, Error> {
+        state::validate_username(username)
+            .map_err(|_| Error::InvalidUsername(username.to_string()))?;
+        let (username_pda, _) = pda::find_username_pda(&self.program.pubkey(), username);
+        let account = self
+            .client
+            .get_account_with_commitment(&username_pda, self.cfg.commitment_config)?
+            .value;
+        match account {
+            Some(account) if account.owner.eq(&self.program.pubkey()) => {
+                let claim = state::UsernameClaim::unpack(&account.data).map_err(|err| {
+                    Error::GeneralError(format!("Failed to decode username claim: {}", err))
+                })?;
+                Ok(Some(claim.owner))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub fn create_user_pda(
+        &self,
+        wallet_pubkey: &Pubkey,
+        username: String,
+        referrer: Option<&Pubkey>,
+    ) -> Result<TransactionToSign, Error> {
+        state::validate_username(&username)
+            .map_err(|_| Error::InvalidUsername(username.clone()))?;
+        // The username is claimed together with the user PDA.
+        // The deposit is locked in the user PDA and refunded when the account is closed.
+        let config = self.program_config()?;
+        let mut lamports = config.registration_fee
+            + config.deposit
+            + self
+                .client
+                .get_minimum_balance_for_rent_exemption(pda::USER_PDA_SIZE as usize)?
+            + self
+                .client
+                .get_minimum_balance_for_rent_exemption(pda::USERNAME_PDA_SIZE as usize)?;
+        // The new user pays for the referral PDA recording the invite.
+        if referrer.is_some() {
+            lamports += self
+                .client
+                .get_minimum_balance_for_rent_exemption(pda::REFERRAL_PDA_SIZE as usize)?;
+        }
+
+        let instruction = instruction::initialize_with_referrer(
+            &self.program.pubkey(),
+            wallet_pubkey,
+            lamports,
+            username,
+            referrer,
+        );
+        self.prepare_transaction(wallet_pubkey, &[instruction])
+    }
+
+    pub fn update_user_profile(
+        &self,
+        wallet_pubkey: &Pubkey,
+        profile: &state::UserProfile,
+    ) -> Result<TransactionToSign, Error> {
+        state::validate_username(&profile.username)
+            .map_err(|_| Error::InvalidUsername(profile.username.clone()))?;
+        state::validate_bio(&profile.bio)
+            .map_err(|_| Error::InvalidProfile("Bio is too long".to_string()))?;
+        state::validate_interests(&profile.interests)
+            .map_err(|_| Error::InvalidProfile("Invalid interests".to_string()))?;
+
+        let mut instructions = vec![];
+        let account = self.get_user_account(wallet_pubkey)?;
+        let current_profile = state::UserProfile::unpack(&account.data).map_err(|err| {
+            Error::GeneralError(format!("Failed to decode user profile: {}", err))
+        })?;
+        // A different username needs to be claimed first.
+        if state::normalize_username(&profile.username)
+            .ne(&state::normalize_username(&current_profile.username))
+        {
+            if !self.is_username_available(&profile.username)? {
+                return Err(Error::UsernameTaken);
+            }
+            instructions.push(instruction::claim_username(
+                &self.program.pubkey(),
+                wallet_pubkey,
+                &current_profile.username,
+                profile.username.clone(),
+            ));
+        }
+        // Grow the PDA first if the updated profile doesn't fit in it.
+        let max_size = self.program_config()?.max_user_pda_size;
+        if let Some(new_size) = required_pda_size(profile, account.data.len(), max_size)? {
+            instructions.push(instruction::resize(
+                &self.program.pubkey(),
+                wallet_pubkey,
+                new_size,
+            ));
+        }
+        instructions.push(instruction::update_profile(
+            &self.program.pubkey(),
+            wallet_pubkey,
+            profile.username.clone(),
+            profile.bio.clone(),
+            profile.interests.clone(),
+        ));
+        self.prepare_transaction(wallet_pubkey, &instructions)
+    }
+
+    /// Lets `delegate` sign the `allowed_instructions` of the user for the next `duration_slots`,
+    /// so that the user doesn't need to sign each of them with the wallet.
+    /// See the SESSION_* flags of the program instructions.
+    pub fn create_session(
+        &self,
+        wallet_pubkey: &Pubkey,
+        delegate: &Pubkey,
+        duration_slots: u64,
+        allowed_instructions: u64,
+    ) -> Result<TransactionToSign, Error> {
+        let expires_at_slot = self.client.get_slot()? + duration_slots;
+        let instruction = instruction::create_session(
+            &self.program.pubkey(),
+            wallet_pubkey,
+            delegate,
+            expires_at_slot,
+            allowed_instructions,
+        );
+        self.prepare_transaction(wallet_pubkey, &[instruction])
+    }
+
+    pub fn revoke_session(
+        &self,
+        wallet_pubkey: &Pubkey,
+        delegate: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let instruction = instruction::revoke_session(
+            &self.program.pubkey(),
+            wallet_pubkey,
+            wallet_pubkey,
+            delegate,
+        );
+        // A leaked delegate key can be revoked also while the program is paused.
+        self.prepare_transaction_while_paused(wallet_pubkey, &[instruction])
+    }
+
+    // The profile moves to the PDA of the new wallet, the current wallet pays for it.
+    pub fn transfer_ownership(
+        &self,
+        wallet_pubkey: &Pubkey,
+        new_wallet_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let profile = self.get_user_profile(wallet_pubkey)?;
+        let instruction = instruction::transfer_ownership(
+            &self.program.pubkey(),
+            wallet_pubkey,
+            new_wallet_pubkey,
+            &profile.username,
+        );
+        self.prepare_transaction(wallet_pubkey, &[instruction])
+    }
+
+    pub fn set_guardians(
+        &self,
+        wallet_pubkey: &Pubkey,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<TransactionToSign, Error> {
+        state::validate_guardians(wallet_pubkey, &guardians, threshold)
+            .map_err(|_| Error::InvalidProfile("Invalid guardians or threshold".to_string()))?;
+
+        let mut instructions = vec![];
+        let account = self.get_user_account(wallet_pubkey)?;
+        let mut profile = state::UserProfile::unpack(&account.data).map_err(|err| {
+            Error::GeneralError(format!("Failed to decode user profile: {}", err))
+        })?;
+        profile.guardians = guardians.clone();
+        profile.guardian_threshold = threshold;
+        // Grow the PDA first if the guardians don't fit in it.
+        let max_size = self.program_config()?.max_user_pda_size;
+        if let Some(new_size) = required_pda_size(&profile, account.data.len(), max_size)? {
+            instructions.push(instruction::resize(
+                &self.program.pubkey(),
+                wallet_pubkey,
+                new_size,
+            ));
+        }
+        instructions.push(instruction::set_guardians(
+            &self.program.pubkey(),
+            wallet_pubkey,
+            guardians,
+            threshold,
+        ));
+        self.prepare_transaction(wallet_pubkey, &instructions)
+    }
+
+    // The guardian pays for the recovery PDA and gets its rent back once it's closed.
+    pub fn propose_recovery(
+        &self,
+        guardian_pubkey: &Pubkey,
+        wallet_pubkey: &Pubkey,
+        new_wallet_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let instruction = instruction::propose_recovery(
+            &self.program.pubkey(),
+            guardian_pubkey,
+            wallet_pubkey,
+            new_wallet_pubkey,
+        );
+        self.prepare_transaction(guardian_pubkey, &[instruction])
+    }
+
+    pub fn approve_recovery(
+        &self,
+        guardian_pubkey: &Pubkey,
+        wallet_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let instruction =
+            instruction::approve_recovery(&self.program.pubkey(), guardian_pubkey, wallet_pubkey);
+        self.prepare_transaction(guardian_pubkey, &[instruction])
+    }
 
+    // Signed by the owner or the guardian who proposed the recovery.
+    pub fn cancel_recovery(
+        &self,
+        signer_pubkey: &Pubkey,
+        wallet_pubkey: &Pubkey,
+        proposer_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let instruction = instruction::cancel_recovery(
+            &self.program.pubkey(),
+            signer_pubkey,
+            wallet_pubkey,
+            proposer_pubkey,
+        );
+        self.prepare_transaction(signer_pubkey, &[instruction])
+    }
+
+    // The profile moves to the PDA of the new wallet, the new wallet pays for it.
+    pub fn execute_recovery(
+        &self,
+        new_wallet_pubkey: &Pubkey,
+        wallet_pubkey: &Pubkey,
+        proposer_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let profile = self.get_user_profile(wallet_pubkey)?;
+        let instruction = instruction::execute_recovery(
+            &self.program.pubkey(),
+            new_wallet_pubkey,
+            wallet_pubkey,
+            &profile.username,
+            proposer_pubkey,
+        );
+        self.prepare_transaction(new_wallet_pubkey, &[instruction])
+    }
+
+    /// Checks that the transaction holds nothing but the [instruction::execute_recovery]
+    /// instruction moving the profile of `wallet_pubkey` to `new_wallet_pubkey`.
+    pub fn check_execute_recovery(
+        &self,
+        transaction: &Transaction,
+        wallet_pubkey: &Pubkey,
+        new_wallet_pubkey: &Pubkey,
+    ) -> Result<(), Error> {
+        let message = transaction.message();
+        let is_recovery = match message.instructions.as_slice() {
+            [compiled] => {
+                let account = |position: usize| {
+                    compiled
+                        .accounts
+                        .get(position)
+                        .and_then(|index| message.account_keys.get(*index as usize))
+                };
+                compiled
+                    .program_id(&message.account_keys)
+                    .eq(&self.program.pubkey())
+                    && matches!(
+                        instruction::ProgramInstruction::unpack(&compiled.data),
+                        Ok(instruction::ProgramInstruction::ExecuteRecovery(_))
+                    )
+                    && account(0) == Some(new_wallet_pubkey)
+                    && account(1) == Some(wallet_pubkey)
+            }
+            _ => false,
+        };
+        if !is_recovery {
+            return Err(Error::InvalidTransaction(
+                "Not the transaction executing the recovery".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn block_user(
+        &self,
+        wallet_pubkey: &Pubkey,
+        user_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let instruction = instruction::block(&self.program.pubkey(), wallet_pubkey, user_pubkey);
+        self.prepare_transaction(wallet_pubkey, &[instruction])
+    }
+
+    pub fn unblock_user(
+        &self,
+        wallet_pubkey: &Pubkey,
+        user_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let instruction = instruction::unblock(&self.program.pubkey(), wallet_pubkey, user_pubkey);
+        self.prepare_transaction(wallet_pubkey, &[instruction])
+    }
+
+    /// Creates an event organized by the user. The id of the event is random,
+    /// it only needs to differ from the ids of the other events of the organizer.
+    pub fn create_event(
+        &self,
+        wallet_pubkey: &Pubkey,
+        event: NewEvent,
+    ) -> Result<TransactionToSign, Error> {
+        state::validate_event(&event.title, &event.location_hint)
+            .map_err(|_| Error::InvalidEvent("Invalid title or location hint".to_string()))?;
+        if event.capacity == 0 {
+            return Err(Error::InvalidEvent(
+                "The event needs at least one attendee".to_string(),
+            ));
+        }
+        if event.starts_at <= unix_timestamp()? {
+            return Err(Error::InvalidEvent(
+                "The event needs to start in the future".to_string(),
+            ));
+        }
+
+        let (event_id, _) = Uuid::new_v4().as_u64_pair();
+        let instruction = instruction::create_event(
+            &self.program.pubkey(),
+            wallet_pubkey,
+            event_id,
+            event.title,
+            event.starts_at,
+            event.location_hint,
+            event.capacity,
+        );
+        self.prepare_transaction(wallet_pubkey, &[instruction])
+    }
+
+    pub fn rsvp(
+        &self,
+        wallet_pubkey: &Pubkey,
+        event_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let instruction = instruction::rsvp(&self.program.pubkey(), wallet_pubkey, event_pubkey);
+        self.prepare_transaction(wallet_pubkey, &[instruction])
+    }
+
+    pub fn cancel_rsvp(
+        &self,
+        wallet_pubkey: &Pubkey,
+        event_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let instruction =
+            instruction::cancel_rsvp(&self.program.pubkey(), wallet_pubkey, event_pubkey);
+        self.prepare_transaction(wallet_pubkey, &[instruction])
+    }
+
+    pub fn close_event(
+        &self,
+        wallet_pubkey: &Pubkey,
+        event_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let instruction =
+            instruction::close_event(&self.program.pubkey(), wallet_pubkey, event_pubkey);
+        self.prepare_transaction(wallet_pubkey, &[instruction])
+    }
+
+    // Creates a message to be signed by the user and keeps
+    // a record of it to validate the signed transaction later.
+    fn prepare_transaction(
+        &self,
+        wallet_pubkey: &Pubkey,
+        instructions: &[Instruction],
+    ) -> Result<TransactionToSign, Error> {
+        // The program would reject the transaction anyway.
+        if self.program_config()?.paused {
+            return Err(Error::ProgramPaused);
+        }
+        self.prepare_transaction_while_paused(wallet_pubkey, instructions)
+    }
+
+    // Same as prepare_transaction, for the instructions the program accepts while paused.
+    fn prepare_transaction_while_paused(
+        &self,
+        wallet_pubkey: &Pubkey,
+        instructions: &[Instruction],
+    ) -> Result<TransactionToSign, Error> {
         // Prepare the final message
         let recent_blockhash = self.client.get_latest_blockhash()?;
-        let instruction = Instruction::new_with_bytes(self.program.pubkey(), &instr_data, accounts);
         let message =
-            Message::new_with_blockhash(&[instruction], Some(&wallet_pubkey), &recent_blockhash);
+            Message::new_with_blockhash(instructions, Some(&wallet_pubkey), &recent_blockhash);
 
         // Save the transaction record in repo
         let mut transaction_record = TransactionRecord {
@@ -178,42 +945,231 @@ impl SolanaService {
         }
 
         // All checks passed, now we can submit the transaction.
-        let signature =
-            self.client
-                .send_and_confirm_transaction(&signed_transaction)
-                .map_err(|err| {
-                    println!("{:?}", err);
-                    let transaction_error = err.kind.get_transaction_error().map(
-                        |transaction_error| match transaction_error {
-                            TransactionError::AccountNotFound => Error::WalletNotFound,
-                            TransactionError::InsufficientFundsForFee
-                            | TransactionError::InsufficientFundsForRent { account_index: _ } => {
-                                Error::WalletInsufficientFounds
-                            }
-                            _ => Error::GeneralError(transaction_error.to_string()),
-                        },
-                    );
-                    transaction_error.or(Some(err.into())).unwrap()
-                })?;
+        let signature = self
+            .client
+            .send_and_confirm_transaction(&signed_transaction)
+            .map_err(|err| self.transaction_error(&signed_transaction, err))?;
 
         transaction_record.client_signature = Some(signature);
         self.repo.update_transaction_record(&transaction_record)?;
 
         Ok(())
     }
+
+    /// Sets or clears the verification flags of the user.
+    /// Unlike the other transactions, this one is signed and paid by the backend authority
+    /// and submitted right away, the user doesn't sign it.
+    pub fn attest(&self, wallet_pubkey: &Pubkey, flags: u64, verified: bool) -> Result<(), Error> {
+        // The program would reject the transaction anyway.
+        if self.program_config()?.attestation_authority != self.authority.pubkey() {
+            return Err(Error::Unauthorized(None));
+        }
+
+        let instruction = instruction::attest(
+            &self.program.pubkey(),
+            &self.authority.pubkey(),
+            wallet_pubkey,
+            flags,
+            verified,
+        );
+        let recent_blockhash = self.client.get_latest_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.authority.pubkey()),
+            &[self.authority.as_ref()],
+            recent_blockhash,
+        );
+        self.client
+            .send_and_confirm_transaction(&transaction)
+            .map_err(|err| self.transaction_error(&transaction, err))?;
+
+        Ok(())
+    }
+
+    fn transaction_error(&self, transaction: &Transaction, err: ClientError) -> Error {
+        println!("{:?}", err);
+        let transaction_error =
+            err.kind
+                .get_transaction_error()
+                .map(|transaction_error| match transaction_error {
+                    TransactionError::AccountNotFound => Error::WalletNotFound,
+                    TransactionError::InsufficientFundsForFee
+                    | TransactionError::InsufficientFundsForRent { account_index: _ } => {
+                        Error::WalletInsufficientFounds
+                    }
+                    TransactionError::InstructionError(index, InstructionError::Custom(code))
+                        if self.is_program_instruction(transaction, index) =>
+                    {
+                        decode_program_error(code, &err)
+                    }
+                    _ => Error::GeneralError(transaction_error.to_string()),
+                });
+        transaction_error.or(Some(err.into())).unwrap()
+    }
+
+    // Custom error codes are defined per program,
+    // only the ones returned by our program can be decoded.
+    fn is_program_instruction(&self, transaction: &Transaction, index: u8) -> bool {
+        transaction
+            .message()
+            .instructions
+            .get(index as usize)
+            .map(|instruction| instruction.program_id(&transaction.message().account_keys))
+            .is_some_and(|program_id| program_id.eq(&self.program.pubkey()))
+    }
+}
+
+// Returns the size the user PDA needs to grow to so that the profile fits in it,
+// None if it already fits. The PDA grows in steps of the initial size up to `max_size`.
+fn required_pda_size(
+    profile: &state::UserProfile,
+    account_size: usize,
+    max_size: u64,
+) -> Result<Option<u64>, Error> {
+    let profile_len = profile.packed_len() as u64;
+    if profile_len <= account_size as u64 {
+        return Ok(None);
+    }
+    let new_size = profile_len.next_multiple_of(pda::USER_PDA_SIZE);
+    if new_size > max_size.min(pda::MAX_USER_PDA_SIZE) {
+        return Err(Error::InvalidProfile("Profile is too large".to_string()));
+    }
+    Ok(Some(new_size))
+}
+
+fn decode_group(pubkey: &Pubkey, account: &Account) -> Result<Group, Error> {
+    let group = state::Group::unpack(&account.data)
+        .map_err(|err| Error::GeneralError(format!("Failed to decode group: {}", err)))?;
+    Ok(Group {
+        pubkey: *pubkey,
+        admin: group.admin,
+        name: group.name,
+        invite_only: group.invite_only,
+        max_members: group.max_members,
+        member_count: group.member_count,
+    })
+}
+
+// Current time in the format of the timestamps stored on-chain.
+fn unix_timestamp() -> Result<i64, Error> {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .map_err(|err| Error::GeneralError(err.to_string()))
+}
+
+fn decode_event(pubkey: &Pubkey, account: &Account) -> Result<Event, Error> {
+    let event = state::Event::unpack(&account.data)
+        .map_err(|err| Error::GeneralError(format!("Failed to decode event: {}", err)))?;
+    Ok(Event {
+        pubkey: *pubkey,
+        organizer: event.organizer,
+        title: event.title,
+        starts_at: event.starts_at,
+        location_hint: event.location_hint,
+        capacity: event.capacity,
+        attendee_count: event.attendee_count,
+        closed: event.closed,
+    })
+}
+
+fn decode_poll(pubkey: &Pubkey, account: &Account) -> Result<Poll, Error> {
+    let poll = state::Poll::unpack(&account.data)
+        .map_err(|err| Error::GeneralError(format!("Failed to decode poll: {}", err)))?;
+    Ok(Poll {
+        pubkey: *pubkey,
+        creator: poll.creator,
+        group: poll.group,
+        question: poll.question,
+        options: poll
+            .options
+            .into_iter()
+            .zip(poll.tallies)
+            .map(|(text, votes)| PollOption { text, votes })
+            .collect(),
+        closed: poll.closed,
+    })
+}
+
+// Maps the custom error returned by the program to a domain error.
+// The account causing the error is found in the program logs.
+fn decode_program_error(code: u32, err: &ClientError) -> Error {
+    use program_error::ErrorCause;
+
+    let logs = match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.logs.clone().unwrap_or_default(),
+        _ => vec![],
+    };
+    let account = program_error::Error::account_key_from_logs(&logs);
+
+    // Lower codes come from the system program invoked by our instruction.
+    if code < program_error::ERROR_CODE_BASE {
+        return if code == SystemError::ResultWithNegativeLamports as u32 {
+            Error::WalletInsufficientFounds
+        } else {
+            Error::GeneralError(format!("System program failed: custom error {}", code))
+        };
+    }
+    let cause = ErrorCause::from(code);
+    match cause {
+        ErrorCause::GeneralError => Error::GeneralError("Program failed".to_string()),
+        ErrorCause::InvalidPubKey => Error::InvalidPubKey(
+            account
+                .map(|a| a.to_string())
+                .unwrap_or("Invalid".to_string()),
+        ),
+        ErrorCause::AccountNotFound => Error::AccountNotFound(account),
+        ErrorCause::AccountAlreadyInitialized => Error::AccountAlreadyInitialized(account),
+        ErrorCause::MissingRequiredSignature
+        | ErrorCause::AccountNotWritable
+        | ErrorCause::InvalidAccountOwner
+        | ErrorCause::InvalidAccountData
+        | ErrorCause::InvalidPda
+        | ErrorCause::InvalidBumpSeed
+        | ErrorCause::InvalidSystemProgram
+        | ErrorCause::InvalidAccountSize => Error::InvalidAccount(format!("{:?}", cause), account),
+        ErrorCause::InvalidInstructionData => {
+            Error::InvalidTransaction("Invalid instruction data".to_string())
+        }
+        ErrorCause::InvalidProfileData => {
+            Error::InvalidProfile("Rejected by the program".to_string())
+        }
+        ErrorCause::AccountDataTooSmall => Error::AccountDataTooSmall(account),
+        ErrorCause::InsufficientRent => {
+            Error::InvalidTransaction("Lamports don't cover the rent".to_string())
+        }
+        ErrorCause::InvalidState => Error::InvalidAccountState(account),
+        ErrorCause::Unauthorized | ErrorCause::Flagged => Error::Unauthorized(account),
+        ErrorCause::UsernameTaken => Error::UsernameTaken,
+        ErrorCause::ProgramPaused => Error::ProgramPaused,
+        ErrorCause::SessionExpired => Error::SessionExpired,
+        ErrorCause::Blocked => Error::Blocked,
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::process::Command;
+    use std::{
+        process::Command,
+        time::{Duration, Instant},
+    };
 
     use solana_sdk::{
-        commitment_config::CommitmentConfig, signature::Keypair, signer::Signer, transaction,
+        commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair, signer::Signer,
+        transaction,
     };
 
+    use crate::domain::error::Error;
     use crate::repo;
 
-    use super::{Config, SolanaService};
+    use super::{
+        decode_program_error, instruction, pda, program_error, required_pda_size, state, Config,
+        SolanaService, ENDORSED_OFFSET, ENDORSER_OFFSET, MEMBERSHIP_GROUP_OFFSET,
+        MEMBERSHIP_MEMBER_OFFSET, POLL_GROUP_OFFSET, RSVP_ATTENDEE_OFFSET, RSVP_EVENT_OFFSET,
+    };
 
     #[test]
     fn test_create_user_pda() {
@@ -234,7 +1190,9 @@ mod tests {
             .wait()
             .unwrap();
 
-        let to_sign = solana.create_user_pda(&wallet_pubkey).unwrap();
+        let to_sign = solana
+            .create_user_pda(&wallet_pubkey, "paulinka".to_string(), None)
+            .unwrap();
 
         let blockhash = solana.client.get_latest_blockhash().unwrap();
         let transaction = transaction::Transaction::new(&[wallet], to_sign.message, blockhash);
@@ -251,14 +1209,261 @@ mod tests {
             "Client signature: {}",
             transaction_record.client_signature.unwrap()
         );
+
+        let profile = solana.get_user_profile(&wallet_pubkey).unwrap();
+        assert_eq!(profile.owner, wallet_pubkey);
+        assert_eq!(profile.username, "paulinka");
     }
 
     fn new_solana_service() -> SolanaService {
         let mut cfg = Config::default();
         cfg.commitment_config = CommitmentConfig::finalized();
-        let keypair_path = "solana_program/target/deploy/solana_program-keypair.json";
+        let keypair_path =
+            "solana_program/target/deploy/anti_loneliness_solana_program-keypair.json";
         let program_keypair = solana_sdk::signer::keypair::read_keypair_file(keypair_path).unwrap();
         let repo = repo::solana::Repo::new();
-        SolanaService::new(cfg, program_keypair, repo)
+        SolanaService::new(cfg, program_keypair, Keypair::new(), repo)
+    }
+
+    fn cache_program_config(solana: &SolanaService, config: state::ProgramConfig, age_sec: u64) {
+        let loaded_at = Instant::now()
+            .checked_sub(Duration::from_secs(age_sec))
+            .unwrap();
+        *solana.program_config.write().unwrap() = Some((config, loaded_at));
+    }
+
+    #[test]
+    fn test_attest_not_authority() {
+        // The default config has no attestation authority.
+        let solana = new_solana_service();
+        cache_program_config(&solana, state::ProgramConfig::default(), 0);
+        assert_eq!(
+            Err(Error::Unauthorized(None)),
+            solana.attest(&Pubkey::new_unique(), state::VERIFIED_ONBOARDING, true)
+        );
+    }
+
+    #[test]
+    fn test_cached_program_config() {
+        let solana = new_solana_service();
+        let wallet = Pubkey::new_unique();
+        let config = state::ProgramConfig {
+            paused: true,
+            ..state::ProgramConfig::default()
+        };
+
+        cache_program_config(&solana, config.clone(), 0);
+        assert!(matches!(
+            solana.block_user(&wallet, &Pubkey::new_unique()),
+            Err(Error::ProgramPaused)
+        ));
+        // The program revokes sessions also while paused.
+        assert!(!matches!(
+            solana.revoke_session(&wallet, &Pubkey::new_unique()),
+            Err(Error::ProgramPaused)
+        ));
+
+        // A stale config is read again.
+        let ttl = Config::default().program_config_ttl_sec;
+        cache_program_config(&solana, config, ttl);
+        assert!(!matches!(
+            solana.block_user(&wallet, &Pubkey::new_unique()),
+            Err(Error::ProgramPaused)
+        ));
+    }
+
+    #[test]
+    fn test_required_pda_size() {
+        let mut profile =
+            state::UserProfile::new(Pubkey::new_unique(), 255, "paulinka".to_string(), 0);
+        let max_size = pda::MAX_USER_PDA_SIZE;
+        assert_eq!(None, required_pda_size(&profile, 1024, max_size).unwrap());
+
+        profile.bio = "a".repeat(1500);
+        assert_eq!(
+            Some(2048),
+            required_pda_size(&profile, 1024, max_size).unwrap()
+        );
+        // Above the limit set in the program config
+        assert!(matches!(
+            required_pda_size(&profile, 1024, 1024),
+            Err(Error::InvalidProfile(_))
+        ));
+
+        profile.bio = "a".repeat(20_000);
+        assert!(matches!(
+            required_pda_size(&profile, 1024, max_size),
+            Err(Error::InvalidProfile(_))
+        ));
+    }
+
+    #[test]
+    fn test_endorsement_offsets() {
+        let endorsement =
+            state::Endorsement::new(Pubkey::new_unique(), Pubkey::new_unique(), 255, 42);
+        let mut data = vec![0; pda::ENDORSEMENT_PDA_SIZE as usize];
+        endorsement.pack(&mut data).unwrap();
+        assert_eq!(
+            endorsement.endorser.as_ref(),
+            &data[ENDORSER_OFFSET..ENDORSER_OFFSET + 32]
+        );
+        assert_eq!(
+            endorsement.endorsed.as_ref(),
+            &data[ENDORSED_OFFSET..ENDORSED_OFFSET + 32]
+        );
+    }
+
+    #[test]
+    fn test_membership_offsets() {
+        let membership =
+            state::Membership::new(Pubkey::new_unique(), Pubkey::new_unique(), 255, 42);
+        let mut data = vec![0; pda::MEMBERSHIP_PDA_SIZE as usize];
+        membership.pack(&mut data).unwrap();
+        assert_eq!(
+            membership.group.as_ref(),
+            &data[MEMBERSHIP_GROUP_OFFSET..MEMBERSHIP_GROUP_OFFSET + 32]
+        );
+        assert_eq!(
+            membership.member.as_ref(),
+            &data[MEMBERSHIP_MEMBER_OFFSET..MEMBERSHIP_MEMBER_OFFSET + 32]
+        );
+    }
+
+    #[test]
+    fn test_rsvp_offsets() {
+        let rsvp = state::Rsvp::new(Pubkey::new_unique(), Pubkey::new_unique(), 255, 42);
+        let mut data = vec![0; pda::RSVP_PDA_SIZE as usize];
+        rsvp.pack(&mut data).unwrap();
+        assert_eq!(
+            rsvp.event.as_ref(),
+            &data[RSVP_EVENT_OFFSET..RSVP_EVENT_OFFSET + 32]
+        );
+        assert_eq!(
+            rsvp.attendee.as_ref(),
+            &data[RSVP_ATTENDEE_OFFSET..RSVP_ATTENDEE_OFFSET + 32]
+        );
+    }
+
+    #[test]
+    fn test_poll_offsets() {
+        let group = Pubkey::new_unique();
+        let poll = state::Poll::new(
+            Pubkey::new_unique(),
+            7,
+            255,
+            Some(group),
+            "Where do we meet?".to_string(),
+            vec!["Park".to_string(), "Cafe".to_string()],
+            42,
+        );
+        let mut data = vec![0; pda::POLL_PDA_SIZE as usize];
+        poll.pack(&mut data).unwrap();
+        assert_eq!(
+            group.as_ref(),
+            &data[POLL_GROUP_OFFSET..POLL_GROUP_OFFSET + 32]
+        );
+    }
+
+    #[test]
+    fn test_check_execute_recovery() {
+        let solana = new_solana_service();
+        let wallet = Pubkey::new_unique();
+        let new_wallet = Pubkey::new_unique();
+        let recovery = instruction::execute_recovery(
+            &solana.program.pubkey(),
+            &new_wallet,
+            &wallet,
+            "paulinka",
+            &Pubkey::new_unique(),
+        );
+        let new_transaction = |instructions: &[solana_sdk::instruction::Instruction]| {
+            transaction::Transaction::new_unsigned(solana_sdk::message::Message::new(
+                instructions,
+                Some(&new_wallet),
+            ))
+        };
+
+        let transaction = new_transaction(std::slice::from_ref(&recovery));
+        assert_eq!(
+            Ok(()),
+            solana.check_execute_recovery(&transaction, &wallet, &new_wallet)
+        );
+        assert!(solana
+            .check_execute_recovery(&transaction, &new_wallet, &wallet)
+            .is_err());
+
+        // Nothing else can be executed with the recovery.
+        let transfer = solana_sdk::system_instruction::transfer(&new_wallet, &wallet, 1);
+        for instructions in [vec![transfer.clone()], vec![recovery, transfer]] {
+            assert!(solana
+                .check_execute_recovery(&new_transaction(&instructions), &wallet, &new_wallet)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_decode_program_error() {
+        use solana_client::{
+            client_error::ClientError,
+            rpc_request::{RpcError, RpcResponseErrorData},
+            rpc_response::RpcSimulateTransactionResult,
+        };
+
+        let account = solana_sdk::pubkey::Pubkey::new_unique();
+        let code: u32 = program_error::ErrorCause::AccountAlreadyInitialized.into();
+        let err = ClientError::from(RpcError::RpcResponseError {
+            code: -32002,
+            message: "Transaction simulation failed".to_string(),
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(
+                RpcSimulateTransactionResult {
+                    err: None,
+                    logs: Some(vec![
+                        format!(
+                            "Program log: Account: {} | AccountAlreadyInitialized",
+                            account
+                        ),
+                        format!("Program failed: custom program error: {:#x}", code),
+                    ]),
+                    accounts: None,
+                    units_consumed: None,
+                    return_data: None,
+                    inner_instructions: None,
+                    replacement_blockhash: None,
+                },
+            ),
+        });
+
+        assert_eq!(
+            Error::AccountAlreadyInitialized(Some(account)),
+            decode_program_error(code, &err)
+        );
+        assert_eq!(
+            Error::UsernameTaken,
+            decode_program_error(program_error::ErrorCause::UsernameTaken.into(), &err)
+        );
+        assert_eq!(
+            Error::SessionExpired,
+            decode_program_error(program_error::ErrorCause::SessionExpired.into(), &err)
+        );
+        assert_eq!(
+            Error::Blocked,
+            decode_program_error(program_error::ErrorCause::Blocked.into(), &err)
+        );
+        assert_eq!(
+            Error::Unauthorized(Some(account)),
+            decode_program_error(program_error::ErrorCause::Flagged.into(), &err)
+        );
+        assert_eq!(
+            Error::GeneralError("Program failed".to_string()),
+            decode_program_error(u32::MAX, &err)
+        );
+        // Reported at our instruction when the CPI to the system program fails.
+        assert_eq!(
+            Error::WalletInsufficientFounds,
+            decode_program_error(
+                solana_sdk::system_instruction::SystemError::ResultWithNegativeLamports as u32,
+                &err
+            )
+        );
     }
 }
@@ -2,11 +2,15 @@ use std::str::FromStr;
 use std::time::SystemTime;
 
 use crate::domain::error::{self, Error};
-use crate::domain::model::{TransactionToSign, User};
+use crate::domain::model::{
+    Endorsements, Event, Group, NewEvent, PendingTransfer, ProfileUpdate, RecoveryStatus,
+    TransactionToSign, User,
+};
 use crate::repo::user::Repo;
 
 use solana_sdk::bs58::decode::DecodeTarget;
 use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
 use uuid::Uuid;
 
 use super::solana_service;
@@ -61,6 +65,31 @@ impl UserService {
         self.repo.get_user(&pubkey)
     }
 
+    // Endorsements are only stored on-chain, read them from there.
+    pub fn get_endorsements(&self, pubkey: &Pubkey) -> Result<Endorsements, Error> {
+        self.solana.get_endorsements(pubkey)
+    }
+
+    // Groups are only stored on-chain, read them from there.
+    pub fn get_groups(&self, pubkey: &Pubkey) -> Result<Vec<Group>, Error> {
+        self.solana.get_user_groups(pubkey)
+    }
+
+    // RSVPs are only stored on-chain, read them from there.
+    pub fn get_events(&self, pubkey: &Pubkey) -> Result<Vec<Event>, Error> {
+        self.solana.get_user_events(pubkey)
+    }
+
+    // The block list is only stored on-chain, read it from there.
+    pub fn get_blocked_users(&self, pubkey: &Pubkey) -> Result<Vec<Pubkey>, Error> {
+        self.solana.get_blocked_users(pubkey)
+    }
+
+    // Guardians and recoveries are only stored on-chain, read them from there.
+    pub fn get_recovery_status(&self, pubkey: &Pubkey) -> Result<RecoveryStatus, Error> {
+        self.solana.get_recovery_status(pubkey)
+    }
+
     pub fn login_init(&self, pubkey: &Pubkey) -> Result<String /* refresh_token */, Error> {
         let mut user: User = self.repo.get_user(pubkey)?;
         // Validate the user
@@ -141,9 +170,20 @@ impl UserService {
             return Err(error::Error::UserAlreadyInitialized);
         }
 
-        // Get user PDA
+        // The profile stored on-chain is the source of truth,
+        // make sure it has been created for this wallet.
+        let profile = self.solana.get_user_profile(pubkey)?;
         let pda = self.solana.get_user_pda(pubkey);
         user.pda_pubkey = Some(pda);
+        user.username = profile.username;
+
+        // The badge is a nice-to-have, the registration succeeds without it.
+        if let Err(err) =
+            self.solana
+                .attest(pubkey, solana_service::state::VERIFIED_ONBOARDING, true)
+        {
+            println!("Failed to attest the onboarding of {}: {}", pubkey, err);
+        }
 
         // Generate access and refresh tokens
         self.assign_auth_tokens(&mut user)
@@ -163,12 +203,31 @@ impl UserService {
         &self,
         pubkey: &Pubkey,
         username: String,
+        invite_code: Option<String>,
     ) -> Result<TransactionToSign, Error> {
+        // The program rejects a taken username anyway,
+        // fail before the user is asked to sign the transaction.
+        if !self.solana.is_username_available(&username)? {
+            return Err(error::Error::UsernameTaken);
+        }
+        // The invite code is the username of the referrer.
+        let referrer = match invite_code {
+            Some(code) => match self.solana.get_username_owner(&code) {
+                Ok(Some(referrer)) if referrer.ne(pubkey) => Some(referrer),
+                Ok(_) | Err(Error::InvalidUsername(_)) => {
+                    return Err(error::Error::InvalidInviteCode(code))
+                }
+                Err(err) => return Err(err),
+            },
+            None => None,
+        };
+
         let user: User = User {
             pubkey: pubkey.clone(),
-            username,
+            username: username.clone(),
             pda_pubkey: None,
             refresh_token: None,
+            pending_transfer: None,
         };
 
         // Check if we already have this user, update if found.
@@ -190,7 +249,360 @@ impl UserService {
 
         // Now we want to create a transaction message creating a PDA
         // for this use. User will sign it and forward it back to backend.
-        self.solana.create_user_pda(pubkey)
+        self.solana
+            .create_user_pda(pubkey, username, referrer.as_ref())
+    }
+
+    pub fn transfer_ownership_init(
+        &self,
+        pubkey: &Pubkey,
+        new_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        // The program rejects a wallet which already has a profile,
+        // fail before the user is asked to sign the transaction.
+        if self
+            .repo
+            .get_user(new_pubkey)
+            .is_ok_and(|user| user.pda_pubkey.is_some())
+        {
+            return Err(error::Error::UserAlreadyInitialized);
+        }
+        // The pending recovery has to be cancelled first.
+        if let Some(pending) = self.solana.get_recovery_status(pubkey)?.pending {
+            return Err(error::Error::InvalidAccountState(Some(pending.pubkey)));
+        }
+
+        let to_sign = self.solana.transfer_ownership(pubkey, new_pubkey)?;
+        self.set_pending_transfer(pubkey, new_pubkey, &to_sign)?;
+        Ok(to_sign)
+    }
+
+    // Remembers the transfer prepared for the user,
+    // transfer_ownership_complete only accepts its transaction.
+    fn set_pending_transfer(
+        &self,
+        pubkey: &Pubkey,
+        new_pubkey: &Pubkey,
+        to_sign: &TransactionToSign,
+    ) -> Result<(), Error> {
+        let mut user: User = self.repo.get_user(pubkey)?;
+        let profile = self.solana.get_user_profile(pubkey)?;
+        user.pending_transfer = Some(PendingTransfer {
+            new_pubkey: *new_pubkey,
+            transaction_id: to_sign.transaction_id,
+            created_at_slot: profile.created_at_slot,
+        });
+        self.repo.update_user(&user)
+    }
+
+    // Check the transaction before it is executed,
+    // only the one prepared by transfer_ownership_init moves the profile.
+    pub fn check_pending_transfer(
+        &self,
+        pubkey: &Pubkey,
+        new_pubkey: &Pubkey,
+        transaction_id: Uuid,
+    ) -> Result<PendingTransfer, Error> {
+        let user: User = self.repo.get_user(pubkey)?;
+        if user.pda_pubkey.is_none() {
+            return Err(error::Error::UserNotConfirmed);
+        }
+        match user.pending_transfer {
+            Some(pending)
+                if pending.new_pubkey.eq(new_pubkey)
+                    && pending.transaction_id.eq(&transaction_id) =>
+            {
+                Ok(pending)
+            }
+            _ => Err(error::Error::InvalidTransaction(
+                "Not the transaction prepared for the transfer".to_string(),
+            )),
+        }
+    }
+
+    pub fn transfer_ownership_complete(
+        &self,
+        pubkey: &Pubkey,
+        new_pubkey: &Pubkey,
+        transaction_id: Uuid,
+    ) -> Result<AuthTokens, Error> {
+        let pending = self.check_pending_transfer(pubkey, new_pubkey, transaction_id)?;
+        self.complete_transfer(pubkey, new_pubkey, pending.created_at_slot)
+    }
+
+    // Re-keys the user once the profile created at `created_at_slot`
+    // has been moved from the old wallet to the new one.
+    fn complete_transfer(
+        &self,
+        pubkey: &Pubkey,
+        new_pubkey: &Pubkey,
+        created_at_slot: u64,
+    ) -> Result<AuthTokens, Error> {
+        let mut user: User = self.repo.get_user(pubkey)?;
+
+        // The profile stored on-chain is the source of truth,
+        // make sure it has been moved from the old wallet to the new one.
+        match self.solana.get_user_profile(pubkey) {
+            Err(Error::UserNotFound) => {}
+            Ok(_) => {
+                return Err(error::Error::InvalidAccountState(Some(
+                    self.solana.get_user_pda(pubkey),
+                )))
+            }
+            Err(err) => return Err(err),
+        }
+        let profile = self.solana.get_user_profile(new_pubkey)?;
+        let new_pda_pubkey = self.solana.get_user_pda(new_pubkey);
+        if profile.created_at_slot != created_at_slot {
+            return Err(error::Error::InvalidAccountState(Some(new_pda_pubkey)));
+        }
+        user.pubkey = *new_pubkey;
+        user.pda_pubkey = Some(new_pda_pubkey);
+        user.username = profile.username;
+        // The refresh token of the previous wallet is dropped with it.
+        user.refresh_token = None;
+        user.pending_transfer = None;
+        self.repo.rekey_user(pubkey, user.clone())?;
+
+        // Generate access and refresh tokens for the new wallet
+        self.assign_auth_tokens(&mut user)
+    }
+
+    pub fn set_guardians_init(
+        &self,
+        pubkey: &Pubkey,
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        self.solana.set_guardians(pubkey, guardians, threshold)
+    }
+
+    pub fn propose_recovery_init(
+        &self,
+        guardian: &Pubkey,
+        pubkey: &Pubkey,
+        new_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        // The program rejects the proposal in these cases,
+        // fail before the guardian is asked to sign the transaction.
+        let status = self.solana.get_recovery_status(pubkey)?;
+        if !status.guardians.contains(guardian) {
+            return Err(error::Error::Unauthorized(None));
+        }
+        if status.pending.is_some() {
+            return Err(error::Error::InvalidAccountState(None));
+        }
+        if new_pubkey.eq(pubkey)
+            || self
+                .repo
+                .get_user(new_pubkey)
+                .is_ok_and(|user| user.pda_pubkey.is_some())
+        {
+            return Err(error::Error::UserAlreadyInitialized);
+        }
+
+        self.solana.propose_recovery(guardian, pubkey, new_pubkey)
+    }
+
+    pub fn approve_recovery_init(
+        &self,
+        guardian: &Pubkey,
+        pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        let status = self.solana.get_recovery_status(pubkey)?;
+        if !status.guardians.contains(guardian) {
+            return Err(error::Error::Unauthorized(None));
+        }
+        let pending = status.pending.ok_or(error::Error::AccountNotFound(None))?;
+        if pending.approvals.contains(guardian) {
+            return Err(error::Error::InvalidAccountState(Some(pending.pubkey)));
+        }
+
+        self.solana.approve_recovery(guardian, pubkey)
+    }
+
+    pub fn cancel_recovery_init(
+        &self,
+        signer: &Pubkey,
+        pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        let pending = self
+            .solana
+            .get_recovery_status(pubkey)?
+            .pending
+            .ok_or(error::Error::AccountNotFound(None))?;
+        // Only the owner and the guardian who proposed the recovery can cancel it.
+        if signer.ne(pubkey) && signer.ne(&pending.proposer) {
+            return Err(error::Error::Unauthorized(Some(pending.pubkey)));
+        }
+
+        self.solana
+            .cancel_recovery(signer, pubkey, &pending.proposer)
+    }
+
+    // The new wallet isn't registered yet and needs no token, the recovery is only
+    // checked against the chain and nothing is stored for it.
+    // Complete it with execute_recovery_complete once the transaction is executed.
+    pub fn execute_recovery_init(
+        &self,
+        pubkey: &Pubkey,
+        new_pubkey: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        let status = self.solana.get_recovery_status(pubkey)?;
+        let pending = status.pending.ok_or(error::Error::AccountNotFound(None))?;
+        if pending.new_owner.ne(new_pubkey) {
+            return Err(error::Error::Unauthorized(Some(pending.pubkey)));
+        }
+        // The program rejects the recovery in these cases,
+        // fail before the new wallet is asked to sign the transaction.
+        if status.threshold == 0
+            || pending.approval_count < status.threshold as usize
+            || self.solana.get_slot()? < pending.executable_at_slot
+        {
+            return Err(error::Error::InvalidAccountState(Some(pending.pubkey)));
+        }
+
+        self.solana
+            .execute_recovery(new_pubkey, pubkey, &pending.proposer)
+    }
+
+    // Check the transaction before it is executed, it must only execute the recovery
+    // of the profile to the new wallet. Returns the slot the profile was created at.
+    pub fn check_recovery_transaction(
+        &self,
+        pubkey: &Pubkey,
+        new_pubkey: &Pubkey,
+        transaction: &Transaction,
+    ) -> Result<u64, Error> {
+        self.check_confirmed(pubkey)?;
+        self.solana
+            .check_execute_recovery(transaction, pubkey, new_pubkey)?;
+        Ok(self.solana.get_user_profile(pubkey)?.created_at_slot)
+    }
+
+    pub fn execute_recovery_complete(
+        &self,
+        pubkey: &Pubkey,
+        new_pubkey: &Pubkey,
+        created_at_slot: u64,
+    ) -> Result<AuthTokens, Error> {
+        self.complete_transfer(pubkey, new_pubkey, created_at_slot)
+    }
+
+    pub fn update_profile_init(
+        &self,
+        pubkey: &Pubkey,
+        update: ProfileUpdate,
+    ) -> Result<TransactionToSign, Error> {
+        let user: User = self.repo.get_user(pubkey)?;
+        if user.pda_pubkey.is_none() {
+            return Err(error::Error::UserNotConfirmed);
+        }
+
+        // Start from the profile currently stored on-chain,
+        // the instruction overwrites all editable fields.
+        let mut profile = self.solana.get_user_profile(pubkey)?;
+        if let Some(username) = update.username {
+            profile.username = username;
+        }
+        if let Some(bio) = update.bio {
+            profile.bio = bio;
+        }
+        if let Some(interests) = update.interests {
+            profile.interests = interests;
+        }
+
+        self.solana.update_user_profile(pubkey, &profile)
+    }
+
+    pub fn block_init(&self, pubkey: &Pubkey, user: &Pubkey) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        // The program rejects blocking a user twice,
+        // fail before the user is asked to sign the transaction.
+        let blocked = self.solana.get_blocked_users(pubkey)?;
+        if blocked.contains(user) {
+            return Err(error::Error::InvalidAccountState(None));
+        }
+        self.solana.block_user(pubkey, user)
+    }
+
+    pub fn unblock_init(&self, pubkey: &Pubkey, user: &Pubkey) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        if !self.solana.get_blocked_users(pubkey)?.contains(user) {
+            return Err(error::Error::InvalidAccountState(None));
+        }
+        self.solana.unblock_user(pubkey, user)
+    }
+
+    pub fn create_event_init(
+        &self,
+        pubkey: &Pubkey,
+        event: NewEvent,
+    ) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        self.solana.create_event(pubkey, event)
+    }
+
+    pub fn rsvp_init(&self, pubkey: &Pubkey, event: &Pubkey) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        // The program rejects an RSVP to a closed or full event,
+        // fail before the user is asked to sign the transaction.
+        let state = self.solana.get_event(event)?;
+        if state.closed || state.attendee_count >= state.capacity {
+            return Err(error::Error::InvalidAccountState(Some(*event)));
+        }
+        self.solana.rsvp(pubkey, event)
+    }
+
+    pub fn cancel_rsvp_init(
+        &self,
+        pubkey: &Pubkey,
+        event: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        self.solana.cancel_rsvp(pubkey, event)
+    }
+
+    pub fn close_event_init(
+        &self,
+        pubkey: &Pubkey,
+        event: &Pubkey,
+    ) -> Result<TransactionToSign, Error> {
+        self.check_confirmed(pubkey)?;
+        let state = self.solana.get_event(event)?;
+        if state.organizer.ne(pubkey) {
+            return Err(error::Error::Unauthorized(Some(*event)));
+        }
+        if state.closed {
+            return Err(error::Error::InvalidAccountState(Some(*event)));
+        }
+        self.solana.close_event(pubkey, event)
+    }
+
+    fn check_confirmed(&self, pubkey: &Pubkey) -> Result<(), Error> {
+        let user: User = self.repo.get_user(pubkey)?;
+        if user.pda_pubkey.is_none() {
+            return Err(error::Error::UserNotConfirmed);
+        }
+        Ok(())
+    }
+
+    // Updates the user with the profile stored on-chain.
+    pub fn refresh_user(&self, pubkey: &Pubkey) -> Result<(), Error> {
+        let mut user: User = self.repo.get_user(pubkey)?;
+        if user.pda_pubkey.is_none() {
+            // Not registered yet, nothing to refresh.
+            return Ok(());
+        }
+
+        let profile = self.solana.get_user_profile(pubkey)?;
+        user.username = profile.username;
+        self.repo.update_user(&user)
     }
 
     fn generate_jwt_token(&self, pubkey: &Pubkey) -> Result<String, Error> {
@@ -4,6 +4,8 @@ mod repo;
 pub mod server;
 mod utils;
 
+use std::sync::Arc;
+
 use crate::app_state::AppStateBuiler;
 use solana_sdk::signature::Keypair;
 
@@ -13,12 +15,46 @@ use crate::server::Server;
 async fn main() {
     // relative to Cargo
     let keypair_dir = "solana_program/target/deploy/";
+    let authority_keypair_path = "authority-keypair.json";
     let program_keypair = get_keypair_from_dir(keypair_dir);
-    let auth_secret = jwt_simple::prelude::HS256Key::generate().to_bytes();
+    // Signs the attestations, the program config needs to name it the attestation authority.
+    let authority_keypair = solana_sdk::signer::keypair::read_keypair_file(authority_keypair_path)
+        .unwrap_or_else(|_| {
+            println!(
+                "No authority keypair in {}, using a new one",
+                authority_keypair_path
+            );
+            Keypair::new()
+        });
+    let auth_secret = jwt_simple::prelude::HS256Key::generate();
+
+    // Access tokens issued by the user service are validated by the server.
+    let mut cfg = app_state::Config::default();
+    cfg.server_config = server::Config::default_with_auth_key(Arc::new(auth_secret.clone()));
 
-    let app =
-        AppStateBuiler::new().build(app_state::Config::default(), auth_secret, program_keypair);
-    let server = Server::new(server::Config::default(), app);
+    let app = AppStateBuiler::new()
+        .with_authority_keypair(authority_keypair)
+        .build(cfg.clone(), auth_secret.to_bytes(), program_keypair);
+    // Transactions are built according to the program config.
+    let program_config = app
+        .solana_service
+        .load_program_config()
+        .expect("Failed to read the program config");
+    println!(
+        "Program config: paused: {}, registration fee: {}, deposit: {}, max user PDA size: {}",
+        program_config.paused,
+        program_config.registration_fee,
+        program_config.deposit,
+        program_config.max_user_pda_size
+    );
+    let authority = app.solana_service.authority_pubkey();
+    if program_config.attestation_authority != authority {
+        println!(
+            "{} is not the attestation authority of the program, users won't be attested",
+            authority
+        );
+    }
+    let server = Server::new(cfg.server_config, app);
     server.run().await.unwrap();
 }
 
@@ -94,5 +94,23 @@ pub mod user {
             users.insert(user.pubkey, user.clone());
             Ok(())
         }
+
+        // Moves the user stored under `pubkey` to the user's new pubkey.
+        // A registration of the new pubkey which hasn't been completed is dropped.
+        pub fn rekey_user(&self, pubkey: &Pubkey, user: User) -> Result<(), Error> {
+            let mut users = self.users.lock().unwrap();
+            if !users.contains_key(pubkey) {
+                return Err(Error::UserNotFound);
+            }
+            if users
+                .get(&user.pubkey)
+                .is_some_and(|existing| existing.pda_pubkey.is_some())
+            {
+                return Err(Error::UserAlreadyInitialized);
+            }
+            users.remove(pubkey);
+            users.insert(user.pubkey, user);
+            Ok(())
+        }
     }
 }
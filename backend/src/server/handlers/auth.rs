@@ -13,6 +13,7 @@ use crate::utils;
 pub struct PostRegisterReq {
     pubkey: String,
     username: String,
+    invite_code: Option<String>, // username of the user who sent the invite
 }
 
 #[axum_macros::debug_handler]
@@ -23,7 +24,7 @@ pub async fn post_register(
     let pubkey = parse_pubkey(&req.pubkey)?;
     let transaction_to_sign = state
         .user_service
-        .register_init(&pubkey, req.username)
+        .register_init(&pubkey, req.username, req.invite_code)
         .inspect_err(|err| {
             println!("Failed to init registration: {}", err);
         })?;
@@ -0,0 +1,203 @@
+use axum::extract::{Json, Path, State};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::domain::model::{Event, NewEvent};
+use crate::server::middleware::auth::AuthPubkey;
+use crate::server::AppState;
+use crate::server::ErrorResp;
+
+use super::{build_request_uri, parse_pubkey, TransactionResp};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EventResp {
+    pub pubkey: String,
+    pub organizer: String,
+    pub title: String,
+    pub starts_at: i64,
+    pub location_hint: String,
+    pub capacity: u32,
+    pub attendee_count: u32,
+    pub closed: bool,
+}
+
+impl From<Event> for EventResp {
+    fn from(value: Event) -> Self {
+        EventResp {
+            pubkey: value.pubkey.to_string(),
+            organizer: value.organizer.to_string(),
+            title: value.title,
+            starts_at: value.starts_at,
+            location_hint: value.location_hint,
+            capacity: value.capacity,
+            attendee_count: value.attendee_count,
+            closed: value.closed,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetEventsResp {
+    pub events: Vec<EventResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetEventAttendeesResp {
+    pub attendees: Vec<String>,
+}
+
+// Open events which haven't started yet.
+pub async fn get_events(State(state): State<AppState>) -> Result<Json<GetEventsResp>, ErrorResp> {
+    let events = state
+        .solana_service
+        .get_upcoming_events()
+        .inspect_err(|err| println!("Failed to read events: {}", err))?;
+
+    Ok(Json(GetEventsResp {
+        events: events.into_iter().map(EventResp::from).collect(),
+    }))
+}
+
+// Events the user RSVPed to.
+pub async fn get_user_events(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<GetEventsResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let events = state
+        .user_service
+        .get_events(&pubkey)
+        .inspect_err(|err| println!("Failed to read events: {}", err))?;
+
+    Ok(Json(GetEventsResp {
+        events: events.into_iter().map(EventResp::from).collect(),
+    }))
+}
+
+pub async fn get_event(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<EventResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let event = state
+        .solana_service
+        .get_event(&pubkey)
+        .inspect_err(|err| println!("Failed to read event: {}", err))?;
+
+    Ok(Json(EventResp::from(event)))
+}
+
+pub async fn get_event_attendees(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<GetEventAttendeesResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let attendees = state
+        .solana_service
+        .get_event_attendees(&pubkey)
+        .inspect_err(|err| println!("Failed to read event attendees: {}", err))?;
+
+    Ok(Json(GetEventAttendeesResp {
+        attendees: attendees.iter().map(|p| p.to_string()).collect(),
+    }))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PostEventReq {
+    pub title: String,
+    pub starts_at: i64, // unix timestamp
+    #[serde(default)]
+    pub location_hint: String,
+    pub capacity: u32,
+}
+
+// The event is organized by the owner of the access token.
+pub async fn post_event(
+    State(state): State<AppState>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+    Json(req): Json<PostEventReq>,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let organizer = authenticated(auth_pubkey)?;
+    let event = NewEvent {
+        title: req.title,
+        starts_at: req.starts_at,
+        location_hint: req.location_hint,
+        capacity: req.capacity,
+    };
+    let transaction_to_sign = state
+        .user_service
+        .create_event_init(&organizer, event)
+        .inspect_err(|err| println!("Failed to init event: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+pub async fn post_rsvp(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let event = parse_pubkey(&pubkey)?;
+    let attendee = authenticated(auth_pubkey)?;
+    let transaction_to_sign = state
+        .user_service
+        .rsvp_init(&attendee, &event)
+        .inspect_err(|err| println!("Failed to init RSVP: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+pub async fn post_cancel_rsvp(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let event = parse_pubkey(&pubkey)?;
+    let attendee = authenticated(auth_pubkey)?;
+    let transaction_to_sign = state
+        .user_service
+        .cancel_rsvp_init(&attendee, &event)
+        .inspect_err(|err| println!("Failed to init RSVP cancel: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+pub async fn post_close_event(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let event = parse_pubkey(&pubkey)?;
+    let organizer = authenticated(auth_pubkey)?;
+    let transaction_to_sign = state
+        .user_service
+        .close_event_init(&organizer, &event)
+        .inspect_err(|err| println!("Failed to init event close: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+// The routes changing events require a valid access token.
+fn authenticated(auth_pubkey: Option<Pubkey>) -> Result<Pubkey, ErrorResp> {
+    auth_pubkey.ok_or(ErrorResp::new(
+        StatusCode::FORBIDDEN,
+        "A valid access token is required",
+    ))
+}
@@ -0,0 +1,85 @@
+use axum::extract::{Json, Path, State};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::model::Group;
+use crate::server::AppState;
+use crate::server::ErrorResp;
+
+use super::parse_pubkey;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GroupResp {
+    pub pubkey: String,
+    pub admin: String,
+    pub name: String,
+    pub invite_only: bool,
+    pub max_members: u32,
+    pub member_count: u32,
+}
+
+impl From<Group> for GroupResp {
+    fn from(value: Group) -> Self {
+        GroupResp {
+            pubkey: value.pubkey.to_string(),
+            admin: value.admin.to_string(),
+            name: value.name,
+            invite_only: value.invite_only,
+            max_members: value.max_members,
+            member_count: value.member_count,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetGroupsResp {
+    pub groups: Vec<GroupResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetGroupMembersResp {
+    pub members: Vec<String>,
+}
+
+// Groups the user is a member of.
+pub async fn get_user_groups(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<GetGroupsResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let groups = state
+        .user_service
+        .get_groups(&pubkey)
+        .inspect_err(|err| println!("Failed to read groups: {}", err))?;
+
+    Ok(Json(GetGroupsResp {
+        groups: groups.into_iter().map(GroupResp::from).collect(),
+    }))
+}
+
+pub async fn get_group(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<GroupResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let group = state
+        .solana_service
+        .get_group(&pubkey)
+        .inspect_err(|err| println!("Failed to read group: {}", err))?;
+
+    Ok(Json(GroupResp::from(group)))
+}
+
+pub async fn get_group_members(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<GetGroupMembersResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let members = state
+        .solana_service
+        .get_group_members(&pubkey)
+        .inspect_err(|err| println!("Failed to read group members: {}", err))?;
+
+    Ok(Json(GetGroupMembersResp {
+        members: members.iter().map(|p| p.to_string()).collect(),
+    }))
+}
@@ -1,7 +1,13 @@
 pub mod auth;
+pub mod events;
+pub mod groups;
+pub mod polls;
+pub mod recovery;
 pub mod users;
+use crate::app_state::AppState;
 use crate::domain::error::Error;
 use crate::{domain::model, server::ErrorResp, utils};
+use axum::extract::{Json, State};
 use bincode::Options;
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
@@ -123,32 +129,67 @@ mod tests {
     }
 }
 
-// #[derive(Serialize, Deserialize, Clone)]
-// pub enum PostTransactionRespDataType {
-//     NoData,
-// }
-
-// #[derive(Serialize, Deserialize, Clone)]
-// pub struct PostTransactionResp {
-//     pub data_type: PostTransactionRespDataType,
-//     pub data: Vec<u8>,
-// }
-
-// impl PostTransactionResp {
-//     fn default() -> PostTransactionResp {
-//         PostTransactionResp {
-//             data_type: PostTransactionRespDataType::NoData,
-//             data: vec![],
-//         }
-//     }
-// }
-
-// #[axum_macros::debug_handler]
-// pub async fn post_transaction(
-
-//     State(state): State<AppState>,
-//     Json(req): Json<PostTransactionReq>,
-// ) -> Result<Json<PostTransactionResp>, ErrorResp> {
-//     state.solana_service.execute_transaction(wallet_pubkey, transaction_id, signed_transaction)
-//     Ok(Json(PostTransactionResp::default()))
-// }
+#[derive(Serialize, Deserialize, Clone)]
+pub enum PostTransactionRespDataType {
+    NoData,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PostTransactionResp {
+    pub data_type: PostTransactionRespDataType,
+    pub data: Vec<u8>,
+}
+
+impl PostTransactionResp {
+    fn default() -> PostTransactionResp {
+        PostTransactionResp {
+            data_type: PostTransactionRespDataType::NoData,
+            data: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PostTransactionReq {
+    data: SignedTransaction,
+    pubkey: String,
+}
+
+// Executes a transaction prepared by the backend and signed by the user.
+#[axum_macros::debug_handler]
+pub async fn post_transaction(
+    State(state): State<AppState>,
+    Json(req): Json<PostTransactionReq>,
+) -> Result<Json<PostTransactionResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&req.pubkey)?;
+    let (transaction_id, transaction) = req.data.parse().inspect_err(|err| {
+        println!("Failed to parse transaction: {}", err.error);
+    })?;
+
+    state
+        .solana_service
+        .execute_transaction(&pubkey, transaction_id, transaction)
+        .inspect_err(|err| {
+            println!("Failed to execute transaction: {}", err);
+        })?;
+
+    // The transaction might have changed the user's profile,
+    // it's already executed so only log the failure.
+    let _ = state
+        .user_service
+        .refresh_user(&pubkey)
+        .inspect_err(|err| println!("Failed to refresh user: {}", err));
+
+    Ok(Json(PostTransactionResp::default()))
+}
+
+// Builds the URI the signed transaction should be sent to.
+pub fn build_request_uri(state: &AppState, path: &str) -> String {
+    http::uri::Builder::new()
+        .authority(state.cfg.server_config.bind_address.clone())
+        .scheme(state.cfg.server_config.scheme.clone())
+        .path_and_query(path)
+        .build()
+        .expect("Host is validated by extractor, path should be always valid")
+        .to_string()
+}
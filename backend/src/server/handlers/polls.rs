@@ -0,0 +1,86 @@
+use axum::extract::{Json, Path, State};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::model::{Poll, PollOption};
+use crate::server::AppState;
+use crate::server::ErrorResp;
+
+use super::parse_pubkey;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PollResp {
+    pub pubkey: String,
+    pub creator: String,
+    pub group: Option<String>, // only its members can vote
+    pub question: String,
+    pub options: Vec<PollOptionResp>,
+    pub vote_count: u64,
+    pub closed: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PollOptionResp {
+    pub text: String,
+    pub votes: u32,
+}
+
+impl From<Poll> for PollResp {
+    fn from(value: Poll) -> Self {
+        PollResp {
+            pubkey: value.pubkey.to_string(),
+            creator: value.creator.to_string(),
+            group: value.group.map(|group| group.to_string()),
+            question: value.question,
+            vote_count: value.options.iter().map(|o| o.votes as u64).sum(),
+            options: value
+                .options
+                .into_iter()
+                .map(PollOptionResp::from)
+                .collect(),
+            closed: value.closed,
+        }
+    }
+}
+
+impl From<PollOption> for PollOptionResp {
+    fn from(value: PollOption) -> Self {
+        PollOptionResp {
+            text: value.text,
+            votes: value.votes,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetPollsResp {
+    pub polls: Vec<PollResp>,
+}
+
+pub async fn get_poll(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<PollResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let poll = state
+        .solana_service
+        .get_poll(&pubkey)
+        .inspect_err(|err| println!("Failed to read poll: {}", err))?;
+
+    Ok(Json(PollResp::from(poll)))
+}
+
+// Polls only the members of the group can vote in.
+pub async fn get_group_polls(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<GetPollsResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let polls = state
+        .solana_service
+        .get_group_polls(&pubkey)
+        .inspect_err(|err| println!("Failed to read group polls: {}", err))?;
+
+    Ok(Json(GetPollsResp {
+        polls: polls.into_iter().map(PollResp::from).collect(),
+    }))
+}
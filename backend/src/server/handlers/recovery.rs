@@ -0,0 +1,243 @@
+use axum::extract::{Json, Path, State};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::domain::model::{PendingRecovery, RecoveryStatus};
+use crate::server::middleware::auth::AuthPubkey;
+use crate::server::AppState;
+use crate::server::ErrorResp;
+
+use super::auth::LoginCompleteResp;
+use super::users::TransferCompleteReq;
+use super::{build_request_uri, parse_pubkey, TransactionResp};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecoveryStatusResp {
+    pub guardians: Vec<String>,
+    pub threshold: u8,
+    pub pending: Option<PendingRecoveryResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PendingRecoveryResp {
+    pub pubkey: String,
+    pub new_pubkey: String,
+    pub proposer: String,
+    pub approvals: Vec<String>,
+    pub executable_at_slot: u64,
+}
+
+impl From<RecoveryStatus> for RecoveryStatusResp {
+    fn from(value: RecoveryStatus) -> Self {
+        RecoveryStatusResp {
+            guardians: value.guardians.iter().map(|p| p.to_string()).collect(),
+            threshold: value.threshold,
+            pending: value.pending.map(PendingRecoveryResp::from),
+        }
+    }
+}
+
+impl From<PendingRecovery> for PendingRecoveryResp {
+    fn from(value: PendingRecovery) -> Self {
+        PendingRecoveryResp {
+            pubkey: value.pubkey.to_string(),
+            new_pubkey: value.new_owner.to_string(),
+            proposer: value.proposer.to_string(),
+            approvals: value.approvals.iter().map(|p| p.to_string()).collect(),
+            executable_at_slot: value.executable_at_slot,
+        }
+    }
+}
+
+// Guardians of the user and the recovery they are working on.
+pub async fn get_recovery(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<RecoveryStatusResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let status = state
+        .user_service
+        .get_recovery_status(&pubkey)
+        .inspect_err(|err| println!("Failed to read recovery: {}", err))?;
+
+    Ok(Json(RecoveryStatusResp::from(status)))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GuardiansReq {
+    pub guardians: Vec<String>, // empty to turn the recovery off
+    pub threshold: u8,
+}
+
+pub async fn post_guardians(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+    Json(req): Json<GuardiansReq>,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    if auth_pubkey != Some(pubkey) {
+        return Err(ErrorResp::new(
+            StatusCode::FORBIDDEN,
+            "Only the owner can set the guardians",
+        ));
+    }
+    let guardians = req
+        .guardians
+        .iter()
+        .map(parse_pubkey)
+        .collect::<Result<Vec<Pubkey>, _>>()?;
+
+    let transaction_to_sign = state
+        .user_service
+        .set_guardians_init(&pubkey, guardians, req.threshold)
+        .inspect_err(|err| println!("Failed to init setting guardians: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RecoveryReq {
+    pub new_pubkey: String, // wallet the profile moves to
+}
+
+// Signed by a guardian of the user.
+pub async fn post_recovery(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+    Json(req): Json<RecoveryReq>,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let (guardian, pubkey) = parse_recovery_req(&pubkey, auth_pubkey)?;
+    let new_pubkey = parse_pubkey(&req.new_pubkey)?;
+    let transaction_to_sign = state
+        .user_service
+        .propose_recovery_init(&guardian, &pubkey, &new_pubkey)
+        .inspect_err(|err| println!("Failed to init recovery proposal: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+// Signed by a guardian of the user.
+pub async fn post_approve_recovery(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let (guardian, pubkey) = parse_recovery_req(&pubkey, auth_pubkey)?;
+    let transaction_to_sign = state
+        .user_service
+        .approve_recovery_init(&guardian, &pubkey)
+        .inspect_err(|err| println!("Failed to init recovery approval: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+// Signed by the owner or the guardian who proposed the recovery.
+pub async fn post_cancel_recovery(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let (signer, pubkey) = parse_recovery_req(&pubkey, auth_pubkey)?;
+    let transaction_to_sign = state
+        .user_service
+        .cancel_recovery_init(&signer, &pubkey)
+        .inspect_err(|err| println!("Failed to init recovery cancellation: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+// Signed by the new wallet, which has no access token yet.
+pub async fn post_execute_recovery(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    Json(req): Json<RecoveryReq>,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let new_pubkey = parse_pubkey(&req.new_pubkey)?;
+    let transaction_to_sign = state
+        .user_service
+        .execute_recovery_init(&pubkey, &new_pubkey)
+        .inspect_err(|err| println!("Failed to init recovery execution: {}", err))?;
+
+    // We want /recovery/execute/complete to be called next
+    let request_uri = build_request_uri(
+        &state,
+        &format!("/api/v1/user/{}/recovery/execute/complete", pubkey),
+    );
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+// Executes the recovery and re-keys the user, the tokens are issued for the new wallet.
+// The transaction signed by the new wallet proves the caller holds its key.
+pub async fn post_execute_recovery_complete(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    Json(req): Json<TransferCompleteReq>,
+) -> Result<Json<LoginCompleteResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let new_pubkey = parse_pubkey(&req.new_pubkey)?;
+    let (transaction_id, transaction) = req.data.parse().inspect_err(|err| {
+        println!("Failed to parse transaction: {}", err.error);
+    })?;
+
+    let created_at_slot = state
+        .user_service
+        .check_recovery_transaction(&pubkey, &new_pubkey, &transaction)
+        .inspect_err(|err| {
+            println!("Failed to check recovery transaction: {}", err);
+        })?;
+    state
+        .solana_service
+        .execute_transaction(&new_pubkey, transaction_id, transaction)
+        .inspect_err(|err| {
+            println!("Failed to execute recovery transaction: {}", err);
+        })?;
+
+    let tokens = state
+        .user_service
+        .execute_recovery_complete(&pubkey, &new_pubkey, created_at_slot)
+        .inspect_err(|err| {
+            println!("Failed to complete recovery: {}", err);
+        })?;
+
+    Ok(Json(LoginCompleteResp {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        token_type: state.cfg.server_config.access_token_type,
+    }))
+}
+
+// The program decides who can sign, the token only identifies the signer.
+fn parse_recovery_req(
+    pubkey: &String,
+    auth_pubkey: Option<Pubkey>,
+) -> Result<(Pubkey, Pubkey), ErrorResp> {
+    let pubkey = parse_pubkey(pubkey)?;
+    let signer = auth_pubkey.ok_or(ErrorResp::new(
+        StatusCode::FORBIDDEN,
+        "A valid access token is required",
+    ))?;
+    Ok((signer, pubkey))
+}
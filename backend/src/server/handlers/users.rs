@@ -1,17 +1,40 @@
 use axum::extract::{Json, Path, State};
+use http::StatusCode;
 use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
 
+use crate::domain::model::{Endorsements, ProfileUpdate};
 use crate::server::middleware::auth::AuthPubkey;
 use crate::server::AppState;
 use crate::server::ErrorResp;
 
-use super::parse_pubkey;
+use super::auth::LoginCompleteResp;
+use super::{build_request_uri, parse_pubkey, SignedTransaction, TransactionResp};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetUserResp {
     pub owned: bool,
     pub pubkey: String,
     pub username: String,
+    // None if the user isn't registered on-chain or the chain can't be read
+    pub endorsements: Option<EndorsementsResp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EndorsementsResp {
+    pub count: u64,
+    pub received: Vec<String>,
+    pub given: Vec<String>,
+}
+
+impl From<Endorsements> for EndorsementsResp {
+    fn from(value: Endorsements) -> Self {
+        EndorsementsResp {
+            count: value.count,
+            received: value.received.iter().map(|p| p.to_string()).collect(),
+            given: value.given.iter().map(|p| p.to_string()).collect(),
+        }
+    }
 }
 
 pub async fn get_user(
@@ -21,10 +44,231 @@ pub async fn get_user(
 ) -> Result<Json<GetUserResp>, ErrorResp> {
     let pubkey = parse_pubkey(&pubkey)?;
     let user = state.user_service.get_user(&pubkey)?;
+    // The user is still returned when the chain can't be read.
+    let endorsements = user.pda_pubkey.and_then(|_| {
+        state
+            .user_service
+            .get_endorsements(&pubkey)
+            .inspect_err(|err| println!("Failed to read endorsements: {}", err))
+            .ok()
+    });
 
     Ok(Json(GetUserResp {
         owned: auth_pubkey.is_some_and(|a| a == pubkey),
         pubkey: user.pubkey.to_string(),
         username: user.username,
+        endorsements: endorsements.map(EndorsementsResp::from),
+    }))
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PatchUserReq {
+    pub username: Option<String>,
+    pub bio: Option<String>,
+    pub interests: Option<Vec<String>>,
+}
+
+pub async fn patch_user(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+    Json(req): Json<PatchUserReq>,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    if !auth_pubkey.is_some_and(|a| a == pubkey) {
+        return Err(ErrorResp::new(
+            StatusCode::FORBIDDEN,
+            "Only the owner can update the profile",
+        ));
+    }
+
+    let update = ProfileUpdate {
+        username: req.username,
+        bio: req.bio,
+        interests: req.interests,
+    };
+    let transaction_to_sign = state
+        .user_service
+        .update_profile_init(&pubkey, update)
+        .inspect_err(|err| {
+            println!("Failed to init profile update: {}", err);
+        })?;
+
+    // The signed transaction is executed by /transaction
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetBlockedUsersResp {
+    pub blocked: Vec<String>,
+}
+
+pub async fn get_blocked_users(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<GetBlockedUsersResp>, ErrorResp> {
+    let pubkey = parse_pubkey(&pubkey)?;
+    let blocked = state
+        .user_service
+        .get_blocked_users(&pubkey)
+        .inspect_err(|err| println!("Failed to read block list: {}", err))?;
+
+    Ok(Json(GetBlockedUsersResp {
+        blocked: blocked.iter().map(|p| p.to_string()).collect(),
+    }))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockReq {
+    pub pubkey: String, // wallet of the user to block or unblock
+}
+
+pub async fn post_block(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+    Json(req): Json<BlockReq>,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let (pubkey, user) = parse_block_req(&pubkey, auth_pubkey, &req)?;
+    let transaction_to_sign = state
+        .user_service
+        .block_init(&pubkey, &user)
+        .inspect_err(|err| println!("Failed to init block: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+pub async fn post_unblock(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+    Json(req): Json<BlockReq>,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let (pubkey, user) = parse_block_req(&pubkey, auth_pubkey, &req)?;
+    let transaction_to_sign = state
+        .user_service
+        .unblock_init(&pubkey, &user)
+        .inspect_err(|err| println!("Failed to init unblock: {}", err))?;
+
+    let request_uri = build_request_uri(&state, "/api/v1/transaction");
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TransferReq {
+    pub new_pubkey: String, // wallet the profile moves to
+}
+
+// Moves the profile to a new wallet, e.g. when the current one is rotated.
+pub async fn post_transfer(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+    Json(req): Json<TransferReq>,
+) -> Result<Json<TransactionResp>, ErrorResp> {
+    let (pubkey, new_pubkey) = parse_transfer_req(&pubkey, auth_pubkey, &req.new_pubkey)?;
+    let transaction_to_sign = state
+        .user_service
+        .transfer_ownership_init(&pubkey, &new_pubkey)
+        .inspect_err(|err| println!("Failed to init ownership transfer: {}", err))?;
+
+    // We want /transfer/complete to be called next
+    let request_uri = build_request_uri(
+        &state,
+        &format!("/api/v1/user/{}/transfer/complete", pubkey),
+    );
+    Ok(Json(
+        TransactionResp::new(&transaction_to_sign, request_uri)
+            .inspect_err(|e| println!("{}", e.error))?,
+    ))
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TransferCompleteReq {
+    pub data: SignedTransaction,
+    pub new_pubkey: String,
+}
+
+// Executes the transfer and re-keys the user, the tokens are issued for the new wallet.
+pub async fn post_transfer_complete(
+    State(state): State<AppState>,
+    Path(pubkey): Path<String>,
+    AuthPubkey(auth_pubkey): AuthPubkey,
+    Json(req): Json<TransferCompleteReq>,
+) -> Result<Json<LoginCompleteResp>, ErrorResp> {
+    let (pubkey, new_pubkey) = parse_transfer_req(&pubkey, auth_pubkey, &req.new_pubkey)?;
+    let (transaction_id, transaction) = req.data.parse().inspect_err(|err| {
+        println!("Failed to parse transaction: {}", err.error);
+    })?;
+
+    state
+        .user_service
+        .check_pending_transfer(&pubkey, &new_pubkey, transaction_id)
+        .inspect_err(|err| {
+            println!("Failed to check ownership transfer transaction: {}", err);
+        })?;
+    state
+        .solana_service
+        .execute_transaction(&pubkey, transaction_id, transaction)
+        .inspect_err(|err| {
+            println!("Failed to execute ownership transfer transaction: {}", err);
+        })?;
+
+    let tokens = state
+        .user_service
+        .transfer_ownership_complete(&pubkey, &new_pubkey, transaction_id)
+        .inspect_err(|err| {
+            println!("Failed to complete ownership transfer: {}", err);
+        })?;
+
+    Ok(Json(LoginCompleteResp {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        token_type: state.cfg.server_config.access_token_type,
     }))
 }
+
+// Only the owner can transfer their profile.
+fn parse_transfer_req(
+    pubkey: &String,
+    auth_pubkey: Option<Pubkey>,
+    new_pubkey: &String,
+) -> Result<(Pubkey, Pubkey), ErrorResp> {
+    let pubkey = parse_pubkey(pubkey)?;
+    if auth_pubkey != Some(pubkey) {
+        return Err(ErrorResp::new(
+            StatusCode::FORBIDDEN,
+            "Only the owner can transfer the profile",
+        ));
+    }
+    let new_pubkey = parse_pubkey(new_pubkey)?;
+    Ok((pubkey, new_pubkey))
+}
+
+// Only the owner can change their block list.
+fn parse_block_req(
+    pubkey: &String,
+    auth_pubkey: Option<Pubkey>,
+    req: &BlockReq,
+) -> Result<(Pubkey, Pubkey), ErrorResp> {
+    let pubkey = parse_pubkey(pubkey)?;
+    if auth_pubkey != Some(pubkey) {
+        return Err(ErrorResp::new(
+            StatusCode::FORBIDDEN,
+            "Only the owner can change the block list",
+        ));
+    }
+    let user = parse_pubkey(&req.pubkey)?;
+    Ok((pubkey, user))
+}
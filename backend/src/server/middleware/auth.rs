@@ -34,6 +34,11 @@ impl AuthMiddlewareConfig {
         self
     }
 
+    pub fn with_allow_unauth(&mut self, allow_unauth: bool) -> &mut Self {
+        self.allow_unauth = allow_unauth;
+        self
+    }
+
     pub fn with_aritificial_time(&mut self, unix_ts: u64) -> &mut Self {
         self.artificial_time = Some(jwt_simple::prelude::UnixTimeStamp::new(unix_ts, 0));
         self
@@ -73,10 +78,9 @@ impl AuthMiddlewareConfig {
     }
 }
 
-use bytes::Bytes;
+use axum::body::Body;
 use futures_util::future::BoxFuture;
 use http::{request::Parts, HeaderValue, Request, Response, StatusCode};
-use http_body_util::Full;
 use tower_http::auth::AsyncAuthorizeRequest;
 
 #[derive(Clone)]
@@ -92,10 +96,10 @@ impl AppAuth {
 
 impl<B> AsyncAuthorizeRequest<B> for AppAuth
 where
-    B: Send + Sync + 'static,
+    B: Send + 'static,
 {
     type RequestBody = B;
-    type ResponseBody = Full<Bytes>;
+    type ResponseBody = Body;
     type Future = BoxFuture<'static, Result<Request<B>, Response<Self::ResponseBody>>>;
 
     fn authorize(&mut self, mut request: Request<B>) -> Self::Future {
@@ -104,7 +108,9 @@ where
             let res = validate_auth_token(cfg.to_owned(), &mut request);
             if let Err(err) = res {
                 if cfg.allow_unauth {
-                    // we can skip the validation error
+                    // we can skip the validation error,
+                    // but the pubkey header can't come from the client
+                    let _ = request.headers_mut().remove(USER_PUBKEY_HEADER);
                     return Ok(request);
                 }
                 let status = match err {
@@ -113,7 +119,7 @@ where
                 };
                 let resp = http::Response::builder()
                     .status(status)
-                    .body(Full::<Bytes>::new(err.to_string().into()))
+                    .body(Body::from(err.to_string()))
                     .expect("Failed to build an error http response");
                 Err(resp)
             } else {
@@ -128,7 +134,7 @@ fn validate_auth_token<B>(
     request: &mut Request<B>,
 ) -> Result<(), AuthError>
 where
-    B: Send + Sync + 'static,
+    B: Send + 'static,
 {
     let headers = request.headers_mut();
     let token = headers.get(http::header::AUTHORIZATION);
@@ -227,7 +233,7 @@ mod tests {
 
     pub async fn test_handler(
         req: Request<http_body_util::Full<bytes::Bytes>>,
-    ) -> Result<Response<http_body_util::Full<bytes::Bytes>>, tower::BoxError> {
+    ) -> Result<Response<axum::body::Body>, tower::BoxError> {
         let user_header = req
             .headers()
             .get(USER_PUBKEY_HEADER)
@@ -242,7 +248,7 @@ mod tests {
             // this should never happen - handler won't be called if auth is invalid
             res = res.status(StatusCode::INTERNAL_SERVER_ERROR);
         }
-        Ok(res.body(http_body_util::Full::new(body)).unwrap())
+        Ok(res.body(axum::body::Body::from(body)).unwrap())
     }
 
     #[tokio::test]
@@ -271,8 +277,7 @@ mod tests {
         assert_eq!(res.status(), StatusCode::OK);
 
         let pubkey = String::from_utf8(
-            res.body()
-                .to_owned()
+            res.into_body()
                 .collect()
                 .await
                 .unwrap()
@@ -309,8 +314,7 @@ mod tests {
         assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
 
         let error_msg = String::from_utf8(
-            res.body()
-                .to_owned()
+            res.into_body()
                 .collect()
                 .await
                 .unwrap()
@@ -353,8 +357,7 @@ mod tests {
         assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
 
         let error_msg = String::from_utf8(
-            res.body()
-                .to_owned()
+            res.into_body()
                 .collect()
                 .await
                 .unwrap()
@@ -389,8 +392,7 @@ mod tests {
         assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
 
         let error_msg = String::from_utf8(
-            res.body()
-                .to_owned()
+            res.into_body()
                 .collect()
                 .await
                 .unwrap()
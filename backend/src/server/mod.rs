@@ -5,7 +5,7 @@ use std::sync::Arc;
 
 use crate::app_state::AppState;
 use axum::{
-    routing::{get, post},
+    routing::{get, patch, post},
     Router,
 };
 
@@ -63,16 +63,119 @@ impl Server {
                 post(handlers::auth::post_register_complete),
             );
 
-        let user_router = Router::new().route(
-            "/:pubkey",
-            get(handlers::users::get_user), //.patch(handlers::users::patch_user),
-        );
-        // .layer(tower_http::auth::AsyncRequireAuthorizationLayer::new(
-        //     middleware::auth::AppAuth::new(auth_config),
-        // ));
+        // Modifying a user requires a valid access token,
+        // reading works without it.
+        let auth_layer = |auth_config| {
+            tower_http::auth::AsyncRequireAuthorizationLayer::new(middleware::auth::AppAuth::new(
+                auth_config,
+            ))
+        };
+        let optional_auth_config = cfg.auth_config.clone().with_allow_unauth(true).to_owned();
+        let user_router = Router::new()
+            .route(
+                "/:pubkey",
+                get(handlers::users::get_user)
+                    .route_layer(auth_layer(optional_auth_config.clone())),
+            )
+            .route(
+                "/:pubkey",
+                patch(handlers::users::patch_user).route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route(
+                "/:pubkey/blocked",
+                get(handlers::users::get_blocked_users)
+                    .route_layer(auth_layer(optional_auth_config)),
+            )
+            .route("/:pubkey/groups", get(handlers::groups::get_user_groups))
+            .route("/:pubkey/events", get(handlers::events::get_user_events))
+            .route(
+                "/:pubkey/block",
+                post(handlers::users::post_block).route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route(
+                "/:pubkey/unblock",
+                post(handlers::users::post_unblock)
+                    .route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route(
+                "/:pubkey/transfer",
+                post(handlers::users::post_transfer)
+                    .route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route(
+                "/:pubkey/transfer/complete",
+                post(handlers::users::post_transfer_complete)
+                    .route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route("/:pubkey/recovery", get(handlers::recovery::get_recovery))
+            .route(
+                "/:pubkey/guardians",
+                post(handlers::recovery::post_guardians)
+                    .route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route(
+                "/:pubkey/recovery",
+                post(handlers::recovery::post_recovery)
+                    .route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route(
+                "/:pubkey/recovery/approve",
+                post(handlers::recovery::post_approve_recovery)
+                    .route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route(
+                "/:pubkey/recovery/cancel",
+                post(handlers::recovery::post_cancel_recovery)
+                    .route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            // The new wallet has no access token, it signs the transaction instead.
+            .route(
+                "/:pubkey/recovery/execute",
+                post(handlers::recovery::post_execute_recovery),
+            )
+            .route(
+                "/:pubkey/recovery/execute/complete",
+                post(handlers::recovery::post_execute_recovery_complete),
+            );
+        // Groups are public, no token is needed to read them.
+        let group_router = Router::new()
+            .route("/:pubkey", get(handlers::groups::get_group))
+            .route("/:pubkey/members", get(handlers::groups::get_group_members))
+            .route("/:pubkey/polls", get(handlers::polls::get_group_polls));
+        // Poll results are public as well.
+        let poll_router = Router::new().route("/:pubkey", get(handlers::polls::get_poll));
+        // Browsing events is public, changing them requires a valid access token.
+        let event_router = Router::new()
+            .route("/", get(handlers::events::get_events))
+            .route(
+                "/",
+                post(handlers::events::post_event).route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route("/:pubkey", get(handlers::events::get_event))
+            .route(
+                "/:pubkey/attendees",
+                get(handlers::events::get_event_attendees),
+            )
+            .route(
+                "/:pubkey/rsvp",
+                post(handlers::events::post_rsvp).route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route(
+                "/:pubkey/rsvp/cancel",
+                post(handlers::events::post_cancel_rsvp)
+                    .route_layer(auth_layer(cfg.auth_config.clone())),
+            )
+            .route(
+                "/:pubkey/close",
+                post(handlers::events::post_close_event).route_layer(auth_layer(cfg.auth_config)),
+            );
         let router = Router::new()
             .route("/", get(handlers::handler))
+            .route("/api/v1/transaction", post(handlers::post_transaction))
             .nest("/api/v1/user", user_router)
+            .nest("/api/v1/group", group_router)
+            .nest("/api/v1/event", event_router)
+            .nest("/api/v1/poll", poll_router)
             .nest("/api/v1/auth", auth_routes);
 
         router
@@ -131,17 +234,31 @@ impl From<crate::domain::error::Error> for ErrorResp {
         let status = match &value {
             crate::domain::error::Error::GeneralError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             crate::domain::error::Error::InvalidPubKey(_) => StatusCode::BAD_REQUEST,
+            crate::domain::error::Error::InvalidUsername(_) => StatusCode::BAD_REQUEST,
+            crate::domain::error::Error::UsernameTaken => StatusCode::CONFLICT,
+            crate::domain::error::Error::InvalidProfile(_) => StatusCode::BAD_REQUEST,
+            crate::domain::error::Error::InvalidEvent(_) => StatusCode::BAD_REQUEST,
+            crate::domain::error::Error::InvalidInviteCode(_) => StatusCode::BAD_REQUEST,
             crate::domain::error::Error::UserNotFound => StatusCode::NOT_FOUND,
             crate::domain::error::Error::UserAlreadyInitialized => StatusCode::BAD_REQUEST,
             crate::domain::error::Error::TransactionNotFound => StatusCode::NOT_FOUND,
             crate::domain::error::Error::InvalidTransaction(_) => StatusCode::BAD_REQUEST,
             crate::domain::error::Error::TransactionExpired => StatusCode::FORBIDDEN,
+            crate::domain::error::Error::ProgramPaused => StatusCode::SERVICE_UNAVAILABLE,
+            crate::domain::error::Error::SessionExpired => StatusCode::FORBIDDEN,
+            crate::domain::error::Error::Blocked => StatusCode::FORBIDDEN,
             crate::domain::error::Error::WalletNotFound => StatusCode::NOT_FOUND,
             crate::domain::error::Error::WalletInsufficientFounds => StatusCode::CONFLICT,
             crate::domain::error::Error::UserNotConfirmed => StatusCode::CONFLICT,
             crate::domain::error::Error::InvalidAuthToken => StatusCode::FORBIDDEN,
             crate::domain::error::Error::AuthTokenExpired => StatusCode::FORBIDDEN,
             crate::domain::error::Error::InvalidSignature => StatusCode::FORBIDDEN,
+            crate::domain::error::Error::AccountNotFound(_) => StatusCode::NOT_FOUND,
+            crate::domain::error::Error::AccountAlreadyInitialized(_) => StatusCode::CONFLICT,
+            crate::domain::error::Error::AccountDataTooSmall(_) => StatusCode::CONFLICT,
+            crate::domain::error::Error::InvalidAccount(_, _) => StatusCode::BAD_REQUEST,
+            crate::domain::error::Error::InvalidAccountState(_) => StatusCode::CONFLICT,
+            crate::domain::error::Error::Unauthorized(_) => StatusCode::FORBIDDEN,
         };
 
         let mut error_resp = value.to_string();
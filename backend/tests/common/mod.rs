@@ -19,6 +19,7 @@ use solana_sdk::transaction::Transaction;
 use solana_sdk::{signature::Keypair, signer::Signer};
 use std::borrow::BorrowMut;
 use std::process::Command;
+use std::sync::Arc;
 
 pub struct TestServerBuilder {
     cfg: Option<app_state::Config>,
@@ -71,27 +72,43 @@ impl TestServerBuilder {
     }
 
     pub fn build(&mut self) -> TestServer {
-        let auth_secret = self
-            .auth_secret
-            .take()
-            .unwrap_or(HS256Key::generate())
-            .to_bytes();
+        let auth_secret = self.auth_secret.take().unwrap_or(HS256Key::generate());
         let program_keypair = self.program_keypair.take().unwrap_or_else(|| {
             // relative to Cargo
-            let keypair_path = "solana_program/target/deploy/solana_program-keypair.json";
+            let keypair_path = "solana_program/target/deploy/anti_loneliness_solana_program-keypair.json";
             solana_sdk::signer::keypair::read_keypair_file(keypair_path).unwrap()
         });
-        let cfg = self.cfg.take().unwrap_or(app_state::Config::default());
+        let mut cfg = self.cfg.take().unwrap_or(app_state::Config::default());
+        cfg.server_config =
+            server::Config::default_with_auth_key(Arc::new(auth_secret.clone()));
         let solana_repo = self.solana_repo.take().unwrap_or(repo::solana::Repo::new());
         let user_repo = self.user_repo.take().unwrap_or(repo::user::Repo::new());
         let app = app_state::AppStateBuiler::new()
             .with_solana_repo(solana_repo)
             .with_user_repo(user_repo)
-            .build(cfg, auth_secret, program_keypair);
+            .build(cfg.clone(), auth_secret.to_bytes(), program_keypair);
 
-        let router = server::Server::new_stateless_router().with_state(app);
+        let router = server::Server::new_stateless_router(cfg.server_config).with_state(app);
 
         let test_server = axum_test::TestServer::new(router).unwrap();
         test_server
     }
 }
+
+// Issues an access token the same way the user service does.
+pub fn generate_access_token(auth_secret: &HS256Key, pubkey: &solana_sdk::pubkey::Pubkey) -> String {
+    use jwt_simple::prelude::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct UserClaims {
+        pubkey: String,
+    }
+    let claims = Claims::with_custom_claims(
+        UserClaims {
+            pubkey: pubkey.to_string(),
+        },
+        Duration::from_secs(3600),
+    )
+    .with_issuer("anti-loneliness");
+    auth_secret.authenticate(claims).unwrap()
+}
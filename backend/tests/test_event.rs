@@ -0,0 +1,47 @@
+use anti_loneliness_solana_dapp::repo;
+use http::StatusCode;
+use serde_json::json;
+use solana_sdk::{signature::Keypair, signer::Signer};
+
+mod common;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_get_event_invalid_pubkey() {
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(repo::user::Repo::new())
+        .build();
+
+    for uri in [
+        "/api/v1/event/not-a-pubkey",
+        "/api/v1/event/not-a-pubkey/attendees",
+        "/api/v1/user/not-a-pubkey/events",
+    ] {
+        let response = test_server.get(uri).await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_change_event_unauthorized() {
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(repo::user::Repo::new())
+        .build();
+
+    let response = test_server
+        .post("/api/v1/event")
+        .json(&json!({
+            "title": "Picnic",
+            "starts_at": 4_000_000_000i64,
+            "capacity": 10,
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let event = Keypair::new().pubkey();
+    for path in ["rsvp", "rsvp/cancel", "close"] {
+        let response = test_server
+            .post(&format!("/api/v1/event/{}/{}", event, path))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,22 @@
+use anti_loneliness_solana_dapp::repo;
+use http::StatusCode;
+
+mod common;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_get_group_invalid_pubkey() {
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(repo::user::Repo::new())
+        .build();
+
+    for uri in [
+        "/api/v1/group/not-a-pubkey",
+        "/api/v1/group/not-a-pubkey/members",
+        "/api/v1/group/not-a-pubkey/polls",
+        "/api/v1/poll/not-a-pubkey",
+        "/api/v1/user/not-a-pubkey/groups",
+    ] {
+        let response = test_server.get(uri).await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -34,6 +34,7 @@ async fn test_login_success() {
                 .checked_add(std::time::Duration::from_secs(3600))
                 .unwrap(),
         }),
+        pending_transfer: None,
     };
 
     let user_repo = repo::user::Repo::new();
@@ -93,7 +94,7 @@ async fn test_login_success() {
     // check if this token works
     // GET USER
     let response = test_server
-        .get(format!("/api/v1/users/{}", wallet.pubkey().to_string()).as_str())
+        .get(format!("/api/v1/user/{}", wallet.pubkey().to_string()).as_str())
         .add_header(
             "Authorization",
             format!("Bearer {}", login_compl_resp.access_token),
@@ -0,0 +1,354 @@
+use anti_loneliness_solana_dapp::domain;
+use anti_loneliness_solana_dapp::repo;
+use anti_loneliness_solana_dapp::server;
+use anti_loneliness_solana_dapp::utils;
+use http::StatusCode;
+use jwt_simple::prelude::HS256Key;
+use serde_json::json;
+use solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction};
+
+mod common;
+
+fn confirmed_user(wallet: &Keypair) -> domain::model::User {
+    domain::model::User {
+        pubkey: wallet.pubkey(),
+        username: "User1".to_string(),
+        pda_pubkey: Some(wallet.pubkey()),
+        refresh_token: None,
+        pending_transfer: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_get_user_owned() {
+    let wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo.add_user(confirmed_user(&wallet)).unwrap();
+
+    let auth_secret = HS256Key::generate();
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .with_auth_secret(&auth_secret)
+        .build();
+    let uri = format!("/api/v1/user/{}", wallet.pubkey());
+
+    // With a valid token
+    let access_token = common::generate_access_token(&auth_secret, &wallet.pubkey());
+    let response = test_server
+        .get(&uri)
+        .add_header("Authorization", format!("Bearer {}", access_token))
+        .await;
+    response.assert_status_ok();
+    let get_user_resp: server::handlers::users::GetUserResp = response.json();
+    assert!(get_user_resp.owned);
+    assert_eq!(get_user_resp.username, "User1");
+    // The user has no PDA on-chain
+    assert!(get_user_resp.endorsements.is_none());
+
+    // Without a token
+    let response = test_server.get(&uri).await;
+    response.assert_status_ok();
+    let get_user_resp: server::handlers::users::GetUserResp = response.json();
+    assert!(!get_user_resp.owned);
+
+    // The pubkey header can't be set by the client
+    let response = test_server
+        .get(&uri)
+        .add_header("user-pubkey", wallet.pubkey().to_string())
+        .await;
+    response.assert_status_ok();
+    let get_user_resp: server::handlers::users::GetUserResp = response.json();
+    assert!(!get_user_resp.owned);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_patch_user_unauthorized() {
+    let wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo.add_user(confirmed_user(&wallet)).unwrap();
+
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .build();
+
+    let response = test_server
+        .patch(&format!("/api/v1/user/{}", wallet.pubkey()))
+        .json(&json!({
+            "bio": "Looking for hiking buddies",
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_patch_user_not_owned() {
+    let wallet = Keypair::new();
+    let other_wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo.add_user(confirmed_user(&wallet)).unwrap();
+
+    let auth_secret = HS256Key::generate();
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .with_auth_secret(&auth_secret)
+        .build();
+
+    // Token issued for a different wallet
+    let access_token = common::generate_access_token(&auth_secret, &other_wallet.pubkey());
+    let response = test_server
+        .patch(&format!("/api/v1/user/{}", wallet.pubkey()))
+        .add_header("Authorization", format!("Bearer {}", access_token))
+        .json(&json!({
+            "bio": "Looking for hiking buddies",
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_block_unauthorized() {
+    let wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo.add_user(confirmed_user(&wallet)).unwrap();
+
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .build();
+
+    for path in ["block", "unblock"] {
+        let response = test_server
+            .post(&format!("/api/v1/user/{}/{}", wallet.pubkey(), path))
+            .json(&json!({
+                "pubkey": Keypair::new().pubkey().to_string(),
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_block_not_owned() {
+    let wallet = Keypair::new();
+    let other_wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo.add_user(confirmed_user(&wallet)).unwrap();
+
+    let auth_secret = HS256Key::generate();
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .with_auth_secret(&auth_secret)
+        .build();
+
+    // Only the owner can block users on their behalf
+    let access_token = common::generate_access_token(&auth_secret, &other_wallet.pubkey());
+    for path in ["block", "unblock"] {
+        let response = test_server
+            .post(&format!("/api/v1/user/{}/{}", wallet.pubkey(), path))
+            .add_header("Authorization", format!("Bearer {}", access_token))
+            .json(&json!({
+                "pubkey": other_wallet.pubkey().to_string(),
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transfer_not_owned() {
+    let wallet = Keypair::new();
+    let other_wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo.add_user(confirmed_user(&wallet)).unwrap();
+
+    let auth_secret = HS256Key::generate();
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .with_auth_secret(&auth_secret)
+        .build();
+    let uri = format!("/api/v1/user/{}/transfer", wallet.pubkey());
+    let req = json!({
+        "new_pubkey": other_wallet.pubkey().to_string(),
+    });
+
+    // Without a token
+    let response = test_server.post(&uri).json(&req).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Only the owner can transfer the profile
+    let access_token = common::generate_access_token(&auth_secret, &other_wallet.pubkey());
+    let response = test_server
+        .post(&uri)
+        .add_header("Authorization", format!("Bearer {}", access_token))
+        .json(&req)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transfer_to_registered_user() {
+    let wallet = Keypair::new();
+    let other_wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo.add_user(confirmed_user(&wallet)).unwrap();
+    user_repo.add_user(confirmed_user(&other_wallet)).unwrap();
+
+    let auth_secret = HS256Key::generate();
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .with_auth_secret(&auth_secret)
+        .build();
+
+    // A wallet can't hold two profiles
+    let access_token = common::generate_access_token(&auth_secret, &wallet.pubkey());
+    let response = test_server
+        .post(&format!("/api/v1/user/{}/transfer", wallet.pubkey()))
+        .add_header("Authorization", format!("Bearer {}", access_token))
+        .json(&json!({
+            "new_pubkey": other_wallet.pubkey().to_string(),
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_transfer_complete_not_prepared() {
+    let wallet = Keypair::new();
+    let other_wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo
+        .add_user(domain::model::User {
+            pending_transfer: Some(domain::model::PendingTransfer {
+                new_pubkey: other_wallet.pubkey(),
+                transaction_id: uuid::Uuid::new_v4(),
+                created_at_slot: 1,
+            }),
+            ..confirmed_user(&wallet)
+        })
+        .unwrap();
+
+    let auth_secret = HS256Key::generate();
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .with_auth_secret(&auth_secret)
+        .build();
+    let access_token = common::generate_access_token(&auth_secret, &wallet.pubkey());
+    let transaction = utils::bincode::serialize(&Transaction::default()).unwrap();
+
+    // Only the transaction prepared for the transfer completes it
+    let response = test_server
+        .post(&format!(
+            "/api/v1/user/{}/transfer/complete",
+            wallet.pubkey()
+        ))
+        .add_header("Authorization", format!("Bearer {}", access_token))
+        .json(&json!({
+            "data": {
+                "transaction": transaction,
+                "transaction_id": uuid::Uuid::new_v4().to_string(),
+            },
+            "new_pubkey": other_wallet.pubkey().to_string(),
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_set_guardians_not_owned() {
+    let wallet = Keypair::new();
+    let other_wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo.add_user(confirmed_user(&wallet)).unwrap();
+
+    let auth_secret = HS256Key::generate();
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .with_auth_secret(&auth_secret)
+        .build();
+    let uri = format!("/api/v1/user/{}/guardians", wallet.pubkey());
+    let req = json!({
+        "guardians": [other_wallet.pubkey().to_string()],
+        "threshold": 1,
+    });
+
+    // Without a token
+    let response = test_server.post(&uri).json(&req).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Only the owner can set the guardians
+    let access_token = common::generate_access_token(&auth_secret, &other_wallet.pubkey());
+    let response = test_server
+        .post(&uri)
+        .add_header("Authorization", format!("Bearer {}", access_token))
+        .json(&req)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_recovery_unauthorized() {
+    let wallet = Keypair::new();
+    let new_wallet = Keypair::new();
+    let test_server = common::TestServerBuilder::new().build();
+
+    // Guardians need a token to propose, approve or cancel a recovery
+    for (path, req) in [
+        (
+            "recovery",
+            json!({"new_pubkey": new_wallet.pubkey().to_string()}),
+        ),
+        ("recovery/approve", json!({})),
+        ("recovery/cancel", json!({})),
+    ] {
+        let response = test_server
+            .post(&format!("/api/v1/user/{}/{}", wallet.pubkey(), path))
+            .json(&req)
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_execute_recovery_invalid_pubkey() {
+    let wallet = Keypair::new();
+    let test_server = common::TestServerBuilder::new().build();
+
+    // The new wallet needs no token, but a valid pubkey
+    let response = test_server
+        .post(&format!(
+            "/api/v1/user/{}/recovery/execute",
+            wallet.pubkey()
+        ))
+        .json(&json!({
+            "new_pubkey": "not a pubkey",
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn test_execute_recovery_complete_not_prepared() {
+    let wallet = Keypair::new();
+    let new_wallet = Keypair::new();
+    let user_repo = repo::user::Repo::new();
+    user_repo.add_user(confirmed_user(&wallet)).unwrap();
+    let test_server = common::TestServerBuilder::new()
+        .with_user_repo(user_repo)
+        .build();
+    let transaction = utils::bincode::serialize(&Transaction::default()).unwrap();
+
+    // Only a transaction executing the recovery completes it
+    let response = test_server
+        .post(&format!(
+            "/api/v1/user/{}/recovery/execute/complete",
+            wallet.pubkey()
+        ))
+        .json(&json!({
+            "data": {
+                "transaction": transaction,
+                "transaction_id": uuid::Uuid::new_v4().to_string(),
+            },
+            "new_pubkey": new_wallet.pubkey().to_string(),
+        }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}